@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// QMK-inspired keycode enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
 pub enum KeyCode {
     // Letters
@@ -183,6 +183,98 @@ pub enum KeyCode {
     KC_INTL_RO,
 }
 
+impl KeyCode {
+    /// Whether this is a modifier key
+    #[must_use]
+    pub const fn is_modifier(self) -> bool {
+        self.unsided_modifier().is_some()
+    }
+
+    /// Left-hand equivalent of a modifier (for side-insensitive matching)
+    /// Returns None for non-modifier keys
+    #[must_use]
+    pub const fn unsided_modifier(self) -> Option<Self> {
+        match self {
+            Self::KC_LCTL | Self::KC_RCTL => Some(Self::KC_LCTL),
+            Self::KC_LSFT | Self::KC_RSFT => Some(Self::KC_LSFT),
+            Self::KC_LALT | Self::KC_RALT => Some(Self::KC_LALT),
+            Self::KC_LGUI | Self::KC_RGUI | Self::KC_LCMD | Self::KC_RCMD => Some(Self::KC_LGUI),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a letter key (KC_A..KC_Z)
+    #[must_use]
+    pub const fn is_letter(self) -> bool {
+        matches!(
+            self,
+            Self::KC_A
+                | Self::KC_B
+                | Self::KC_C
+                | Self::KC_D
+                | Self::KC_E
+                | Self::KC_F
+                | Self::KC_G
+                | Self::KC_H
+                | Self::KC_I
+                | Self::KC_J
+                | Self::KC_K
+                | Self::KC_L
+                | Self::KC_M
+                | Self::KC_N
+                | Self::KC_O
+                | Self::KC_P
+                | Self::KC_Q
+                | Self::KC_R
+                | Self::KC_S
+                | Self::KC_T
+                | Self::KC_U
+                | Self::KC_V
+                | Self::KC_W
+                | Self::KC_X
+                | Self::KC_Y
+                | Self::KC_Z
+        )
+    }
+
+    /// Whether this is a punctuation or symbol key (KC_MINS, KC_SLSH, ...)
+    #[must_use]
+    pub const fn is_symbol(self) -> bool {
+        matches!(
+            self,
+            Self::KC_MINS
+                | Self::KC_EQL
+                | Self::KC_LBRC
+                | Self::KC_RBRC
+                | Self::KC_BSLS
+                | Self::KC_SCLN
+                | Self::KC_QUOT
+                | Self::KC_GRV
+                | Self::KC_COMM
+                | Self::KC_DOT
+                | Self::KC_SLSH
+        )
+    }
+
+    /// Whether this is a number row key (KC_1..KC_0)
+    #[must_use]
+    pub const fn is_digit(self) -> bool {
+        matches!(
+            self,
+            Self::KC_1
+                | Self::KC_2
+                | Self::KC_3
+                | Self::KC_4
+                | Self::KC_5
+                | Self::KC_6
+                | Self::KC_7
+                | Self::KC_8
+                | Self::KC_9
+                | Self::KC_0
+        )
+    }
+}
+
 /// Layer identifier - fully generic string-based layers
 /// "base" and "game_mode" are reserved layer names
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
@@ -249,9 +341,30 @@ pub enum Action {
     /// Supports: permissive hold, roll detection, chord detection, adaptive timing
     /// Now fully recursive - can nest any actions!
     /// Example: MT(Key(KC_TAB), TO("nav")) - tap for Tab, hold for nav layer
-    MT(Box<Action>, Box<Action>),
-    /// Switch to layer
+    /// Optional per-key options: MT(KC_A, KC_LGUI, (tapping_term_ms: 220, permissive_hold: false))
+    MT(
+        Box<Action>,
+        Box<Action>,
+        #[serde(default, skip_serializing_if = "KeyOptions::is_empty")] KeyOptions,
+    ),
+    /// Switch to layer while held (same as MO, kept for existing configs)
     TO(Layer),
+    /// Momentary layer - active while held
+    /// Example: MO("nav")
+    MO(Layer),
+    /// Toggle layer - tap to turn on, tap again to turn off
+    /// Example: TG("num")
+    TG(Layer),
+    /// Tap-toggle layer - momentary while held, a quick tap toggles it
+    /// Example: TT("num")
+    TT(Layer),
+    /// One-shot layer - tap to activate for the next key press only, hold for momentary
+    /// Expires after oneshot_timeout_ms
+    /// Example: OSL("sym")
+    OSL(Layer),
+    /// Set the default layer (the bottom of the layer stack)
+    /// Example: DF("colemak"), DF("base") to go back
+    DF(Layer),
     /// SOCD (Simultaneous Opposite Cardinal Direction) - fully generic
     /// When this key is pressed, unpress all opposing keys
     /// Format: SOCD(this_action, [opposing_actions...])
@@ -261,15 +374,589 @@ pub enum Action {
     /// Perfect for typing capital letters without holding shift
     /// Format: OSM(modifier_action)
     /// Example: OSM(Key(KC_LSFT)) - tap for one-shot shift
-    OSM(Box<Action>),
+    /// Optional per-key options: OSM(KC_LSFT, (oneshot_timeout_ms: 2000))
+    OSM(
+        Box<Action>,
+        #[serde(default, skip_serializing_if = "KeyOptions::is_empty")] KeyOptions,
+    ),
     /// Double-Tap action (QMK-style tap dance)
     /// Single tap: performs first action, Double tap: performs second action
     /// Format: DT(single_tap_action, double_tap_action)
     /// Example: DT(Key(KC_LALT), TO("nav")) - single tap for alt, double tap for nav layer
-    DT(Box<Action>, Box<Action>),
+    /// Optional per-key options: DT(KC_LALT, TO("nav"), (double_tap_window_ms: 300))
+    DT(
+        Box<Action>,
+        Box<Action>,
+        #[serde(default, skip_serializing_if = "KeyOptions::is_empty")] KeyOptions,
+    ),
+    /// Multi-tap tap dance - one step per tap count, each with its own tap and hold action
+    /// Format: TD([(tap: action, hold: Some(action)), ...])
+    /// Example: TD([(tap: KC_LBRC), (tap: KC_RBRC), (tap: KC_LPRN, hold: Some(KC_RPRN))])
+    ///   1 tap = [, 2 taps = ], 3 taps = (, tap twice then hold = )
+    TD(Vec<TapDanceStep>),
     /// Run arbitrary shell command
     /// Example: CMD("/usr/bin/notify-send 'Hello'")
     CMD(String),
+    /// Type a string
+    /// Example: STR("user@example.com")
+    STR(String),
+    /// Macro - key presses, releases, taps and delays played in order
+    /// Example: SEQ([Down(KC_LCTL), Tap(KC_C), Up(KC_LCTL), Delay(20)])
+    SEQ(Vec<MacroStep>),
+    /// Modifier-wrapped actions - hold the modifier(s) around the wrapped action
+    /// Can be nested and used anywhere an action is accepted
+    /// Example: LCTL(KC_C) - Ctrl+C, LSFT(LALT(KC_T)) - Shift+Alt+T
+    LCTL(Box<Action>),
+    LSFT(Box<Action>),
+    LALT(Box<Action>),
+    LGUI(Box<Action>),
+    RCTL(Box<Action>),
+    RSFT(Box<Action>),
+    RALT(Box<Action>),
+    RGUI(Box<Action>),
+    /// Hyper - Ctrl+Shift+Alt+GUI around the wrapped action
+    HYPR(Box<Action>),
+    /// Meh - Ctrl+Shift+Alt around the wrapped action
+    MEH(Box<Action>),
+    /// Transparent - fall through to the next active layer below
+    /// Written as KC_TRNS in configs
+    TRNS,
+    /// No-op - the key does nothing on this layer
+    /// Written as KC_NO in configs
+    NO,
+    /// Leader key - the next keys are matched against leader_sequences
+    LEADER,
+    /// Caps Word - shift letters until the end of the word
+    #[allow(non_camel_case_types)]
+    CAPS_WORD,
+    /// Repeat the last key sent, with the modifiers it was sent with
+    REPEAT,
+    /// Send the alternate of the last key sent (alt_repeat_keys), e.g. Left after Right
+    #[allow(non_camel_case_types)]
+    ALT_REPEAT,
+    /// Mouse key - move the cursor, scroll or click
+    /// Example: MOUSE(MS_LEFT), MOUSE(MS_WHLD), MOUSE(MS_BTN1)
+    MOUSE(MouseAction),
+}
+
+/// One step of a SEQ macro
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MacroStep {
+    /// Press a key
+    Down(KeyCode),
+    /// Release a key
+    Up(KeyCode),
+    /// Press and release a key
+    Tap(KeyCode),
+    /// Wait (milliseconds)
+    Delay(u64),
+}
+
+/// Per-key options for MT, DT and OSM actions
+/// Unset options fall back to the global (or per-keyboard) settings.
+/// Values are written bare: `(tapping_term_ms: 220)`, not `Some(220)`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyOptions {
+    /// Tapping term for this key (MT, DT, OSM)
+    /// With adaptive timing, the key's learned threshold starts from here
+    #[serde(with = "bare_option", skip_serializing_if = "Option::is_none")]
+    pub tapping_term_ms: Option<u32>,
+    /// Permissive hold for this key (MT)
+    #[serde(with = "bare_option", skip_serializing_if = "Option::is_none")]
+    pub permissive_hold: Option<bool>,
+    /// Emit the tap when held past the term without another key (MT)
+    #[serde(with = "bare_option", skip_serializing_if = "Option::is_none")]
+    pub hold_do_nothing_emits_tap: Option<bool>,
+    /// Double-tap then hold to hold the tap key (MT)
+    #[serde(with = "bare_option", skip_serializing_if = "Option::is_none")]
+    pub double_tap_then_hold: Option<bool>,
+    /// Window for the second tap (MT double-tap-then-hold, DT)
+    #[serde(with = "bare_option", skip_serializing_if = "Option::is_none")]
+    pub double_tap_window_ms: Option<u32>,
+    /// One-shot timeout for this key (OSM)
+    #[serde(with = "bare_option", skip_serializing_if = "Option::is_none")]
+    pub oneshot_timeout_ms: Option<u64>,
+    /// Require-prior-idle window for this key, 0 turns it off (MT)
+    #[serde(with = "bare_option", skip_serializing_if = "Option::is_none")]
+    pub require_prior_idle_ms: Option<u32>,
+}
+
+impl KeyOptions {
+    /// Whether no option is set
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// Options are written without Some(..), unset ones are left out
+mod bare_option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        match value {
+            Some(value) => value.serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        T::deserialize(deserializer).map(Some)
+    }
+}
+
+// Minimal RON text scanning for in-place edits (strings and comments are skipped)
+mod ron_text {
+    use std::ops::Range;
+
+    /// Code characters of `content[range]` with their nesting depth within the range
+    /// Brackets are at the depth outside them
+    fn code_chars(content: &str, range: Range<usize>) -> Vec<(usize, u8, usize)> {
+        let bytes = content.as_bytes();
+        let mut chars = Vec::new();
+        let mut depth = 0usize;
+        let mut i = range.start;
+        while i < range.end {
+            let byte = bytes[i];
+            let next = bytes.get(i + 1).copied();
+            match byte {
+                b'"' => {
+                    i += 1;
+                    while i < range.end && bytes[i] != b'"' {
+                        i += if bytes[i] == b'\\' { 2 } else { 1 };
+                    }
+                }
+                b'/' if next == Some(b'/') => {
+                    while i < range.end && bytes[i] != b'\n' {
+                        i += 1;
+                    }
+                }
+                b'/' if next == Some(b'*') => {
+                    i += 2;
+                    while i < range.end && !bytes[i..].starts_with(b"*/") {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                b'(' | b'[' | b'{' => {
+                    chars.push((i, byte, depth));
+                    depth += 1;
+                }
+                b')' | b']' | b'}' => {
+                    depth = depth.saturating_sub(1);
+                    chars.push((i, byte, depth));
+                }
+                _ => chars.push((i, byte, depth)),
+            }
+            i += 1;
+        }
+        chars
+    }
+
+    const fn is_ident(byte: u8) -> bool {
+        byte.is_ascii_alphanumeric() || byte == b'_'
+    }
+
+    /// Whether `name` starts at `i` as a whole identifier
+    /// Compares bytes, `i` may be inside a multi-byte character
+    fn ident_at(content: &str, i: usize, name: &str) -> bool {
+        let bytes = content.as_bytes();
+        bytes[i..].starts_with(name.as_bytes())
+            && (i == 0 || !is_ident(bytes[i - 1]))
+            && bytes
+                .get(i + name.len())
+                .is_none_or(|&byte| !is_ident(byte))
+    }
+
+    /// Position after `expected`, skipping whitespace before it
+    fn skip_to_after(content: &str, i: usize, expected: &str) -> Option<usize> {
+        let rest = &content.as_bytes()[i..];
+        let trimmed = rest.trim_ascii_start();
+        trimmed
+            .starts_with(expected.as_bytes())
+            .then(|| i + (rest.len() - trimmed.len()) + expected.len())
+    }
+
+    /// Closing bracket matching the one at `open`
+    fn matching_close(content: &str, open: usize) -> Option<usize> {
+        code_chars(content, open..content.len())
+            .into_iter()
+            .skip(1)
+            .find(|&(_, byte, depth)| depth == 0 && matches!(byte, b')' | b']' | b'}'))
+            .map(|(i, _, _)| i)
+    }
+
+    /// Inside of the struct whose `(` is the first code character from `start`
+    pub fn struct_body(content: &str, start: usize) -> Option<Range<usize>> {
+        let (open, byte, _) = *code_chars(content, start..content.len()).first()?;
+        if byte != b'(' {
+            return None;
+        }
+        Some(open + 1..matching_close(content, open)?)
+    }
+
+    /// Inside of the struct at `start`, which may be wrapped in `Some(...)`
+    pub fn option_struct_body(content: &str, start: usize) -> Option<Range<usize>> {
+        let (first, _, _) = *code_chars(content, start..content.len()).first()?;
+        if !ident_at(content, first, "Some") {
+            return struct_body(content, first);
+        }
+        let option = struct_body(content, first + "Some".len())?;
+        struct_body(content, option.start)
+    }
+
+    /// Value of the entry with string key `key` in the map whose `{` is the first code
+    /// character from `start`
+    pub fn map_entry(content: &str, start: usize, key: &str) -> Option<Range<usize>> {
+        let (open, byte, _) = *code_chars(content, start..content.len()).first()?;
+        if byte != b'{' {
+            return None;
+        }
+        let body = open + 1..matching_close(content, open)?;
+        let quoted = format!("{key:?}");
+
+        let chars = code_chars(content, body.clone());
+        let value_start = chars.iter().find_map(|&(i, byte, depth)| {
+            (depth == 0 && byte == b':' && content[..i].trim_end().ends_with(&quoted))
+                .then_some(i + 1)
+        })?;
+        let value_end = chars
+            .iter()
+            .find(|&&(i, byte, depth)| i >= value_start && depth == 0 && byte == b',')
+            .map_or(body.end, |&(i, _, _)| i);
+        Some(trim(content, value_start..value_end))
+    }
+
+    /// Trim whitespace off a range
+    fn trim(content: &str, range: Range<usize>) -> Range<usize> {
+        let text = &content[range.clone()];
+        let start = range.start + (text.len() - text.trim_start().len());
+        let end = range.end - (text.len() - text.trim_end().len());
+        start..end.max(start)
+    }
+
+    /// Value of the field `name` in a struct body
+    pub fn field_value(content: &str, body: Range<usize>, name: &str) -> Option<Range<usize>> {
+        let chars = code_chars(content, body.clone());
+        let value_start = chars.iter().find_map(|&(i, _, depth)| {
+            (depth == 0 && ident_at(content, i, name))
+                .then(|| skip_to_after(content, i + name.len(), ":"))
+                .flatten()
+        })?;
+        let value_end = chars
+            .iter()
+            .find(|&&(i, byte, depth)| i >= value_start && depth == 0 && byte == b',')
+            .map_or(body.end, |&(i, _, _)| i);
+        Some(trim(content, value_start..value_end))
+    }
+
+    /// Set the field `name` in a struct body, adding it if missing
+    pub fn set_field(content: &str, body: Range<usize>, name: &str, value: &str) -> String {
+        if let Some(range) = field_value(content, body.clone(), name) {
+            return format!(
+                "{}{value}{}",
+                &content[..range.start],
+                &content[range.end..]
+            );
+        }
+
+        let inner = &content[body.clone()];
+        let insert = if inner.trim().is_empty() {
+            format!("{name}: {value}")
+        } else if inner.contains('\n') {
+            let indent: String = inner
+                .lines()
+                .skip(1)
+                .find(|line| !line.trim().is_empty())
+                .map(|line| line.chars().take_while(|c| c.is_whitespace()).collect())
+                .unwrap_or_else(|| "    ".to_string());
+            format!("\n{indent}{name}: {value},")
+        } else {
+            format!("{name}: {value}, ")
+        };
+        format!(
+            "{}{insert}{}",
+            &content[..body.start],
+            &content[body.start..]
+        )
+    }
+
+    /// Positions of the `(` of every `KEY: MT(...)` entry
+    pub fn mt_actions(content: &str, key: &str) -> Vec<usize> {
+        code_chars(content, 0..content.len())
+            .into_iter()
+            .filter(|&(i, _, _)| ident_at(content, i, key))
+            .filter_map(|(i, _, _)| {
+                let after_colon = skip_to_after(content, i + key.len(), ":")?;
+                let after_mt = skip_to_after(content, after_colon, "MT")?;
+                let after_open = skip_to_after(content, after_mt, "(")?;
+                Some(after_open - 1)
+            })
+            .collect()
+    }
+
+    /// Set a per-key option of the `MT(...)` whose `(` is at `open`
+    pub fn set_mt_option(content: &str, open: usize, name: &str, value: &str) -> Option<String> {
+        let close = matching_close(content, open)?;
+        let mut args = Vec::new();
+        let mut arg_start = open + 1;
+        for (i, byte, depth) in code_chars(content, open + 1..close) {
+            if depth == 0 && byte == b',' {
+                args.push(trim(content, arg_start..i));
+                arg_start = i + 1;
+            }
+        }
+        args.push(trim(content, arg_start..close));
+        args.retain(|arg| !arg.is_empty());
+
+        match args.as_slice() {
+            [_, second] => Some(format!(
+                "{}, ({name}: {value}){}",
+                &content[..second.end],
+                &content[second.end..]
+            )),
+            [_, _, options] => {
+                let body = struct_body(content, options.start)?;
+                Some(set_field(content, body, name, value))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// One step of a tap dance (TD) - what happens for a given tap count
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TapDanceStep {
+    /// Action when the dance ends on this tap count
+    pub tap: Action,
+    /// Action while held on this tap count (default: hold the tap action)
+    #[serde(default)]
+    pub hold: Option<Action>,
+}
+
+impl Action {
+    /// Modifiers held around a modifier-wrapped action (empty for other actions)
+    #[must_use]
+    pub fn wrap_modifiers(&self) -> &'static [KeyCode] {
+        match self {
+            Self::LCTL(_) => &[KeyCode::KC_LCTL],
+            Self::LSFT(_) => &[KeyCode::KC_LSFT],
+            Self::LALT(_) => &[KeyCode::KC_LALT],
+            Self::LGUI(_) => &[KeyCode::KC_LGUI],
+            Self::RCTL(_) => &[KeyCode::KC_RCTL],
+            Self::RSFT(_) => &[KeyCode::KC_RSFT],
+            Self::RALT(_) => &[KeyCode::KC_RALT],
+            Self::RGUI(_) => &[KeyCode::KC_RGUI],
+            Self::HYPR(_) => &[
+                KeyCode::KC_LCTL,
+                KeyCode::KC_LSFT,
+                KeyCode::KC_LALT,
+                KeyCode::KC_LGUI,
+            ],
+            Self::MEH(_) => &[KeyCode::KC_LCTL, KeyCode::KC_LSFT, KeyCode::KC_LALT],
+            _ => &[],
+        }
+    }
+
+    /// Wrap this action in modifier-wrapped actions for the given modifiers
+    /// Example: Key(KC_Z) with [KC_LCTL] → LCTL(Key(KC_Z))
+    #[must_use]
+    pub fn with_modifiers(self, mods: &[KeyCode]) -> Self {
+        mods.iter().rev().fold(self, |inner, modifier| {
+            let inner = Box::new(inner);
+            match modifier {
+                KeyCode::KC_LCTL => Self::LCTL(inner),
+                KeyCode::KC_LSFT => Self::LSFT(inner),
+                KeyCode::KC_LALT => Self::LALT(inner),
+                KeyCode::KC_LGUI | KeyCode::KC_LCMD => Self::LGUI(inner),
+                KeyCode::KC_RCTL => Self::RCTL(inner),
+                KeyCode::KC_RSFT => Self::RSFT(inner),
+                KeyCode::KC_RALT => Self::RALT(inner),
+                KeyCode::KC_RGUI | KeyCode::KC_RCMD => Self::RGUI(inner),
+                _ => *inner,
+            }
+        })
+    }
+
+    /// All layers referenced by this action, including nested actions
+    #[must_use]
+    pub fn referenced_layers(&self) -> Vec<&Layer> {
+        match self {
+            Self::TO(layer)
+            | Self::MO(layer)
+            | Self::TG(layer)
+            | Self::TT(layer)
+            | Self::OSL(layer)
+            | Self::DF(layer) => vec![layer],
+            Self::MT(first, second, _) | Self::DT(first, second, _) => {
+                let mut layers = first.referenced_layers();
+                layers.extend(second.referenced_layers());
+                layers
+            }
+            Self::SOCD(this_action, opposing_actions) => {
+                let mut layers = this_action.referenced_layers();
+                for action in opposing_actions {
+                    layers.extend(action.referenced_layers());
+                }
+                layers
+            }
+            Self::OSM(action, _)
+            | Self::LCTL(action)
+            | Self::LSFT(action)
+            | Self::LALT(action)
+            | Self::LGUI(action)
+            | Self::RCTL(action)
+            | Self::RSFT(action)
+            | Self::RALT(action)
+            | Self::RGUI(action)
+            | Self::HYPR(action)
+            | Self::MEH(action) => action.referenced_layers(),
+            Self::TD(steps) => steps
+                .iter()
+                .flat_map(|step| {
+                    let mut layers = step.tap.referenced_layers();
+                    if let Some(hold) = &step.hold {
+                        layers.extend(hold.referenced_layers());
+                    }
+                    layers
+                })
+                .collect(),
+            Self::Key(_)
+            | Self::CMD(_)
+            | Self::STR(_)
+            | Self::SEQ(_)
+            | Self::TRNS
+            | Self::NO
+            | Self::LEADER
+            | Self::CAPS_WORD
+            | Self::REPEAT
+            | Self::ALT_REPEAT
+            | Self::MOUSE(_) => Vec::new(),
+        }
+    }
+
+    /// Per-key options of this action and its nested actions, with the action name
+    #[must_use]
+    pub fn key_options(&self) -> Vec<(&'static str, &KeyOptions)> {
+        match self {
+            Self::MT(first, second, options) => std::iter::once(("MT", options))
+                .chain(first.key_options())
+                .chain(second.key_options())
+                .collect(),
+            Self::DT(first, second, options) => std::iter::once(("DT", options))
+                .chain(first.key_options())
+                .chain(second.key_options())
+                .collect(),
+            Self::OSM(action, options) => std::iter::once(("OSM", options))
+                .chain(action.key_options())
+                .collect(),
+            Self::SOCD(this_action, opposing_actions) => {
+                let mut all = this_action.key_options();
+                for action in opposing_actions {
+                    all.extend(action.key_options());
+                }
+                all
+            }
+            Self::LCTL(action)
+            | Self::LSFT(action)
+            | Self::LALT(action)
+            | Self::LGUI(action)
+            | Self::RCTL(action)
+            | Self::RSFT(action)
+            | Self::RALT(action)
+            | Self::RGUI(action)
+            | Self::HYPR(action)
+            | Self::MEH(action) => action.key_options(),
+            Self::TD(steps) => steps
+                .iter()
+                .flat_map(|step| {
+                    let mut all = step.tap.key_options();
+                    if let Some(hold) = &step.hold {
+                        all.extend(hold.key_options());
+                    }
+                    all
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether this action or a nested action is a MOUSE action
+    #[must_use]
+    pub fn has_mouse_action(&self) -> bool {
+        match self {
+            Self::MOUSE(_) => true,
+            Self::TD(steps) => steps.iter().any(|step| {
+                step.tap.has_mouse_action()
+                    || step.hold.as_ref().is_some_and(Self::has_mouse_action)
+            }),
+            Self::MT(first, second, _) | Self::DT(first, second, _) => {
+                first.has_mouse_action() || second.has_mouse_action()
+            }
+            Self::SOCD(this_action, opposing_actions) => {
+                this_action.has_mouse_action()
+                    || opposing_actions
+                        .iter()
+                        .any(|action| action.has_mouse_action())
+            }
+            Self::OSM(action, _)
+            | Self::LCTL(action)
+            | Self::LSFT(action)
+            | Self::LALT(action)
+            | Self::LGUI(action)
+            | Self::RCTL(action)
+            | Self::RSFT(action)
+            | Self::RALT(action)
+            | Self::RGUI(action)
+            | Self::HYPR(action)
+            | Self::MEH(action) => action.has_mouse_action(),
+            _ => false,
+        }
+    }
+
+    /// Whether this action or a nested action is a tap dance without steps
+    #[must_use]
+    pub fn has_empty_tap_dance(&self) -> bool {
+        match self {
+            Self::TD(steps) => {
+                steps.is_empty()
+                    || steps.iter().any(|step| {
+                        step.tap.has_empty_tap_dance()
+                            || step.hold.as_ref().is_some_and(Self::has_empty_tap_dance)
+                    })
+            }
+            Self::MT(first, second, _) | Self::DT(first, second, _) => {
+                first.has_empty_tap_dance() || second.has_empty_tap_dance()
+            }
+            Self::SOCD(this_action, opposing_actions) => {
+                this_action.has_empty_tap_dance()
+                    || opposing_actions
+                        .iter()
+                        .any(|action| action.has_empty_tap_dance())
+            }
+            Self::OSM(action, _)
+            | Self::LCTL(action)
+            | Self::LSFT(action)
+            | Self::LALT(action)
+            | Self::LGUI(action)
+            | Self::RCTL(action)
+            | Self::RSFT(action)
+            | Self::RALT(action)
+            | Self::RGUI(action)
+            | Self::HYPR(action)
+            | Self::MEH(action) => action.has_empty_tap_dance(),
+            _ => false,
+        }
+    }
 }
 
 /// Game mode detection methods
@@ -287,6 +974,128 @@ pub struct LayerConfig {
     pub remaps: HashMap<KeyCode, Action>,
 }
 
+/// Combo - keys pressed together within the combo term trigger an action
+/// Example: (keys: [KC_J, KC_K], action: KC_ESC)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Combo {
+    /// Keys that must be pressed together
+    #[serde(deserialize_with = "deserialize_plain_keys")]
+    pub keys: Vec<KeyCode>,
+    /// Action to run when the combo triggers
+    pub action: Action,
+    /// Only enable the combo while one of these layers is active (default: all layers)
+    #[serde(default)]
+    pub layers: Option<Vec<Layer>>,
+    /// Override the global combo_term_ms for this combo
+    #[serde(default)]
+    pub term_ms: Option<u64>,
+}
+
+/// Key override - replace a key while modifiers are held
+/// Example: (mods: [KC_LSFT], key: KC_BSPC, replacement: KC_DEL) - Shift+Backspace = Delete
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyOverride {
+    /// Modifiers that must be held (either side matches, KC_LSFT also matches KC_RSFT)
+    #[serde(deserialize_with = "deserialize_plain_keys")]
+    pub mods: Vec<KeyCode>,
+    /// Key that triggers the override (after layer lookup)
+    #[serde(deserialize_with = "deserialize_plain_key")]
+    pub key: KeyCode,
+    /// Action sent instead, with the trigger modifiers released
+    pub replacement: Action,
+    /// Only enable the override while one of these layers is active (default: all layers)
+    #[serde(default)]
+    pub layers: Option<Vec<Layer>>,
+}
+
+/// Alternate key for ALT_REPEAT
+/// Example: (key: KC_Z, mods: [KC_LCTL], alt: KC_Y) - ALT_REPEAT after Ctrl+Z sends Ctrl+Y
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AltRepeatKey {
+    /// Last key sent
+    #[serde(deserialize_with = "deserialize_plain_key")]
+    pub key: KeyCode,
+    /// Only match if these modifiers were held with it (either side, default: any)
+    #[serde(default, deserialize_with = "deserialize_plain_keys")]
+    pub mods: Vec<KeyCode>,
+    /// Key sent instead, with the same modifiers
+    #[serde(deserialize_with = "deserialize_plain_key")]
+    pub alt: KeyCode,
+}
+
+impl AltRepeatKey {
+    fn new(key: KeyCode, mods: &[KeyCode], alt: KeyCode) -> Self {
+        Self {
+            key,
+            mods: mods.to_vec(),
+            alt,
+        }
+    }
+}
+
+fn default_alt_repeat_keys() -> Vec<AltRepeatKey> {
+    use KeyCode::*;
+    vec![
+        AltRepeatKey::new(KC_LEFT, &[], KC_RGHT),
+        AltRepeatKey::new(KC_RGHT, &[], KC_LEFT),
+        AltRepeatKey::new(KC_UP, &[], KC_DOWN),
+        AltRepeatKey::new(KC_DOWN, &[], KC_UP),
+        AltRepeatKey::new(KC_HOME, &[], KC_END),
+        AltRepeatKey::new(KC_END, &[], KC_HOME),
+        AltRepeatKey::new(KC_PGUP, &[], KC_PGDN),
+        AltRepeatKey::new(KC_PGDN, &[], KC_PGUP),
+        AltRepeatKey::new(KC_Z, &[KC_LCTL], KC_Y),
+        AltRepeatKey::new(KC_Y, &[KC_LCTL], KC_Z),
+    ]
+}
+
+/// Conditional layer - activated while all of its condition layers are active
+/// Example: (when: ["lower", "raise"], then: "adjust") - the tri-layer setup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConditionalLayer {
+    /// Layers that must all be active
+    pub when: Vec<Layer>,
+    /// Layer activated on top of them
+    pub then: Layer,
+}
+
+/// Leader sequence - keys typed after LEADER that trigger an action
+/// Example: (keys: [KC_G, KC_S], action: CMD("git status"))
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderSequence {
+    /// Physical keys typed after the leader key, in order
+    #[serde(deserialize_with = "deserialize_plain_keys")]
+    pub keys: Vec<KeyCode>,
+    /// Action to run when the sequence is typed
+    pub action: Action,
+}
+
+// Plain key fields go through the preprocessor like everything else, so accept
+// both KC_J and Key(KC_J)
+fn plain_key<E: serde::de::Error>(action: Action) -> Result<KeyCode, E> {
+    match action {
+        Action::Key(keycode) => Ok(keycode),
+        other => Err(E::custom(format!("expected a plain key, got {:?}", other))),
+    }
+}
+
+fn deserialize_plain_key<'de, D>(deserializer: D) -> Result<KeyCode, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    plain_key(Action::deserialize(deserializer)?)
+}
+
+fn deserialize_plain_keys<'de, D>(deserializer: D) -> Result<Vec<KeyCode>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<Action>::deserialize(deserializer)?
+        .into_iter()
+        .map(plain_key)
+        .collect()
+}
+
 /// Game mode configuration
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct GameMode {
@@ -325,9 +1134,22 @@ pub struct PerKeyboardConfig {
     pub mt_config: Option<MtConfig>,
     pub double_tap_window_ms: Option<u64>,
     pub oneshot_timeout_ms: Option<u64>,
+    pub combo_term_ms: Option<u64>,
     pub remaps: Option<HashMap<KeyCode, Action>>,
     pub layers: Option<HashMap<Layer, LayerConfig>>,
     pub game_mode: Option<GameMode>,
+    pub combos: Option<Vec<Combo>>,
+    pub key_overrides: Option<Vec<KeyOverride>>,
+    pub conditional_layers: Option<Vec<ConditionalLayer>>,
+    pub leader_sequences: Option<Vec<LeaderSequence>>,
+    pub leader_timeout_ms: Option<u64>,
+    pub leader_replay_unmatched: Option<bool>,
+    pub caps_word: Option<CapsWordConfig>,
+    pub auto_shift_groups: Option<Vec<AutoShiftGroup>>,
+    pub auto_shift_timeout_ms: Option<u64>,
+    pub alt_repeat_keys: Option<Vec<AltRepeatKey>>,
+    pub mouse_keys: Option<MouseKeysConfig>,
+    pub hands: Option<HashMap<KeyCode, Hand>>,
 }
 
 /// MT (Mod-Tap) configuration
@@ -403,10 +1225,84 @@ pub struct MtConfig {
     pub auto_save_interval_secs: u32,
 
     /// When holding an MT key and doing nothing, emit tap on release (default: true)
-    /// If true, holding then releasing without other action sends the tap key
+    /// If true, a key released past the term before its hold was pressed sends the tap
+    /// key. Once the hold is pressed at the term, release only releases it (use the
+    /// RetroTap flavor to send the tap then too)
     /// If false, holding then releasing without other action does nothing
     #[serde(default = "default_true")]
     pub hold_do_nothing_emits_tap: bool,
+
+    /// How MT keys decide between tap and hold (default: Heuristic)
+    #[serde(default)]
+    pub flavor: MtFlavor,
+
+    /// Require-prior-idle / flow tap (default: 0 = off)
+    /// An MT key pressed within this many ms of a non-modifier key press resolves
+    /// to tap right away, so home row mods don't misfire while typing
+    #[serde(default)]
+    pub require_prior_idle_ms: u32,
+}
+
+/// MT hold-tap flavor (QMK/ZMK-style) - how an MT key decides between tap and hold
+///
+/// "Term" is the key's tapping term (its adaptive threshold with adaptive timing).
+/// A key released before the term with no other key pressed is a tap in every flavor.
+/// The precise flavors ignore the heuristic options (permissive_hold, roll/chord/multi-mod
+/// detection, predictive scoring, hold_do_nothing_emits_tap). Cross-hand unwrap and
+/// double-tap-then-hold still apply, as they are decided when the key is pressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MtFlavor {
+    /// keymux heuristics: permissive hold, roll/chord/multi-mod detection, predictive
+    /// scoring, hold at the term and hold_do_nothing_emits_tap
+    #[default]
+    Heuristic,
+    /// Hold as soon as another key is pressed, or at the term
+    HoldPreferred,
+    /// Hold when another key is pressed and released while the MT key is held, or at
+    /// the term. Tap if the MT key is released first (a roll). Keys pressed meanwhile
+    /// are held back until the MT key is decided
+    Balanced,
+    /// Hold only at the term, other keys don't matter. Keys pressed meanwhile are held
+    /// back until the MT key is decided
+    TapPreferred,
+    /// Hold only when another key is pressed before the term. Reaching the term without
+    /// one presses the tap (held until release)
+    TapUnlessInterrupted,
+    /// Like HoldPreferred, but a hold released without another key pressed meanwhile
+    /// sends the tap (after the hold is released), even past the term. A lone Alt/GUI
+    /// hold is neutralized with a Right Ctrl tap before it's released
+    RetroTap,
+}
+
+/// Hand a physical key is typed with (hands section)
+/// Used by roll/chord/multi-mod detection and cross-hand unwrap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Hand {
+    Left,
+    Right,
+    /// Thumb cluster key - not part of rolls or chords, either hand for unwrap
+    Thumb,
+    /// No hand - left out of all hand-based detection
+    Unknown,
+}
+
+impl Hand {
+    /// Whether this is the left or right hand (thumbs and unknown keys are neither)
+    #[must_use]
+    pub const fn is_side(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+
+    /// Whether a key on this hand is on the other hand than a key on `other`
+    /// Thumbs count as either hand
+    #[must_use]
+    pub fn is_opposite(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::Unknown, _) | (_, Self::Unknown) => false,
+            (Self::Thumb, _) | (_, Self::Thumb) => true,
+            _ => self != other,
+        }
+    }
 }
 
 fn default_ema_alpha() -> f32 {
@@ -456,10 +1352,184 @@ impl Default for MtConfig {
             ema_alpha: 0.02,
             auto_save_interval_secs: 30,
             hold_do_nothing_emits_tap: true,
+            flavor: MtFlavor::Heuristic,
+            require_prior_idle_ms: 0,
+        }
+    }
+}
+
+/// Mouse key action (QMK-style names)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum MouseAction {
+    // Cursor movement (accelerates while held)
+    MS_UP,
+    MS_DOWN,
+    MS_LEFT,
+    MS_RGHT,
+
+    // Wheel
+    MS_WHLU,
+    MS_WHLD,
+    MS_WHLL,
+    MS_WHLR,
+
+    // Buttons (left, right, middle, back, forward)
+    MS_BTN1,
+    MS_BTN2,
+    MS_BTN3,
+    MS_BTN4,
+    MS_BTN5,
+}
+
+/// Mouse keys configuration
+/// Cursor speed ramps up linearly from move_delta to max_move_delta over time_to_max_ms
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MouseKeysConfig {
+    /// Time between movement steps while a key is held (default: 16)
+    #[serde(default = "default_mouse_interval")]
+    pub interval_ms: u64,
+
+    /// Cursor movement per step when a key is first pressed (default: 4)
+    #[serde(default = "default_mouse_move_delta")]
+    pub move_delta: u32,
+
+    /// Cursor movement per step at full speed (default: 32)
+    #[serde(default = "default_mouse_max_move_delta")]
+    pub max_move_delta: u32,
+
+    /// Time to reach full speed (default: 800)
+    #[serde(default = "default_mouse_time_to_max")]
+    pub time_to_max_ms: u64,
+
+    /// Time between wheel steps while a wheel key is held (default: 80)
+    #[serde(default = "default_mouse_wheel_interval")]
+    pub wheel_interval_ms: u64,
+
+    /// Wheel clicks per step (default: 1)
+    #[serde(default = "default_mouse_wheel_delta")]
+    pub wheel_delta: u32,
+}
+
+fn default_mouse_interval() -> u64 {
+    16
+}
+
+fn default_mouse_move_delta() -> u32 {
+    4
+}
+
+fn default_mouse_max_move_delta() -> u32 {
+    32
+}
+
+fn default_mouse_time_to_max() -> u64 {
+    800
+}
+
+fn default_mouse_wheel_interval() -> u64 {
+    80
+}
+
+fn default_mouse_wheel_delta() -> u32 {
+    1
+}
+
+impl Default for MouseKeysConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_mouse_interval(),
+            move_delta: default_mouse_move_delta(),
+            max_move_delta: default_mouse_max_move_delta(),
+            time_to_max_ms: default_mouse_time_to_max(),
+            wheel_interval_ms: default_mouse_wheel_interval(),
+            wheel_delta: default_mouse_wheel_delta(),
+        }
+    }
+}
+
+/// Caps Word configuration
+/// Letters are shifted; digits, backspace and delete continue the word unshifted;
+/// any other key ends the word
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapsWordConfig {
+    /// Extra keys typed shifted that continue the word (default: [KC_MINS], types `_`)
+    #[serde(
+        default = "default_caps_word_shifted_keys",
+        deserialize_with = "deserialize_plain_keys"
+    )]
+    pub shifted_keys: Vec<KeyCode>,
+
+    /// Extra keys typed unshifted that continue the word (default: none)
+    /// Example: [KC_MINS] with shifted_keys: [] keeps `-` in the word
+    #[serde(default, deserialize_with = "deserialize_plain_keys")]
+    pub continue_keys: Vec<KeyCode>,
+
+    /// Turn Caps Word off after this long without a key press (default: 5000)
+    #[serde(default = "default_caps_word_idle_timeout")]
+    pub idle_timeout_ms: u64,
+}
+
+fn default_caps_word_shifted_keys() -> Vec<KeyCode> {
+    vec![KeyCode::KC_MINS]
+}
+
+fn default_caps_word_idle_timeout() -> u64 {
+    5000
+}
+
+impl Default for CapsWordConfig {
+    fn default() -> Self {
+        Self {
+            shifted_keys: default_caps_word_shifted_keys(),
+            continue_keys: Vec::new(),
+            idle_timeout_ms: default_caps_word_idle_timeout(),
+        }
+    }
+}
+
+/// Key group that auto-shift can be turned on for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoShiftGroup {
+    /// KC_A..KC_Z
+    Letters,
+    /// KC_1..KC_0
+    Numbers,
+    /// Punctuation and symbol keys (KC_MINS, KC_SLSH, ...)
+    Symbols,
+}
+
+impl AutoShiftGroup {
+    /// Whether a key belongs to this group
+    #[must_use]
+    pub const fn contains(self, keycode: KeyCode) -> bool {
+        match self {
+            Self::Letters => keycode.is_letter(),
+            Self::Numbers => keycode.is_digit(),
+            Self::Symbols => keycode.is_symbol(),
         }
     }
 }
 
+/// An in-place edit of the config file text (see `Config::edit_text`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigEdit {
+    /// Set a top-level field, or a field of a top-level struct such as `mt_config`
+    /// With `keyboard`, in that keyboard's `per_keyboard_overrides` entry instead
+    Field {
+        keyboard: Option<String>,
+        section: Option<&'static str>,
+        field: &'static str,
+        value: String,
+    },
+    /// Set a per-key option on every MT action of a key
+    MtOption {
+        key: KeyCode,
+        field: &'static str,
+        value: String,
+    },
+}
+
 /// Main configuration structure
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Config {
@@ -486,6 +1556,61 @@ pub struct Config {
     /// Default: 5000ms (5 seconds, like QMK)
     pub oneshot_timeout_ms: Option<u64>,
 
+    /// Combos - keys pressed together that trigger an action
+    #[serde(default)]
+    pub combos: Vec<Combo>,
+
+    /// Combo term (milliseconds) - max time between the first and last key of a combo
+    /// Default: 50ms
+    pub combo_term_ms: Option<u64>,
+
+    /// Key overrides - replace a key while modifiers are held
+    #[serde(default)]
+    pub key_overrides: Vec<KeyOverride>,
+
+    /// Conditional layers - layers activated while other layers are all active
+    #[serde(default)]
+    pub conditional_layers: Vec<ConditionalLayer>,
+
+    /// Leader sequences - keys typed after a LEADER key that trigger an action
+    #[serde(default)]
+    pub leader_sequences: Vec<LeaderSequence>,
+
+    /// Leader timeout (milliseconds) - max time between keys of a leader sequence
+    /// Default: 500ms
+    pub leader_timeout_ms: Option<u64>,
+
+    /// Replay the keys of an unmatched leader sequence instead of swallowing them (default: false)
+    #[serde(default)]
+    pub leader_replay_unmatched: bool,
+
+    /// Auto-shift - hold a key past auto_shift_timeout_ms to type it shifted
+    /// Only keys in these groups auto-shift (default: none, auto-shift off)
+    #[serde(default)]
+    pub auto_shift_groups: Vec<AutoShiftGroup>,
+
+    /// Auto-shift timeout (milliseconds) - hold longer than this for the shifted key
+    /// Default: 175ms (like QMK)
+    pub auto_shift_timeout_ms: Option<u64>,
+
+    /// Caps Word settings (CAPS_WORD action)
+    #[serde(default)]
+    pub caps_word: CapsWordConfig,
+
+    /// Mouse keys settings (MOUSE actions)
+    #[serde(default)]
+    pub mouse_keys: MouseKeysConfig,
+
+    /// Alternate keys for ALT_REPEAT (first match wins)
+    /// Default: arrows, Home/End and PgUp/PgDn swap, Ctrl+Z ↔ Ctrl+Y
+    #[serde(default = "default_alt_repeat_keys")]
+    pub alt_repeat_keys: Vec<AltRepeatKey>,
+
+    /// Hand of each physical key, on top of the built-in QWERTY assignment
+    /// For split boards, thumb clusters and non-QWERTY layouts: { KC_SPC: Thumb, KC_Y: Left }
+    #[serde(default)]
+    pub hands: HashMap<KeyCode, Hand>,
+
     /// Enable hot config reload - automatically reload config when file changes (default: false)
     /// When enabled, changes to config.ron are immediately applied without restarting daemon
     #[serde(default)]
@@ -542,15 +1667,27 @@ impl Config {
             // Don't wrap if:
             // 1. It's followed by a colon (HashMap key): `KC_A:`
             // 2. It's preceded by "Key(" (already wrapped)
+            // 3. It's a SEQ macro step: `Down(KC_A)`, `Up(KC_A)`, `Tap(KC_A)`
             let is_hashmap_key = next_char == Some(':');
-            let already_wrapped = prev_trimmed.ends_with("Key(");
+            let already_wrapped = ["Key(", "Down(", "Up(", "Tap("]
+                .iter()
+                .any(|wrapper| prev_trimmed.ends_with(wrapper));
 
             // Wrap if it's after: colon, open paren, comma, or open bracket
             let should_wrap = !is_hashmap_key
                 && !already_wrapped
                 && matches!(prev_char, Some(':') | Some('(') | Some(',') | Some('['));
 
-            if should_wrap {
+            // KC_TRNS and KC_NO are actions of their own, not keycodes
+            let special_action = match keycode {
+                "KC_TRNS" | "KC_TRANSPARENT" => Some("TRNS"),
+                "KC_NO" => Some("NO"),
+                _ => None,
+            };
+
+            if let (true, Some(action)) = (should_wrap, special_action) {
+                result.push_str(action);
+            } else if should_wrap {
                 result.push_str(&format!("Key({})", keycode));
             } else {
                 result.push_str(keycode);
@@ -568,9 +1705,14 @@ impl Config {
     #[allow(clippy::missing_errors_doc)]
     pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
         let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
 
+    /// Parse config from RON text
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
         // Preprocess to support bare KeyCode syntax
-        let preprocessed = Self::preprocess_config(&content);
+        let preprocessed = Self::preprocess_config(content);
 
         let config = ron::from_str(&preprocessed)
             .map_err(|e| anyhow::anyhow!("Config parsing error: {}", e))?;
@@ -637,9 +1779,64 @@ impl Config {
                     config.game_mode.remaps.extend(game_mode.remaps.clone());
                 }
 
-                config
-            } else {
-                // NON-INHERITING MODE: Build from scratch with per-keyboard config only
+                // MERGE combos: per-keyboard combos are added to the global ones
+                if let Some(combos) = &override_cfg.combos {
+                    config.combos.extend(combos.iter().cloned());
+                }
+                if let Some(term) = override_cfg.combo_term_ms {
+                    config.combo_term_ms = Some(term);
+                }
+
+                // MERGE key overrides: per-keyboard overrides are checked before global ones
+                if let Some(key_overrides) = &override_cfg.key_overrides {
+                    let mut merged = key_overrides.clone();
+                    merged.extend(config.key_overrides);
+                    config.key_overrides = merged;
+                }
+
+                // MERGE conditional layers: per-keyboard conditions are added to the global ones
+                if let Some(conditional_layers) = &override_cfg.conditional_layers {
+                    config
+                        .conditional_layers
+                        .extend(conditional_layers.iter().cloned());
+                }
+
+                // MERGE leader sequences: per-keyboard sequences are added to the global ones
+                if let Some(sequences) = &override_cfg.leader_sequences {
+                    config.leader_sequences.extend(sequences.iter().cloned());
+                }
+                if let Some(timeout) = override_cfg.leader_timeout_ms {
+                    config.leader_timeout_ms = Some(timeout);
+                }
+                if let Some(replay) = override_cfg.leader_replay_unmatched {
+                    config.leader_replay_unmatched = replay;
+                }
+                if let Some(caps_word) = &override_cfg.caps_word {
+                    config.caps_word = caps_word.clone();
+                }
+                if let Some(mouse_keys) = &override_cfg.mouse_keys {
+                    config.mouse_keys = mouse_keys.clone();
+                }
+                if let Some(alt_repeat_keys) = &override_cfg.alt_repeat_keys {
+                    config.alt_repeat_keys = alt_repeat_keys.clone();
+                }
+                if let Some(groups) = &override_cfg.auto_shift_groups {
+                    config.auto_shift_groups = groups.clone();
+                }
+                if let Some(timeout) = override_cfg.auto_shift_timeout_ms {
+                    config.auto_shift_timeout_ms = Some(timeout);
+                }
+
+                // MERGE hands: per-keyboard hands override global ones for the same keys
+                if let Some(hands) = &override_cfg.hands {
+                    config
+                        .hands
+                        .extend(hands.iter().map(|(&key, &hand)| (key, hand)));
+                }
+
+                config
+            } else {
+                // NON-INHERITING MODE: Build from scratch with per-keyboard config only
                 // Use defaults for any fields not specified in per-keyboard config
                 Config {
                     tapping_term_ms: override_cfg
@@ -655,6 +1852,26 @@ impl Config {
                         .double_tap_window_ms
                         .or(self.double_tap_window_ms),
                     oneshot_timeout_ms: override_cfg.oneshot_timeout_ms.or(self.oneshot_timeout_ms),
+                    combos: override_cfg.combos.clone().unwrap_or_default(),
+                    combo_term_ms: override_cfg.combo_term_ms.or(self.combo_term_ms),
+                    key_overrides: override_cfg.key_overrides.clone().unwrap_or_default(),
+                    conditional_layers: override_cfg.conditional_layers.clone().unwrap_or_default(),
+                    leader_sequences: override_cfg.leader_sequences.clone().unwrap_or_default(),
+                    leader_timeout_ms: override_cfg.leader_timeout_ms.or(self.leader_timeout_ms),
+                    leader_replay_unmatched: override_cfg
+                        .leader_replay_unmatched
+                        .unwrap_or(self.leader_replay_unmatched),
+                    caps_word: override_cfg.caps_word.clone().unwrap_or_default(),
+                    mouse_keys: override_cfg.mouse_keys.clone().unwrap_or_default(),
+                    alt_repeat_keys: override_cfg
+                        .alt_repeat_keys
+                        .clone()
+                        .unwrap_or_else(default_alt_repeat_keys),
+                    hands: override_cfg.hands.clone().unwrap_or_default(),
+                    auto_shift_groups: override_cfg.auto_shift_groups.clone().unwrap_or_default(),
+                    auto_shift_timeout_ms: override_cfg
+                        .auto_shift_timeout_ms
+                        .or(self.auto_shift_timeout_ms),
                     hot_config_reload: self.hot_config_reload, // Keep global hot reload setting
                     per_keyboard_inherits_global_layout: self.per_keyboard_inherits_global_layout, // Keep global setting
                 }
@@ -770,6 +1987,589 @@ impl Config {
         }
     }
 
+    /// Apply edits to config file text, keeping its formatting and comments
+    /// Returns None if a field or MT action couldn't be edited
+    #[must_use]
+    pub fn edit_text(content: &str, edits: &[ConfigEdit]) -> Option<String> {
+        let mut content = content.to_string();
+        for edit in edits {
+            content = match edit {
+                ConfigEdit::Field {
+                    keyboard,
+                    section,
+                    field,
+                    value,
+                } => {
+                    // Override entries are Some(...) fields
+                    let fields_body = |content: &str| {
+                        let body = ron_text::struct_body(content, 0)?;
+                        let Some(keyboard) = keyboard else {
+                            return Some(body);
+                        };
+                        let overrides =
+                            ron_text::field_value(content, body, "per_keyboard_overrides")?;
+                        let entry = ron_text::map_entry(content, overrides.start, keyboard)?;
+                        ron_text::struct_body(content, entry.start)
+                    };
+                    let wrap = |value: &str| match keyboard {
+                        Some(_) => format!("Some({value})"),
+                        None => value.to_string(),
+                    };
+
+                    let mut body = fields_body(&content)?;
+                    match section {
+                        Some(section) => {
+                            if ron_text::field_value(&content, body.clone(), section).is_none() {
+                                content = ron_text::set_field(&content, body, section, &wrap("()"));
+                                body = fields_body(&content)?;
+                            }
+                            let section_value = ron_text::field_value(&content, body, section)?;
+                            body = ron_text::option_struct_body(&content, section_value.start)?;
+                            ron_text::set_field(&content, body, field, value)
+                        }
+                        None => ron_text::set_field(&content, body, field, &wrap(value)),
+                    }
+                }
+                ConfigEdit::MtOption { key, field, value } => {
+                    let key = format!("{key:?}");
+                    let actions = ron_text::mt_actions(&content, &key);
+                    if actions.is_empty() {
+                        return None;
+                    }
+                    // Back to front so earlier positions stay valid
+                    for open in actions.into_iter().rev() {
+                        content = ron_text::set_mt_option(&content, open, field, value)?;
+                    }
+                    content
+                }
+            };
+        }
+        Some(content)
+    }
+
+    /// Per-key options of every MT action bound to a key, in all layers,
+    /// game mode and per-keyboard overrides
+    pub fn mt_options_mut(&mut self) -> Vec<(KeyCode, &mut KeyOptions)> {
+        let mut remap_tables: Vec<&mut HashMap<KeyCode, Action>> = vec![&mut self.remaps];
+        remap_tables.extend(self.layers.values_mut().map(|layer| &mut layer.remaps));
+        remap_tables.push(&mut self.game_mode.remaps);
+        for keyboard in self.per_keyboard_overrides.values_mut() {
+            remap_tables.extend(keyboard.remaps.as_mut());
+            if let Some(layers) = &mut keyboard.layers {
+                remap_tables.extend(layers.values_mut().map(|layer| &mut layer.remaps));
+            }
+            if let Some(game_mode) = &mut keyboard.game_mode {
+                remap_tables.push(&mut game_mode.remaps);
+            }
+        }
+
+        remap_tables
+            .into_iter()
+            .flat_map(|remaps| remaps.iter_mut())
+            .filter_map(|(&keycode, action)| match action {
+                Action::MT(_, _, options) => Some((keycode, options)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Validate timing settings - returns a list of errors
+    #[must_use]
+    pub fn validate_timing(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.tapping_term_ms == 0 || self.tapping_term_ms > 1000 {
+            errors.push(format!(
+                "tapping_term_ms out of reasonable range (0-1000): {}",
+                self.tapping_term_ms
+            ));
+        }
+
+        // MT config timing
+        let window = self.mt_config.double_tap_window_ms;
+        if window == 0 || window > 1000 {
+            errors.push(format!(
+                "mt_config.double_tap_window_ms out of reasonable range (0-1000): {}",
+                window
+            ));
+        }
+        if self.mt_config.require_prior_idle_ms > 1000 {
+            errors.push(format!(
+                "mt_config.require_prior_idle_ms out of reasonable range (0-1000): {}",
+                self.mt_config.require_prior_idle_ms
+            ));
+        }
+
+        if self.mouse_keys.interval_ms == 0 || self.mouse_keys.wheel_interval_ms == 0 {
+            errors.push("mouse_keys intervals must be greater than 0".to_string());
+        }
+        if self.mouse_keys.move_delta > self.mouse_keys.max_move_delta {
+            errors.push(format!(
+                "mouse_keys.move_delta ({}) is greater than max_move_delta ({})",
+                self.mouse_keys.move_delta, self.mouse_keys.max_move_delta
+            ));
+        }
+
+        if let Some(timeout) = self.auto_shift_timeout_ms {
+            if timeout == 0 || timeout > 1000 {
+                errors.push(format!(
+                    "auto_shift_timeout_ms out of reasonable range (0-1000): {}",
+                    timeout
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// Validate combo definitions - returns a list of errors
+    #[must_use]
+    pub fn validate_combos(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Some(term) = self.combo_term_ms {
+            if term == 0 || term > 1000 {
+                errors.push(format!(
+                    "combo_term_ms out of reasonable range (0-1000): {}",
+                    term
+                ));
+            }
+        }
+
+        let layer_defined = |layer: &Layer| layer.is_base() || self.layers.contains_key(layer);
+
+        for combo in &self.combos {
+            if combo.keys.len() < 2 {
+                errors.push(format!("Combo needs at least 2 keys: {:?}", combo.keys));
+            }
+            let unique: std::collections::HashSet<_> = combo.keys.iter().collect();
+            if unique.len() != combo.keys.len() {
+                errors.push(format!("Combo has duplicate keys: {:?}", combo.keys));
+            }
+
+            let scoped_layers = combo.layers.iter().flatten();
+            for layer in combo
+                .action
+                .referenced_layers()
+                .into_iter()
+                .chain(scoped_layers)
+            {
+                if !layer_defined(layer) {
+                    errors.push(format!(
+                        "Combo {:?} references undefined layer: \"{}\"",
+                        combo.keys, layer.0
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Validate conditional layers - returns a list of errors
+    /// Every layer must be defined and conditions must not form cycles, also once
+    /// per-keyboard overrides are merged in
+    #[must_use]
+    pub fn validate_conditional_layers(&self) -> Vec<String> {
+        let mut errors = self.conditional_layer_errors();
+
+        let mut keyboards: Vec<_> = self.per_keyboard_overrides.iter().collect();
+        keyboards.sort_by_key(|(name, _)| *name);
+        for (name, keyboard) in keyboards {
+            if keyboard.conditional_layers.is_none() && keyboard.layers.is_none() {
+                continue;
+            }
+            // Errors of the global config are only reported once
+            let global_errors = errors.clone();
+            errors.extend(
+                self.for_keyboard(name)
+                    .conditional_layer_errors()
+                    .into_iter()
+                    .filter(|error| !global_errors.contains(error))
+                    .map(|error| format!("per_keyboard_overrides[{}] {}", name, error)),
+            );
+        }
+
+        errors
+    }
+
+    /// Conditional layer errors of this config alone
+    fn conditional_layer_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        let layer_defined = |layer: &Layer| layer.is_base() || self.layers.contains_key(layer);
+
+        // Edges from each condition layer to the layer it activates
+        let mut activates: HashMap<&Layer, Vec<&Layer>> = HashMap::new();
+        for conditional in &self.conditional_layers {
+            if conditional.when.is_empty() {
+                errors.push(format!(
+                    "Conditional layer \"{}\" needs at least 1 condition layer",
+                    conditional.then.0
+                ));
+            }
+            for layer in conditional
+                .when
+                .iter()
+                .chain(std::iter::once(&conditional.then))
+            {
+                if !layer_defined(layer) {
+                    errors.push(format!(
+                        "Conditional layer \"{}\" references undefined layer: \"{}\"",
+                        conditional.then.0, layer.0
+                    ));
+                }
+            }
+            for layer in &conditional.when {
+                activates.entry(layer).or_default().push(&conditional.then);
+            }
+        }
+
+        // A layer that (indirectly) activates itself is a cycle
+        for &start in activates.keys() {
+            let mut visited = std::collections::HashSet::new();
+            let mut pending: Vec<&Layer> = activates[start].clone();
+            while let Some(layer) = pending.pop() {
+                if layer == start {
+                    errors.push(format!(
+                        "Conditional layers form a cycle through \"{}\"",
+                        start.0
+                    ));
+                    break;
+                }
+                if visited.insert(layer) {
+                    pending.extend(activates.get(layer).into_iter().flatten());
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Whether the config binds a MOUSE action, so its virtual device needs mouse
+    /// buttons and axes. Per-keyboard overrides aren't included, ask `for_keyboard`
+    #[must_use]
+    pub fn binds_mouse_keys(&self) -> bool {
+        self.global_bound_actions()
+            .into_iter()
+            .map(|(_, action)| action)
+            .chain(self.key_overrides.iter().map(|o| &o.replacement))
+            .any(Action::has_mouse_action)
+    }
+
+    /// Every action bound in the config (keymaps, combos, leader sequences, and the
+    /// same in per-keyboard overrides), with what it is bound to
+    fn bound_actions(&self) -> Vec<(String, &Action)> {
+        let mut actions = self.global_bound_actions();
+
+        let mut keyboards: Vec<_> = self.per_keyboard_overrides.iter().collect();
+        keyboards.sort_by_key(|(name, _)| *name);
+        for (name, keyboard) in keyboards {
+            let owner = |what: String| format!("per_keyboard_overrides[{}] {}", name, what);
+            let keymaps = keyboard
+                .remaps
+                .iter()
+                .chain(
+                    keyboard
+                        .layers
+                        .iter()
+                        .flatten()
+                        .map(|(_, layer)| &layer.remaps),
+                )
+                .chain(keyboard.game_mode.iter().map(|game_mode| &game_mode.remaps));
+            actions.extend(
+                keymaps
+                    .flat_map(|remaps| remaps.iter())
+                    .map(|(key, action)| (owner(format!("{:?}", key)), action)),
+            );
+            actions.extend(
+                keyboard
+                    .combos
+                    .iter()
+                    .flatten()
+                    .map(|combo| (owner(format!("combo {:?}", combo.keys)), &combo.action)),
+            );
+            actions.extend(keyboard.leader_sequences.iter().flatten().map(|sequence| {
+                (
+                    owner(format!("leader sequence {:?}", sequence.keys)),
+                    &sequence.action,
+                )
+            }));
+        }
+        actions
+    }
+
+    /// Actions bound in the config itself, without per-keyboard overrides
+    fn global_bound_actions(&self) -> Vec<(String, &Action)> {
+        let keymaps = std::iter::once(&self.remaps)
+            .chain(self.layers.values().map(|layer| &layer.remaps))
+            .chain(std::iter::once(&self.game_mode.remaps));
+        let mut actions: Vec<(String, &Action)> = keymaps
+            .flat_map(|remaps| remaps.iter())
+            .map(|(key, action)| (format!("{:?}", key), action))
+            .collect();
+        actions.extend(
+            self.combos
+                .iter()
+                .map(|combo| (format!("combo {:?}", combo.keys), &combo.action)),
+        );
+        actions.extend(self.leader_sequences.iter().map(|sequence| {
+            (
+                format!("leader sequence {:?}", sequence.keys),
+                &sequence.action,
+            )
+        }));
+        actions
+    }
+
+    /// Validate tap dances - returns a list of errors
+    #[must_use]
+    pub fn validate_tap_dances(&self) -> Vec<String> {
+        self.bound_actions()
+            .into_iter()
+            .filter(|(_, action)| action.has_empty_tap_dance())
+            .map(|(owner, _)| format!("TD on {} needs at least 1 step", owner))
+            .collect()
+    }
+
+    /// Validate per-key MT/DT/OSM options - returns a list of errors
+    #[must_use]
+    pub fn validate_key_options(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for (owner, action) in self.bound_actions() {
+            for (name, options) in action.key_options() {
+                let mut check_range = |option: &str, value: Option<u64>| {
+                    if let Some(value) = value {
+                        if value == 0 || value > 1000 {
+                            errors.push(format!(
+                                "{} options on {}: {} out of reasonable range (0-1000): {}",
+                                name, owner, option, value
+                            ));
+                        }
+                    }
+                };
+                check_range("tapping_term_ms", options.tapping_term_ms.map(u64::from));
+                check_range(
+                    "double_tap_window_ms",
+                    options.double_tap_window_ms.map(u64::from),
+                );
+                if options.oneshot_timeout_ms == Some(0) {
+                    errors.push(format!(
+                        "{} options on {}: oneshot_timeout_ms must be greater than 0",
+                        name, owner
+                    ));
+                }
+                if let Some(idle) = options.require_prior_idle_ms.filter(|&idle| idle > 1000) {
+                    errors.push(format!(
+                        "{} options on {}: require_prior_idle_ms out of reasonable range (0-1000): {}",
+                        name, owner, idle
+                    ));
+                }
+
+                // Options the action doesn't use
+                let set_options = [
+                    ("tapping_term_ms", options.tapping_term_ms.is_some()),
+                    ("permissive_hold", options.permissive_hold.is_some()),
+                    (
+                        "hold_do_nothing_emits_tap",
+                        options.hold_do_nothing_emits_tap.is_some(),
+                    ),
+                    (
+                        "double_tap_then_hold",
+                        options.double_tap_then_hold.is_some(),
+                    ),
+                    (
+                        "double_tap_window_ms",
+                        options.double_tap_window_ms.is_some(),
+                    ),
+                    ("oneshot_timeout_ms", options.oneshot_timeout_ms.is_some()),
+                    (
+                        "require_prior_idle_ms",
+                        options.require_prior_idle_ms.is_some(),
+                    ),
+                ];
+                let supported: &[&str] = match name {
+                    "MT" => &[
+                        "tapping_term_ms",
+                        "permissive_hold",
+                        "hold_do_nothing_emits_tap",
+                        "double_tap_then_hold",
+                        "double_tap_window_ms",
+                        "require_prior_idle_ms",
+                    ],
+                    "DT" => &["tapping_term_ms", "double_tap_window_ms"],
+                    _ => &["tapping_term_ms", "oneshot_timeout_ms"],
+                };
+                for (option, _) in set_options
+                    .iter()
+                    .filter(|(option, set)| *set && !supported.contains(option))
+                {
+                    errors.push(format!(
+                        "{} options on {}: {} has no effect on {}",
+                        name, owner, option, name
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Check the hands sections (global and per-keyboard) are used - returns a list of
+    /// warnings (hands that no hand-based MT option reads)
+    #[must_use]
+    pub fn hand_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        // Hands only matter to the hand-based MT options
+        let uses_hands = |config: &Config| {
+            let mt = &config.mt_config;
+            mt.cross_hand_unwrap
+                || (mt.flavor == MtFlavor::Heuristic
+                    && (mt.same_hand_roll_detection
+                        || mt.opposite_hand_chord_detection
+                        || mt.multi_mod_detection
+                        || mt.predictive_scoring))
+        };
+        if !self.hands.is_empty() && !uses_hands(self) {
+            warnings.push(
+                "hands has no effect: no hand-based MT option (roll/chord/multi-mod detection, \
+                 predictive scoring, cross_hand_unwrap) is enabled"
+                    .to_string(),
+            );
+        }
+
+        let mut keyboards: Vec<_> = self.per_keyboard_overrides.iter().collect();
+        keyboards.sort_by_key(|(name, _)| name.as_str());
+        for (name, override_cfg) in keyboards {
+            let Some(hands) = &override_cfg.hands else {
+                continue;
+            };
+            if !hands.is_empty() && !uses_hands(&self.for_keyboard(name)) {
+                warnings.push(format!(
+                    "per_keyboard_overrides[{}]: hands has no effect: no hand-based MT option is enabled",
+                    name
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    /// Validate the hands sections (global and per-keyboard) - returns a list of errors
+    #[must_use]
+    pub fn validate_hands(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        let mut keyboards: Vec<_> = self.per_keyboard_overrides.iter().collect();
+        keyboards.sort_by_key(|(name, _)| name.as_str());
+
+        // Keyboards send KC_LGUI/KC_RGUI, never their KC_LCMD/KC_RCMD aliases
+        let mut sections = vec![("hands".to_string(), &self.hands)];
+        sections.extend(keyboards.iter().filter_map(|(name, override_cfg)| {
+            let hands = override_cfg.hands.as_ref()?;
+            Some((format!("per_keyboard_overrides[{}].hands", name), hands))
+        }));
+        for (owner, hands) in sections {
+            for (alias, key) in [
+                (KeyCode::KC_LCMD, KeyCode::KC_LGUI),
+                (KeyCode::KC_RCMD, KeyCode::KC_RGUI),
+            ] {
+                if hands.contains_key(&alias) {
+                    errors.push(format!(
+                        "{}: {:?} is never sent by a keyboard, use {:?}",
+                        owner, alias, key
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Validate leader sequence definitions - returns a list of errors
+    #[must_use]
+    pub fn validate_leader_sequences(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Some(timeout) = self.leader_timeout_ms {
+            if timeout == 0 || timeout > 5000 {
+                errors.push(format!(
+                    "leader_timeout_ms out of reasonable range (0-5000): {}",
+                    timeout
+                ));
+            }
+        }
+
+        let layer_defined = |layer: &Layer| layer.is_base() || self.layers.contains_key(layer);
+
+        for (i, sequence) in self.leader_sequences.iter().enumerate() {
+            if sequence.keys.is_empty() {
+                errors.push("Leader sequence needs at least 1 key".to_string());
+            }
+            if self.leader_sequences[..i]
+                .iter()
+                .any(|other| other.keys == sequence.keys)
+            {
+                errors.push(format!("Duplicate leader sequence: {:?}", sequence.keys));
+            }
+
+            for layer in sequence.action.referenced_layers() {
+                if !layer_defined(layer) {
+                    errors.push(format!(
+                        "Leader sequence {:?} references undefined layer: \"{}\"",
+                        sequence.keys, layer.0
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Validate key override definitions - returns a list of errors
+    #[must_use]
+    pub fn validate_key_overrides(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        let layer_defined = |layer: &Layer| layer.is_base() || self.layers.contains_key(layer);
+
+        for key_override in &self.key_overrides {
+            if key_override.mods.is_empty() {
+                errors.push(format!(
+                    "Key override for {:?} needs at least 1 modifier",
+                    key_override.key
+                ));
+            }
+            for modifier in &key_override.mods {
+                if !modifier.is_modifier() {
+                    errors.push(format!(
+                        "Key override for {:?} uses non-modifier key: {:?}",
+                        key_override.key, modifier
+                    ));
+                }
+            }
+
+            let scoped_layers = key_override.layers.iter().flatten();
+            for layer in key_override
+                .replacement
+                .referenced_layers()
+                .into_iter()
+                .chain(scoped_layers)
+            {
+                if !layer_defined(layer) {
+                    errors.push(format!(
+                        "Key override for {:?} references undefined layer: \"{}\"",
+                        key_override.key, layer.0
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
     /// Validate config without printing - returns errors as a Vec<String>
     pub fn validate_silent(&self) -> Result<()> {
         use std::collections::{HashMap, HashSet};
@@ -838,20 +2638,7 @@ impl Config {
         }
 
         // Validation 2: Check timing values are reasonable
-        if self.tapping_term_ms == 0 || self.tapping_term_ms > 1000 {
-            errors.push(format!(
-                "tapping_term_ms out of reasonable range (0-1000): {}",
-                self.tapping_term_ms
-            ));
-        }
-
-        // Validate MT config timing
-        if self.mt_config.double_tap_window_ms == 0 || self.mt_config.double_tap_window_ms > 1000 {
-            errors.push(format!(
-                "mt_config.double_tap_window_ms out of reasonable range (0-1000): {}",
-                self.mt_config.double_tap_window_ms
-            ));
-        }
+        errors.extend(self.validate_timing());
 
         // Validation 3: Check layer references
         let mut referenced_layers = HashSet::new();
@@ -859,7 +2646,7 @@ impl Config {
         let extract_layer_refs = |remaps: &HashMap<KeyCode, Action>| {
             let mut refs = Vec::new();
             for action in remaps.values() {
-                if let Action::TO(layer) = action {
+                for layer in action.referenced_layers() {
                     refs.push(layer.0.clone());
                 }
             }
@@ -881,6 +2668,30 @@ impl Config {
             }
         }
 
+        // Validation 4: Check combos
+        errors.extend(self.validate_combos());
+
+        // Validation 5: Check key overrides
+        errors.extend(self.validate_key_overrides());
+
+        // Validation 6: Check leader sequences
+        errors.extend(self.validate_leader_sequences());
+
+        // Validation 7: Check conditional layers
+        errors.extend(self.validate_conditional_layers());
+
+        // Validation 8: Check per-key options
+        errors.extend(self.validate_key_options());
+
+        // Validation 9: Check hands (unused hands are only worth a warning)
+        errors.extend(self.validate_hands());
+        for warning in self.hand_warnings() {
+            tracing::warn!("{}", warning);
+        }
+
+        // Validation 10: Check tap dances
+        errors.extend(self.validate_tap_dances());
+
         if !errors.is_empty() {
             Err(anyhow::anyhow!(
                 "Config validation failed: {}",
@@ -947,4 +2758,518 @@ mod tests {
         let input = r#"KC_F1: CMD("/usr/bin/test"),"#;
         assert_eq!(Config::preprocess_config(input), input);
     }
+
+    #[test]
+    fn test_preprocess_trns_and_no() {
+        let input = "KC_A: KC_TRNS, KC_B: KC_NO, KC_C: MT(KC_NO, KC_LSFT),";
+        let expected = "KC_A: TRNS, KC_B: NO, KC_C: MT(NO, Key(KC_LSFT)),";
+        assert_eq!(Config::preprocess_config(input), expected);
+    }
+
+    #[test]
+    fn test_parse_combos() {
+        let input = r#"(
+            combo_term_ms: Some(40),
+            combos: [
+                (keys: [KC_J, KC_K], action: KC_ESC),
+                (keys: [KC_S, KC_D, KC_F], action: CMD("true"), layers: Some(["nav"]), term_ms: Some(80)),
+            ],
+        )"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(config.combo_term_ms, Some(40));
+        assert_eq!(config.combos[0].keys, vec![KeyCode::KC_J, KeyCode::KC_K]);
+        assert_eq!(config.combos[0].action, Action::Key(KeyCode::KC_ESC));
+        assert_eq!(config.combos[1].layers, Some(vec![Layer::new("nav")]));
+        assert_eq!(config.combos[1].term_ms, Some(80));
+    }
+
+    #[test]
+    fn test_parse_key_overrides() {
+        let input = r#"(
+            key_overrides: [
+                (mods: [KC_LSFT], key: KC_BSPC, replacement: KC_DEL),
+                (mods: [KC_LCTL, KC_LALT], key: KC_H, replacement: MO("nav"), layers: Some(["nav"])),
+            ],
+        )"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(config.key_overrides[0].mods, vec![KeyCode::KC_LSFT]);
+        assert_eq!(config.key_overrides[0].key, KeyCode::KC_BSPC);
+        assert_eq!(
+            config.key_overrides[0].replacement,
+            Action::Key(KeyCode::KC_DEL)
+        );
+        assert_eq!(
+            config.key_overrides[1].replacement,
+            Action::MO(Layer::new("nav"))
+        );
+        assert_eq!(
+            config.key_overrides[1].layers,
+            Some(vec![Layer::new("nav")])
+        );
+    }
+
+    #[test]
+    fn test_parse_leader_sequences() {
+        let input = r#"(
+            remaps: {KC_RALT: LEADER},
+            leader_timeout_ms: Some(400),
+            leader_replay_unmatched: true,
+            leader_sequences: [
+                (keys: [KC_G, KC_S], action: CMD("git status")),
+                (keys: [KC_E], action: KC_ESC),
+            ],
+        )"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(config.remaps[&KeyCode::KC_RALT], Action::LEADER);
+        assert_eq!(config.leader_timeout_ms, Some(400));
+        assert!(config.leader_replay_unmatched);
+        assert_eq!(
+            config.leader_sequences[0].keys,
+            vec![KeyCode::KC_G, KeyCode::KC_S]
+        );
+        assert_eq!(
+            config.leader_sequences[1].action,
+            Action::Key(KeyCode::KC_ESC)
+        );
+    }
+
+    #[test]
+    fn test_parse_caps_word() {
+        let input = r#"(
+            remaps: {KC_CAPS: CAPS_WORD},
+            caps_word: (shifted_keys: [], continue_keys: [KC_MINS]),
+        )"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(config.remaps[&KeyCode::KC_CAPS], Action::CAPS_WORD);
+        assert!(config.caps_word.shifted_keys.is_empty());
+        assert_eq!(config.caps_word.continue_keys, vec![KeyCode::KC_MINS]);
+        assert_eq!(config.caps_word.idle_timeout_ms, 5000);
+
+        // Defaults when the section is left out
+        let config: Config = ron::from_str("()").unwrap();
+        assert_eq!(config.caps_word.shifted_keys, vec![KeyCode::KC_MINS]);
+    }
+
+    #[test]
+    fn test_parse_auto_shift() {
+        let input = "(auto_shift_groups: [Letters, Symbols], auto_shift_timeout_ms: Some(160))";
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(
+            config.auto_shift_groups,
+            vec![AutoShiftGroup::Letters, AutoShiftGroup::Symbols]
+        );
+        assert_eq!(config.auto_shift_timeout_ms, Some(160));
+        assert!(AutoShiftGroup::Letters.contains(KeyCode::KC_Q));
+        assert!(AutoShiftGroup::Symbols.contains(KeyCode::KC_SLSH));
+        assert!(!AutoShiftGroup::Numbers.contains(KeyCode::KC_SPC));
+    }
+
+    #[test]
+    fn test_parse_alt_repeat_keys() {
+        let input = r#"(
+            remaps: {KC_RALT: REPEAT, KC_RCTL: ALT_REPEAT},
+            alt_repeat_keys: [
+                (key: KC_Z, mods: [KC_LCTL], alt: KC_Y),
+                (key: KC_LEFT, alt: KC_RGHT),
+            ],
+        )"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(config.remaps[&KeyCode::KC_RALT], Action::REPEAT);
+        assert_eq!(config.remaps[&KeyCode::KC_RCTL], Action::ALT_REPEAT);
+        assert_eq!(
+            config.alt_repeat_keys[0],
+            AltRepeatKey::new(KeyCode::KC_Z, &[KeyCode::KC_LCTL], KeyCode::KC_Y)
+        );
+        assert!(config.alt_repeat_keys[1].mods.is_empty());
+
+        // Defaults when the list is left out
+        let config: Config = ron::from_str("()").unwrap();
+        assert_eq!(config.alt_repeat_keys, default_alt_repeat_keys());
+    }
+
+    #[test]
+    fn test_action_with_modifiers() {
+        assert_eq!(
+            Action::Key(KeyCode::KC_Z).with_modifiers(&[KeyCode::KC_LCTL, KeyCode::KC_RSFT]),
+            Action::LCTL(Box::new(Action::RSFT(Box::new(Action::Key(KeyCode::KC_Z)))))
+        );
+    }
+
+    #[test]
+    fn test_parse_mouse_keys() {
+        let input = r#"(
+            remaps: {KC_H: MOUSE(MS_LEFT), KC_F: MOUSE(MS_BTN1)},
+            mouse_keys: (move_delta: 2, max_move_delta: 20),
+        )"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(
+            config.remaps[&KeyCode::KC_H],
+            Action::MOUSE(MouseAction::MS_LEFT)
+        );
+        assert_eq!(
+            config.remaps[&KeyCode::KC_F],
+            Action::MOUSE(MouseAction::MS_BTN1)
+        );
+        assert_eq!(config.mouse_keys.move_delta, 2);
+        assert_eq!(config.mouse_keys.max_move_delta, 20);
+        assert_eq!(config.mouse_keys.interval_ms, 16);
+        assert!(config.binds_mouse_keys());
+
+        // Only configs binding a MOUSE action get a virtual pointer, nested ones included
+        let config: Config =
+            ron::from_str(&Config::preprocess_config(r#"(remaps: {KC_H: KC_J})"#)).unwrap();
+        assert!(!config.binds_mouse_keys());
+        let input = r#"(remaps: {KC_H: MT(KC_H, LSFT(MOUSE(MS_BTN1)))})"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+        assert!(config.binds_mouse_keys());
+    }
+
+    #[test]
+    fn test_validate_conditional_layers() {
+        let input = r#"(
+            layers: {"lower": (remaps: {}), "raise": (remaps: {}), "adjust": (remaps: {})},
+            conditional_layers: [(when: ["lower", "raise"], then: "adjust")],
+        )"#;
+        let mut config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+        assert_eq!(
+            config.conditional_layers[0].when,
+            vec![Layer::new("lower"), Layer::new("raise")]
+        );
+        assert!(config.validate_conditional_layers().is_empty());
+
+        // adjust → lower closes a cycle
+        config.conditional_layers.push(ConditionalLayer {
+            when: vec![Layer::new("adjust")],
+            then: Layer::new("lower"),
+        });
+        assert!(!config.validate_conditional_layers().is_empty());
+
+        // Undefined layers are rejected
+        config.conditional_layers = vec![ConditionalLayer {
+            when: vec![Layer::new("lower")],
+            then: Layer::new("missing"),
+        }];
+        assert!(!config.validate_conditional_layers().is_empty());
+
+        // Per-keyboard conditions are checked merged with the global ones
+        let input = r#"(
+            layers: {"lower": (remaps: {}), "raise": (remaps: {}), "adjust": (remaps: {})},
+            conditional_layers: [(when: ["lower", "raise"], then: "adjust")],
+            per_keyboard_overrides: {
+                "cycle": (conditional_layers: Some([(when: ["adjust"], then: "lower")])),
+                "missing": (conditional_layers: Some([(when: ["lower"], then: "missing")])),
+                "defined": (
+                    layers: Some({"missing": (remaps: {})}),
+                    conditional_layers: Some([(when: ["lower"], then: "missing")]),
+                ),
+            },
+        )"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+        let errors = config.validate_conditional_layers();
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(errors[0].starts_with("per_keyboard_overrides[cycle] "));
+        assert!(errors.iter().all(|error| !error.contains("[defined]")));
+        assert!(
+            errors[2].contains("per_keyboard_overrides[missing] ")
+                && errors[2].contains("\"missing\"")
+        );
+    }
+
+    #[test]
+    fn test_parse_key_options() {
+        let input = r#"{
+            KC_A: MT(KC_A, KC_LGUI, (tapping_term_ms: 220, permissive_hold: false)),
+            KC_B: DT(KC_B, TO("nav"), (double_tap_window_ms: 300)),
+            KC_C: OSM(KC_LSFT, (oneshot_timeout_ms: 2000)),
+            KC_D: MT(KC_D, KC_LCTL),
+        }"#;
+        let remaps: HashMap<KeyCode, Action> =
+            ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(
+            remaps[&KeyCode::KC_A],
+            Action::MT(
+                Box::new(Action::Key(KeyCode::KC_A)),
+                Box::new(Action::Key(KeyCode::KC_LGUI)),
+                KeyOptions {
+                    tapping_term_ms: Some(220),
+                    permissive_hold: Some(false),
+                    ..KeyOptions::default()
+                }
+            )
+        );
+        let Action::DT(_, _, options) = &remaps[&KeyCode::KC_B] else {
+            panic!("expected DT");
+        };
+        assert_eq!(options.double_tap_window_ms, Some(300));
+        let Action::OSM(_, options) = &remaps[&KeyCode::KC_C] else {
+            panic!("expected OSM");
+        };
+        assert_eq!(options.oneshot_timeout_ms, Some(2000));
+        let Action::MT(_, _, options) = &remaps[&KeyCode::KC_D] else {
+            panic!("expected MT");
+        };
+        assert!(options.is_empty());
+
+        // Options survive a save, and keys without options are saved without them
+        let saved = ron::to_string(&remaps[&KeyCode::KC_A]).unwrap();
+        assert_eq!(
+            ron::from_str::<Action>(&saved).unwrap(),
+            remaps[&KeyCode::KC_A]
+        );
+        let saved = ron::to_string(&remaps[&KeyCode::KC_D]).unwrap();
+        assert_eq!(saved, "MT(Key(KC_D),Key(KC_LCTL))");
+
+        // Out of range and unused options are rejected
+        let input = "(remaps: {KC_A: MT(KC_A, KC_LGUI, (tapping_term_ms: 0)), KC_B: DT(KC_B, KC_C, (oneshot_timeout_ms: 900))})";
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+        assert_eq!(config.validate_key_options().len(), 2);
+
+        // Per-keyboard remaps are checked too
+        let input = r#"(per_keyboard_overrides: {
+            "split": (remaps: Some({KC_A: MT(KC_A, KC_LGUI, (tapping_term_ms: 0))})),
+        })"#;
+        let config: Config = ron::from_str(&Config::preprocess_config(input)).unwrap();
+        let errors = config.validate_key_options();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("per_keyboard_overrides[split] KC_A"));
+    }
+
+    #[test]
+    fn test_parse_hands() {
+        let input = r#"(
+            hands: { KC_SPC: Thumb, KC_Y: Left },
+            per_keyboard_overrides: {
+                "split": (hands: Some({ KC_Y: Right, KC_ENT: Thumb })),
+            },
+        )"#;
+        let config: Config = ron::from_str(input).unwrap();
+        assert_eq!(config.hands[&KeyCode::KC_SPC], Hand::Thumb);
+        assert!(config.validate_hands().is_empty());
+
+        // Per-keyboard hands are merged over the global ones
+        let split = config.for_keyboard("split");
+        assert_eq!(split.hands.len(), 3);
+        assert_eq!(split.hands[&KeyCode::KC_Y], Hand::Right);
+
+        // Thumbs are either hand for unwrap, but never part of rolls or chords
+        assert!(Hand::Thumb.is_opposite(Hand::Left) && Hand::Right.is_opposite(Hand::Thumb));
+        assert!(!Hand::Left.is_opposite(Hand::Left) && !Hand::Thumb.is_opposite(Hand::Unknown));
+        assert!(!Hand::Thumb.is_side());
+
+        // Aliases are rejected
+        let config: Config = ron::from_str("(hands: { KC_LCMD: Left })").unwrap();
+        assert_eq!(config.validate_hands().len(), 1);
+
+        // Hands with no hand-based option enabled are only warned about
+        let input =
+            "(hands: { KC_Y: Left }, mt_config: (cross_hand_unwrap: false, flavor: HoldPreferred))";
+        let config: Config = ron::from_str(input).unwrap();
+        assert_eq!(config.hand_warnings().len(), 1);
+        assert!(config.validate_silent().is_ok());
+    }
+
+    #[test]
+    fn test_edit_text() {
+        let input = r#"(
+            // Base timing
+            /* réglé à la main */
+            tapping_term_ms: 130, // tuned by hand
+            mt_config: (
+                flavor: Heuristic,
+            ),
+            remaps: {
+                KC_A: MT(KC_A, KC_LGUI), // "KC_S: MT(" in a comment is left alone
+                KC_S: MT(KC_S, KC_LALT, (permissive_hold: false)),
+            },
+        )"#;
+        let field = |section, field, value: &str| ConfigEdit::Field {
+            keyboard: None,
+            section,
+            field,
+            value: value.to_string(),
+        };
+        let option = |key, field, value: &str| ConfigEdit::MtOption {
+            key,
+            field,
+            value: value.to_string(),
+        };
+        let edits = [
+            field(None, "tapping_term_ms", "180"),
+            field(Some("mt_config"), "require_prior_idle_ms", "150"),
+            option(KeyCode::KC_A, "tapping_term_ms", "160"),
+            option(KeyCode::KC_S, "permissive_hold", "true"),
+        ];
+        let edited = Config::edit_text(input, &edits).unwrap();
+        assert!(edited.contains("tapping_term_ms: 180, // tuned by hand"));
+        assert!(edited.contains("KC_A: MT(KC_A, KC_LGUI, (tapping_term_ms: 160)), //"));
+
+        let config = Config::parse(&edited).unwrap();
+        assert_eq!(config.tapping_term_ms, 180);
+        assert_eq!(config.mt_config.require_prior_idle_ms, 150);
+        assert_eq!(config.mt_config.flavor, MtFlavor::Heuristic);
+        let Action::MT(_, _, options) = &config.remaps[&KeyCode::KC_S] else {
+            panic!("KC_S is not MT");
+        };
+        assert_eq!(options.permissive_hold, Some(true));
+
+        // Keys without an MT action can't be edited
+        assert!(
+            Config::edit_text(input, &[option(KeyCode::KC_D, "tapping_term_ms", "1")]).is_none()
+        );
+
+        // Non-ASCII text in comments is skipped byte by byte
+        let input = "(\n /* héllo */\n tapping_term_ms: 130,\n)";
+        let edited = Config::edit_text(input, &[field(None, "tapping_term_ms", "180")]).unwrap();
+        assert_eq!(edited, "(\n /* héllo */\n tapping_term_ms: 180,\n)");
+
+        // Fields of a per-keyboard override are Some(...) values
+        let input = r#"(
+            tapping_term_ms: 130,
+            per_keyboard_overrides: {
+                "1:2:3": (tapping_term_ms: Some(150)),
+                "4:5:6": (
+                    tapping_term_ms: Some(180),
+                    mt_config: Some((flavor: HoldPreferred)),
+                ),
+            },
+        )"#;
+        let keyboard_field = |keyboard: &str, section, field, value: &str| ConfigEdit::Field {
+            keyboard: Some(keyboard.to_string()),
+            section,
+            field,
+            value: value.to_string(),
+        };
+        let edits = [
+            keyboard_field("4:5:6", None, "tapping_term_ms", "160"),
+            keyboard_field("4:5:6", Some("mt_config"), "require_prior_idle_ms", "100"),
+            keyboard_field("1:2:3", Some("mt_config"), "require_prior_idle_ms", "50"),
+        ];
+        let config = Config::parse(&Config::edit_text(input, &edits).unwrap()).unwrap();
+        assert_eq!(config.tapping_term_ms, 130);
+        let keyboard = config.for_keyboard("4:5:6");
+        assert_eq!(keyboard.tapping_term_ms, 160);
+        assert_eq!(keyboard.mt_config.flavor, MtFlavor::HoldPreferred);
+        assert_eq!(keyboard.mt_config.require_prior_idle_ms, 100);
+        let keyboard = config.for_keyboard("1:2:3");
+        assert_eq!(keyboard.tapping_term_ms, 150);
+        assert_eq!(keyboard.mt_config.require_prior_idle_ms, 50);
+
+        // Keyboards without an override entry can't be edited
+        assert!(Config::edit_text(input, &[keyboard_field("7:8:9", None, "x", "1")]).is_none());
+    }
+
+    #[test]
+    fn test_parse_tap_dance() {
+        let input = "{KC_LBRC: TD([(tap: KC_LBRC), (tap: KC_RBRC, hold: Some(KC_LCTL))])}";
+        let remaps: HashMap<KeyCode, Action> =
+            ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(
+            remaps[&KeyCode::KC_LBRC],
+            Action::TD(vec![
+                TapDanceStep {
+                    tap: Action::Key(KeyCode::KC_LBRC),
+                    hold: None,
+                },
+                TapDanceStep {
+                    tap: Action::Key(KeyCode::KC_RBRC),
+                    hold: Some(Action::Key(KeyCode::KC_LCTL)),
+                },
+            ])
+        );
+
+        // Tap dances without steps do nothing and are rejected, nested ones too
+        let config = Config::parse("(remaps: { KC_A: MT(TD([]), KC_LCTL) })").unwrap();
+        assert_eq!(
+            config.validate_tap_dances(),
+            vec!["TD on KC_A needs at least 1 step".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_macros() {
+        let input = r#"{KC_F1: STR("hello"), KC_F2: SEQ([Down(KC_LCTL), Tap(KC_C), Up(KC_LCTL), Delay(20)])}"#;
+        let remaps: HashMap<KeyCode, Action> =
+            ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(remaps[&KeyCode::KC_F1], Action::STR("hello".to_string()));
+        assert_eq!(
+            remaps[&KeyCode::KC_F2],
+            Action::SEQ(vec![
+                MacroStep::Down(KeyCode::KC_LCTL),
+                MacroStep::Tap(KeyCode::KC_C),
+                MacroStep::Up(KeyCode::KC_LCTL),
+                MacroStep::Delay(20),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_modifier_wraps() {
+        let input =
+            "{KC_F1: LCTL(KC_C), KC_F2: LSFT(LALT(KC_T)), KC_F3: MT(HYPR(KC_X), MEH(KC_Y))}";
+        let remaps: HashMap<KeyCode, Action> =
+            ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(
+            remaps[&KeyCode::KC_F1],
+            Action::LCTL(Box::new(Action::Key(KeyCode::KC_C)))
+        );
+        assert_eq!(
+            remaps[&KeyCode::KC_F2],
+            Action::LSFT(Box::new(Action::LALT(Box::new(Action::Key(KeyCode::KC_T)))))
+        );
+        assert_eq!(
+            remaps[&KeyCode::KC_F3],
+            Action::MT(
+                Box::new(Action::HYPR(Box::new(Action::Key(KeyCode::KC_X)))),
+                Box::new(Action::MEH(Box::new(Action::Key(KeyCode::KC_Y)))),
+                KeyOptions::default()
+            )
+        );
+        assert_eq!(
+            Action::HYPR(Box::new(Action::Key(KeyCode::KC_X))).wrap_modifiers(),
+            &[
+                KeyCode::KC_LCTL,
+                KeyCode::KC_LSFT,
+                KeyCode::KC_LALT,
+                KeyCode::KC_LGUI
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_nested_mt_actions() {
+        let input = r#"{KC_TAB: MT(KC_TAB, TO("nav")), KC_RALT: MT(OSM(KC_RSFT), KC_RALT)}"#;
+        let remaps: HashMap<KeyCode, Action> =
+            ron::from_str(&Config::preprocess_config(input)).unwrap();
+
+        assert_eq!(
+            remaps[&KeyCode::KC_TAB],
+            Action::MT(
+                Box::new(Action::Key(KeyCode::KC_TAB)),
+                Box::new(Action::TO(Layer::new("nav"))),
+                KeyOptions::default()
+            )
+        );
+        assert_eq!(
+            remaps[&KeyCode::KC_RALT],
+            Action::MT(
+                Box::new(Action::OSM(
+                    Box::new(Action::Key(KeyCode::KC_RSFT)),
+                    KeyOptions::default()
+                )),
+                Box::new(Action::Key(KeyCode::KC_RALT)),
+                KeyOptions::default()
+            )
+        );
+    }
 }
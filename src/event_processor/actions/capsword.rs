@@ -0,0 +1,121 @@
+/// Caps Word processor - QMK-inspired Caps Word
+///
+/// Word Model:
+/// - CAPS_WORD turns Caps Word on (or off again)
+/// - Letters and the configured shifted keys are typed shifted
+/// - Digits, backspace, delete and the configured continue keys are typed unshifted
+/// - Modifier keys are ignored
+/// - Any other key, or a key pressed with Ctrl/Alt/GUI held, ends the word
+/// - Caps Word turns off after the idle timeout without a key press
+///
+/// Keys are classified by what they emit (after MT, DT and layer resolution),
+/// so MT taps continue the word like any other key. The shift itself is pressed
+/// and released by the keymap.
+use crate::config::{CapsWordConfig, KeyCode};
+use crate::event_processor::clock::Clock;
+use std::time::{Duration, Instant};
+
+/// How a key press affects Caps Word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapsWordKey {
+    /// Caps Word is off (or the key is a modifier) - leave the key alone
+    Ignored,
+    /// Type the key shifted, word continues
+    Shifted,
+    /// Type the key unshifted, word continues
+    Unshifted,
+    /// Key ends the word - Caps Word turned off
+    Break,
+}
+
+/// Caps Word processor - tracks whether the current word is capitalized
+pub struct CapsWordProcessor {
+    /// Config
+    config: CapsWordConfig,
+
+    /// Whether Caps Word is on
+    active: bool,
+
+    /// When Caps Word was turned on or the last key was pressed
+    last_key_at: Instant,
+
+    /// Time source
+    clock: Clock,
+}
+
+impl CapsWordProcessor {
+    /// Create new Caps Word processor
+    pub fn new(config: CapsWordConfig) -> Self {
+        Self {
+            config,
+            active: false,
+            last_key_at: Instant::now(),
+            clock: Clock::system(),
+        }
+    }
+
+    /// Use a shared clock (event timestamps, simulated time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Whether Caps Word is on
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Turn Caps Word on, or off if it's already on
+    pub fn toggle(&mut self) {
+        self.active = !self.active;
+        self.last_key_at = self.clock.now();
+    }
+
+    /// Turn Caps Word off
+    pub fn deactivate(&mut self) {
+        self.active = false;
+    }
+
+    /// Handle a key about to be emitted
+    /// `other_mods_held` - Ctrl, Alt or GUI is held (shortcuts end the word)
+    pub fn on_key(&mut self, output_key: KeyCode, other_mods_held: bool) -> CapsWordKey {
+        if !self.active || output_key.is_modifier() {
+            return CapsWordKey::Ignored;
+        }
+        self.last_key_at = self.clock.now();
+
+        if other_mods_held {
+            self.active = false;
+            return CapsWordKey::Break;
+        }
+
+        if output_key.is_letter() || self.config.shifted_keys.contains(&output_key) {
+            CapsWordKey::Shifted
+        } else if output_key.is_digit()
+            || matches!(output_key, KeyCode::KC_BSPC | KeyCode::KC_DEL)
+            || self.config.continue_keys.contains(&output_key)
+        {
+            CapsWordKey::Unshifted
+        } else {
+            self.active = false;
+            CapsWordKey::Break
+        }
+    }
+
+    /// Turn Caps Word off after the idle timeout
+    /// Should be called periodically (e.g., every 1ms in the idle loop)
+    pub fn check_timeouts(&mut self) {
+        if self.active
+            && self.clock.elapsed(self.last_key_at).as_millis()
+                > u128::from(self.config.idle_timeout_ms)
+        {
+            self.active = false;
+        }
+    }
+
+    /// When the idle timeout may next turn Caps Word off (None = inactive)
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.active
+            .then(|| self.last_key_at + Duration::from_millis(self.config.idle_timeout_ms))
+    }
+}
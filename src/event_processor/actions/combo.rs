@@ -0,0 +1,267 @@
+/// Combo processor - QMK-inspired chords of simultaneous keys
+///
+/// Buffering Model:
+/// - A key press that can start a combo is held back in a buffer instead of being processed
+/// - Keys pressed within the combo term keep joining the buffer as long as some combo
+///   still contains every buffered key
+/// - The combo triggers once the buffer matches a combo exactly and no bigger combo
+///   could still complete
+///
+/// Flushing:
+/// - Combo term expires, a non-matching key is pressed or a buffered key is released
+///   → if the buffer matches a combo exactly it triggers, otherwise all buffered keys
+///   are replayed as normal presses in the order they were pressed
+///
+/// Release:
+/// - Releasing any key of a triggered combo releases the combo action
+/// - Releases of the keys still held after that are swallowed
+use crate::config::{Combo, KeyCode, Layer};
+use crate::event_processor::clock::Clock;
+use std::time::{Duration, Instant};
+
+/// Result of combo processing, applied in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComboEvent {
+    /// Process a key press normally
+    PressKey(KeyCode),
+    /// Process a key release normally
+    ReleaseKey(KeyCode),
+    /// Press the action of the combo with this index
+    PressCombo(usize),
+    /// Release the action of the combo with this index
+    ReleaseCombo(usize),
+}
+
+/// Combo processor configuration
+#[derive(Debug, Clone)]
+pub struct ComboConfig {
+    /// Max time from the first to the last key of a combo (ms)
+    pub combo_term_ms: u64,
+}
+
+impl Default for ComboConfig {
+    fn default() -> Self {
+        Self { combo_term_ms: 50 }
+    }
+}
+
+/// A key press held back while a combo may still complete
+#[derive(Debug, Clone)]
+struct BufferedKey {
+    keycode: KeyCode,
+    pressed_at: Instant,
+}
+
+/// A triggered combo whose keys are still (partly) held
+#[derive(Debug, Clone)]
+struct ActiveCombo {
+    index: usize,
+    held_keys: Vec<KeyCode>,
+    released: bool,
+}
+
+/// Combo processor - buffers key presses ahead of action lookup
+pub struct ComboProcessor {
+    /// Config
+    config: ComboConfig,
+
+    /// All combo definitions
+    combos: Vec<Combo>,
+
+    /// Key presses held back (in press order)
+    buffer: Vec<BufferedKey>,
+
+    /// Combos that triggered and still have keys held
+    active_combos: Vec<ActiveCombo>,
+
+    /// Time source
+    clock: Clock,
+}
+
+impl ComboProcessor {
+    /// Create new combo processor
+    pub fn new(config: ComboConfig, combos: Vec<Combo>) -> Self {
+        Self {
+            config,
+            combos,
+            buffer: Vec::new(),
+            active_combos: Vec::new(),
+            clock: Clock::system(),
+        }
+    }
+
+    /// Use a shared clock (event timestamps, simulated time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Get a combo definition by index
+    pub fn combo(&self, index: usize) -> &Combo {
+        &self.combos[index]
+    }
+
+    /// Whether any key press is currently held back
+    pub fn is_buffering(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    /// Handle key press
+    /// `active_layers` decides which combos are enabled
+    pub fn on_press(&mut self, keycode: KeyCode, active_layers: &[Layer]) -> Vec<ComboEvent> {
+        let enabled = self.enabled_combos(active_layers);
+
+        // Fast path: key can't start a combo
+        if self.buffer.is_empty()
+            && !enabled
+                .iter()
+                .any(|&i| self.combos[i].keys.contains(&keycode))
+        {
+            return vec![ComboEvent::PressKey(keycode)];
+        }
+
+        let mut candidate_keys: Vec<KeyCode> = self.buffer.iter().map(|b| b.keycode).collect();
+        candidate_keys.push(keycode);
+
+        if self.has_candidate(&enabled, &candidate_keys) {
+            // Key joins the buffer
+            self.buffer.push(BufferedKey {
+                keycode,
+                pressed_at: self.clock.now(),
+            });
+            return self.try_trigger(&enabled, false);
+        }
+
+        // Key doesn't fit - resolve what's buffered, then handle this key on its own
+        let mut events = self.try_trigger(&enabled, true);
+        events.extend(self.on_press(keycode, active_layers));
+        events
+    }
+
+    /// Handle key release
+    pub fn on_release(&mut self, keycode: KeyCode, active_layers: &[Layer]) -> Vec<ComboEvent> {
+        let mut events = Vec::new();
+
+        // Releasing a buffered key ends the buffering
+        if self.buffer.iter().any(|b| b.keycode == keycode) {
+            let enabled = self.enabled_combos(active_layers);
+            events.extend(self.try_trigger(&enabled, true));
+        }
+
+        // Key belongs to a triggered combo
+        if let Some(pos) = self
+            .active_combos
+            .iter()
+            .position(|c| c.held_keys.contains(&keycode))
+        {
+            let active = &mut self.active_combos[pos];
+            active.held_keys.retain(|&k| k != keycode);
+            if !active.released {
+                active.released = true;
+                events.push(ComboEvent::ReleaseCombo(active.index));
+            }
+            if active.held_keys.is_empty() {
+                self.active_combos.remove(pos);
+            }
+            return events;
+        }
+
+        events.push(ComboEvent::ReleaseKey(keycode));
+        events
+    }
+
+    /// Check for an expired combo term
+    /// Should be called periodically (e.g., every 1ms in the idle loop)
+    pub fn check_timeouts(&mut self, active_layers: &[Layer]) -> Vec<ComboEvent> {
+        let Some(first) = self.buffer.first() else {
+            return Vec::new();
+        };
+
+        let enabled = self.enabled_combos(active_layers);
+        let buffered_keys: Vec<KeyCode> = self.buffer.iter().map(|b| b.keycode).collect();
+
+        // The longest term of the combos that could still complete applies
+        let term_ms = enabled
+            .iter()
+            .map(|&i| &self.combos[i])
+            .filter(|combo| buffered_keys.iter().all(|k| combo.keys.contains(k)))
+            .map(|combo| combo.term_ms.unwrap_or(self.config.combo_term_ms))
+            .max()
+            .unwrap_or(self.config.combo_term_ms);
+
+        if self.clock.elapsed(first.pressed_at).as_millis() > u128::from(term_ms) {
+            self.try_trigger(&enabled, true)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// When the combo term of the buffered keys may next expire (None = not buffering)
+    /// Assumes the shortest term of all combos, so it's never late
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let first = self.buffer.first()?;
+        let term_ms = self
+            .combos
+            .iter()
+            .filter_map(|combo| combo.term_ms)
+            .fold(self.config.combo_term_ms, u64::min);
+        Some(first.pressed_at + Duration::from_millis(term_ms))
+    }
+
+    /// Trigger the combo matching the buffer, or flush the buffer if `force` is set
+    fn try_trigger(&mut self, enabled: &[usize], force: bool) -> Vec<ComboEvent> {
+        let buffered_keys: Vec<KeyCode> = self.buffer.iter().map(|b| b.keycode).collect();
+        if buffered_keys.is_empty() {
+            return Vec::new();
+        }
+
+        let exact = enabled.iter().copied().find(|&i| {
+            let keys = &self.combos[i].keys;
+            keys.len() == buffered_keys.len() && buffered_keys.iter().all(|k| keys.contains(k))
+        });
+        let bigger_possible = enabled.iter().any(|&i| {
+            let keys = &self.combos[i].keys;
+            keys.len() > buffered_keys.len() && buffered_keys.iter().all(|k| keys.contains(k))
+        });
+
+        match exact {
+            Some(index) if force || !bigger_possible => {
+                self.buffer.clear();
+                self.active_combos.push(ActiveCombo {
+                    index,
+                    held_keys: buffered_keys,
+                    released: false,
+                });
+                vec![ComboEvent::PressCombo(index)]
+            }
+            _ if force => {
+                // No combo - replay the buffered presses in order
+                self.buffer
+                    .drain(..)
+                    .map(|b| ComboEvent::PressKey(b.keycode))
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether some enabled combo contains all of the given keys
+    fn has_candidate(&self, enabled: &[usize], keys: &[KeyCode]) -> bool {
+        enabled
+            .iter()
+            .any(|&i| keys.iter().all(|k| self.combos[i].keys.contains(k)))
+    }
+
+    /// Indices of combos enabled on the active layers
+    fn enabled_combos(&self, active_layers: &[Layer]) -> Vec<usize> {
+        self.combos
+            .iter()
+            .enumerate()
+            .filter(|(_, combo)| match &combo.layers {
+                Some(layers) => layers.iter().any(|l| active_layers.contains(l)),
+                None => true,
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
@@ -23,9 +23,19 @@
 /// - Single-tap emits when double_tap_window expires (even if no other key pressed)
 /// - Hold activates at tapping_term (typically < double_tap_window)
 /// - Double-tap must complete within double_tap_window from first press
-use crate::config::KeyCode;
+///
+/// Multi-tap dances (TD) reuse the same states, counting taps instead of
+/// stopping at two:
+/// - Each further tap must start within double_tap_window of the PREVIOUS press
+/// - Held beyond tapping_term → hold of the current tap count
+/// - Window expires after a release → tap of the current tap count
+/// - Released on the last step, or another key pressed → resolves immediately
+///
+/// DT keys can override tapping_term_ms and double_tap_window_ms per key.
+use crate::config::{Action, KeyCode, KeyOptions};
+use crate::event_processor::clock::Clock;
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// State of a double-tap key
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -46,9 +56,9 @@ pub struct DtKey {
     /// Physical keycode
     pub keycode: KeyCode,
     /// First action (single-tap/hold)
-    pub first_action_key: KeyCode,
+    pub first_action: Action,
     /// Second action (double-tap)
-    pub second_action_key: KeyCode,
+    pub second_action: Action,
     /// When first press occurred
     pub first_press_at: Instant,
     /// When first release occurred (if released)
@@ -57,29 +67,72 @@ pub struct DtKey {
     pub state: DtState,
     /// Has the action been emitted yet?
     pub action_emitted: bool,
+    /// Tapping term for this key (per-key option or global)
+    pub tapping_term_ms: u32,
+    /// Double-tap window for this key (per-key option or global)
+    pub double_tap_window_ms: u64,
 }
 
 impl DtKey {
-    pub fn new(keycode: KeyCode, first_key: KeyCode, second_key: KeyCode) -> Self {
+    pub fn new(
+        keycode: KeyCode,
+        first_action: Action,
+        second_action: Action,
+        first_press_at: Instant,
+        config: &DtConfig,
+        options: &KeyOptions,
+    ) -> Self {
         Self {
             keycode,
-            first_action_key: first_key,
-            second_action_key: second_key,
-            first_press_at: Instant::now(),
+            first_action,
+            second_action,
+            first_press_at,
             first_release_at: None,
             state: DtState::Pending,
             action_emitted: false,
+            tapping_term_ms: options.tapping_term_ms.unwrap_or(config.tapping_term_ms),
+            double_tap_window_ms: options
+                .double_tap_window_ms
+                .map_or(config.double_tap_window_ms, u64::from),
         }
     }
 
     /// Time since first press
-    pub fn elapsed_since_press(&self) -> u128 {
-        self.first_press_at.elapsed().as_millis()
+    pub fn elapsed_since_press(&self, now: Instant) -> u128 {
+        now.saturating_duration_since(self.first_press_at)
+            .as_millis()
     }
 
     /// Time since first release (if released)
-    pub fn elapsed_since_release(&self) -> Option<u128> {
-        self.first_release_at.map(|t| t.elapsed().as_millis())
+    pub fn elapsed_since_release(&self, now: Instant) -> Option<u128> {
+        self.first_release_at
+            .map(|t| now.saturating_duration_since(t).as_millis())
+    }
+}
+
+/// Multi-tap dance (TD) tracking
+#[derive(Debug, Clone)]
+pub struct TdKey {
+    /// Number of steps in the dance
+    pub step_count: usize,
+    /// Taps so far (including the current press)
+    pub taps: usize,
+    /// When the latest press occurred
+    pub last_press_at: Instant,
+    /// Current state (Pending, Tapped or Holding)
+    pub state: DtState,
+}
+
+impl TdKey {
+    /// Time since the latest press
+    pub fn elapsed_since_press(&self, now: Instant) -> u128 {
+        now.saturating_duration_since(self.last_press_at)
+            .as_millis()
+    }
+
+    /// Index of the step for the current tap count
+    pub fn step(&self) -> usize {
+        self.taps - 1
     }
 }
 
@@ -87,15 +140,19 @@ impl DtKey {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DtResolution {
     /// Start emitting first action as hold (press it)
-    HoldFirst(KeyCode),
+    HoldFirst(Action),
     /// Release held first action
-    ReleaseFirst(KeyCode),
+    ReleaseFirst(Action),
     /// Emit first action as tap (press+release)
-    TapFirst(KeyCode),
+    TapFirst(Action),
     /// Start emitting second action (press it) - for double-tap
-    PressSecond(KeyCode),
+    PressSecond(Action),
     /// Release second action
-    ReleaseSecond(KeyCode),
+    ReleaseSecond(Action),
+    /// Tap dance resolved to a tap of the step at this index
+    DanceTap(usize),
+    /// Tap dance resolved to a hold of the step at this index
+    DanceHold(usize),
     /// Still undecided
     Undecided,
 }
@@ -125,6 +182,12 @@ pub struct DtProcessor {
 
     /// Currently tracked DT keys
     tracked_keys: HashMap<KeyCode, DtKey>,
+
+    /// Currently tracked TD keys
+    tracked_dances: HashMap<KeyCode, TdKey>,
+
+    /// Time source
+    clock: Clock,
 }
 
 impl DtProcessor {
@@ -133,27 +196,38 @@ impl DtProcessor {
         Self {
             config,
             tracked_keys: HashMap::new(),
+            tracked_dances: HashMap::new(),
+            clock: Clock::system(),
         }
     }
 
+    /// Use a shared clock (event timestamps, simulated time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Handle key press
+    /// `options` override the config for this key (taken from the first press)
     pub fn on_press(
         &mut self,
         keycode: KeyCode,
-        first_key: KeyCode,
-        second_key: KeyCode,
+        first_action: Action,
+        second_action: Action,
+        options: &KeyOptions,
     ) -> DtResolution {
+        let now = self.clock.now();
         if let Some(dt_key) = self.tracked_keys.get_mut(&keycode) {
             // Already tracking this key - check if it's a second tap
             if dt_key.state == DtState::Tapped {
                 // Check if within double-tap window FROM FIRST PRESS (not release!)
                 // This matches QMK behavior - the entire double-tap sequence
                 // must happen within the double_tap_window_ms
-                if dt_key.elapsed_since_press() <= self.config.double_tap_window_ms as u128 {
+                if dt_key.elapsed_since_press(now) <= dt_key.double_tap_window_ms as u128 {
                     // Double-tap detected! Emit second action
                     dt_key.state = DtState::DoubleTapping;
                     dt_key.action_emitted = true;
-                    return DtResolution::PressSecond(dt_key.second_action_key);
+                    return DtResolution::PressSecond(dt_key.second_action.clone());
                 }
             }
 
@@ -162,7 +236,14 @@ impl DtProcessor {
         }
 
         // First press - start tracking
-        let dt_key = DtKey::new(keycode, first_key, second_key);
+        let dt_key = DtKey::new(
+            keycode,
+            first_action,
+            second_action,
+            now,
+            &self.config,
+            options,
+        );
         self.tracked_keys.insert(keycode, dt_key);
 
         DtResolution::Undecided
@@ -175,20 +256,20 @@ impl DtProcessor {
                 DtState::Pending => {
                     // Released quickly - transition to Tapped state
                     dt_key.state = DtState::Tapped;
-                    dt_key.first_release_at = Some(Instant::now());
+                    dt_key.first_release_at = Some(self.clock.now());
                     DtResolution::Undecided
                 }
                 DtState::Holding => {
                     // Was holding first action - release it
-                    let key = dt_key.first_action_key;
+                    let action = dt_key.first_action.clone();
                     self.tracked_keys.remove(&keycode);
-                    DtResolution::ReleaseFirst(key)
+                    DtResolution::ReleaseFirst(action)
                 }
                 DtState::DoubleTapping => {
                     // Was double-tapping - release second action
-                    let key = dt_key.second_action_key;
+                    let action = dt_key.second_action.clone();
                     self.tracked_keys.remove(&keycode);
-                    DtResolution::ReleaseSecond(key)
+                    DtResolution::ReleaseSecond(action)
                 }
                 DtState::Tapped => {
                     // Shouldn't happen (already released), but handle gracefully
@@ -200,23 +281,120 @@ impl DtProcessor {
         }
     }
 
+    /// Handle tap dance key press
+    pub fn on_dance_press(&mut self, keycode: KeyCode, step_count: usize) -> DtResolution {
+        let window = self.config.double_tap_window_ms as u128;
+        let now = self.clock.now();
+
+        if let Some(td_key) = self.tracked_dances.get_mut(&keycode) {
+            // Another tap of a running dance
+            if td_key.state == DtState::Tapped
+                && td_key.elapsed_since_press(now) <= window
+                && td_key.taps < step_count
+            {
+                td_key.taps += 1;
+                td_key.step_count = step_count;
+                td_key.last_press_at = now;
+                td_key.state = DtState::Pending;
+                return DtResolution::Undecided;
+            }
+
+            // Stale dance - start fresh
+            self.tracked_dances.remove(&keycode);
+        }
+
+        self.tracked_dances.insert(
+            keycode,
+            TdKey {
+                step_count,
+                taps: 1,
+                last_press_at: now,
+                state: DtState::Pending,
+            },
+        );
+        DtResolution::Undecided
+    }
+
+    /// Handle tap dance key release
+    pub fn on_dance_release(&mut self, keycode: KeyCode) -> DtResolution {
+        let Some(td_key) = self.tracked_dances.get_mut(&keycode) else {
+            return DtResolution::Undecided;
+        };
+
+        match td_key.state {
+            DtState::Pending if td_key.taps >= td_key.step_count => {
+                // Last step - nothing more to wait for
+                let step = td_key.step();
+                self.tracked_dances.remove(&keycode);
+                DtResolution::DanceTap(step)
+            }
+            DtState::Pending => {
+                // Wait for a further tap
+                td_key.state = DtState::Tapped;
+                DtResolution::Undecided
+            }
+            DtState::Holding => {
+                // The held action is released by the caller
+                self.tracked_dances.remove(&keycode);
+                DtResolution::Undecided
+            }
+            _ => DtResolution::Undecided,
+        }
+    }
+
+    /// Another key was pressed - resolve running dances right away
+    /// Held dances become holds, released dances become taps
+    pub fn interrupt_dances(&mut self, other_keycode: KeyCode) -> Vec<(KeyCode, DtResolution)> {
+        let keys: Vec<KeyCode> = self
+            .tracked_dances
+            .keys()
+            .copied()
+            .filter(|&k| k != other_keycode)
+            .collect();
+
+        keys.into_iter()
+            .filter_map(|keycode| {
+                self.resolve_dance(keycode)
+                    .map(|resolution| (keycode, resolution))
+            })
+            .collect()
+    }
+
+    /// Resolve a running dance based on whether it's currently held
+    fn resolve_dance(&mut self, keycode: KeyCode) -> Option<DtResolution> {
+        let td_key = self.tracked_dances.get_mut(&keycode)?;
+        match td_key.state {
+            DtState::Pending => {
+                td_key.state = DtState::Holding;
+                Some(DtResolution::DanceHold(td_key.step()))
+            }
+            DtState::Tapped => {
+                let step = td_key.step();
+                self.tracked_dances.remove(&keycode);
+                Some(DtResolution::DanceTap(step))
+            }
+            _ => None,
+        }
+    }
+
     /// Check for timeouts and state transitions
     /// Should be called periodically (e.g., on every key event)
     pub fn check_timeouts(&mut self) -> Vec<(KeyCode, DtResolution)> {
-        let mut resolutions = Vec::new();
-        let tapping_term = self.config.tapping_term_ms as u128;
-        let double_tap_window = self.config.double_tap_window_ms as u128;
+        let mut resolutions = self.check_dance_timeouts();
 
         // Collect keys that need state transitions
         let mut transitions = Vec::new();
+        let now = self.clock.now();
 
         for (keycode, dt_key) in &self.tracked_keys {
+            let tapping_term = dt_key.tapping_term_ms as u128;
+            let double_tap_window = dt_key.double_tap_window_ms as u128;
             match dt_key.state {
                 DtState::Pending => {
                     // Check if held beyond tapping term → transition to Holding
                     // BUT: only if tapping_term < double_tap_window
                     // This allows hold to activate while still in double-tap window
-                    if dt_key.elapsed_since_press() > tapping_term {
+                    if dt_key.elapsed_since_press(now) > tapping_term {
                         transitions.push((*keycode, DtState::Holding));
                     }
                 }
@@ -224,7 +402,7 @@ impl DtProcessor {
                     // Check if double-tap window expired → emit single-tap
                     // Use elapsed_since_press() for consistency - the entire interaction
                     // must complete within double_tap_window_ms
-                    if dt_key.elapsed_since_press() > double_tap_window {
+                    if dt_key.elapsed_since_press(now) > double_tap_window {
                         transitions.push((*keycode, DtState::Tapped)); // Mark for cleanup
                     }
                 }
@@ -240,14 +418,16 @@ impl DtProcessor {
                         // Transition to holding first action
                         dt_key.state = DtState::Holding;
                         dt_key.action_emitted = true;
-                        resolutions
-                            .push((keycode, DtResolution::HoldFirst(dt_key.first_action_key)));
+                        resolutions.push((
+                            keycode,
+                            DtResolution::HoldFirst(dt_key.first_action.clone()),
+                        ));
                     }
                     DtState::Tapped => {
                         // Timeout expired in Tapped state → emit single-tap
-                        let key = dt_key.first_action_key;
+                        let action = dt_key.first_action.clone();
                         self.tracked_keys.remove(&keycode);
-                        resolutions.push((keycode, DtResolution::TapFirst(key)));
+                        resolutions.push((keycode, DtResolution::TapFirst(action)));
                     }
                     _ => {}
                 }
@@ -257,8 +437,55 @@ impl DtProcessor {
         resolutions
     }
 
+    /// Tap dances: hold at tapping_term, tap once the window after a release expires
+    fn check_dance_timeouts(&mut self) -> Vec<(KeyCode, DtResolution)> {
+        let tapping_term = self.config.tapping_term_ms as u128;
+        let window = self.config.double_tap_window_ms as u128;
+        let now = self.clock.now();
+
+        let expired: Vec<KeyCode> = self
+            .tracked_dances
+            .iter()
+            .filter(|(_, td_key)| match td_key.state {
+                DtState::Pending => td_key.elapsed_since_press(now) > tapping_term,
+                DtState::Tapped => td_key.elapsed_since_press(now) > window,
+                _ => false,
+            })
+            .map(|(keycode, _)| *keycode)
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|keycode| {
+                self.resolve_dance(keycode)
+                    .map(|resolution| (keycode, resolution))
+            })
+            .collect()
+    }
+
+    /// Earliest time a tracked key or dance may time out (None = nothing tracked)
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let keys = self.tracked_keys.values().filter_map(|dt_key| {
+            let timeout_ms = match dt_key.state {
+                DtState::Pending => u64::from(dt_key.tapping_term_ms),
+                DtState::Tapped => dt_key.double_tap_window_ms,
+                _ => return None,
+            };
+            Some(dt_key.first_press_at + Duration::from_millis(timeout_ms))
+        });
+        let dances = self.tracked_dances.values().filter_map(|td_key| {
+            let timeout_ms = match td_key.state {
+                DtState::Pending => u64::from(self.config.tapping_term_ms),
+                DtState::Tapped => self.config.double_tap_window_ms,
+                _ => return None,
+            };
+            Some(td_key.last_press_at + Duration::from_millis(timeout_ms))
+        });
+        keys.chain(dances).min()
+    }
+
     /// Get currently tracked keys (for debugging)
     pub fn tracked_count(&self) -> usize {
-        self.tracked_keys.len()
+        self.tracked_keys.len() + self.tracked_dances.len()
     }
 }
@@ -0,0 +1,165 @@
+/// Leader processor - QMK-inspired leader key sequences
+///
+/// Capture Model:
+/// - Pressing a LEADER key starts capturing the following key presses
+/// - Captured keys are held back (not processed) and matched against the sequences
+/// - Each key must follow the previous one within the leader timeout
+///
+/// Resolution:
+/// - Captured keys match a sequence and no longer sequence starts with them → matched
+/// - Captured keys can't start any sequence → unmatched
+/// - Timeout expires → matched if the captured keys are a complete sequence, else unmatched
+///
+/// Releases of captured keys are swallowed, so the rest of the keymap never sees them.
+/// Unmatched keys are swallowed or replayed by the keymap, depending on config.
+use crate::config::{KeyCode, LeaderSequence};
+use crate::event_processor::clock::Clock;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Result of leader processing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaderEvent {
+    /// Key was captured, sequence not decided yet
+    Pending,
+    /// Captured keys matched the sequence with this index
+    Matched(usize),
+    /// Captured keys matched no sequence (keys in press order)
+    Unmatched(Vec<KeyCode>),
+}
+
+/// Leader processor configuration
+#[derive(Debug, Clone)]
+pub struct LeaderConfig {
+    /// Max time between keys of a sequence (ms)
+    pub leader_timeout_ms: u64,
+}
+
+impl Default for LeaderConfig {
+    fn default() -> Self {
+        Self {
+            leader_timeout_ms: 500,
+        }
+    }
+}
+
+/// Leader processor - captures the keys typed after a leader key
+pub struct LeaderProcessor {
+    /// Config
+    config: LeaderConfig,
+
+    /// All sequence definitions
+    sequences: Vec<LeaderSequence>,
+
+    /// Keys captured so far (None = not capturing)
+    captured: Option<Vec<KeyCode>>,
+
+    /// When the leader key or the last captured key was pressed
+    last_press_at: Instant,
+
+    /// Captured keys still physically held (their releases get swallowed)
+    held_keys: HashSet<KeyCode>,
+
+    /// Time source
+    clock: Clock,
+}
+
+impl LeaderProcessor {
+    /// Create new leader processor
+    pub fn new(config: LeaderConfig, sequences: Vec<LeaderSequence>) -> Self {
+        Self {
+            config,
+            sequences,
+            captured: None,
+            last_press_at: Instant::now(),
+            held_keys: HashSet::new(),
+            clock: Clock::system(),
+        }
+    }
+
+    /// Use a shared clock (event timestamps, simulated time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Get a sequence definition by index
+    pub fn sequence(&self, index: usize) -> &LeaderSequence {
+        &self.sequences[index]
+    }
+
+    /// Whether key presses are currently being captured
+    pub fn is_capturing(&self) -> bool {
+        self.captured.is_some()
+    }
+
+    /// Start capturing (LEADER key pressed)
+    pub fn start(&mut self) {
+        self.captured = Some(Vec::new());
+        self.last_press_at = self.clock.now();
+    }
+
+    /// Stop capturing without resolving the captured keys
+    /// Releases of keys captured so far are still swallowed
+    pub fn cancel(&mut self) {
+        self.captured = None;
+    }
+
+    /// Handle key press while capturing
+    pub fn on_press(&mut self, keycode: KeyCode) -> LeaderEvent {
+        let Some(captured) = self.captured.as_mut() else {
+            return LeaderEvent::Pending;
+        };
+        captured.push(keycode);
+        self.held_keys.insert(keycode);
+        self.last_press_at = self.clock.now();
+
+        let captured = captured.clone();
+        let exact = self.sequences.iter().position(|s| s.keys == captured);
+        let longer_possible = self
+            .sequences
+            .iter()
+            .any(|s| s.keys.len() > captured.len() && s.keys.starts_with(&captured));
+
+        match exact {
+            Some(index) if !longer_possible => self.finish(LeaderEvent::Matched(index)),
+            None if !longer_possible => self.finish(LeaderEvent::Unmatched(captured)),
+            _ => LeaderEvent::Pending,
+        }
+    }
+
+    /// Handle key release
+    /// Returns true if the release belongs to a captured key and must be swallowed
+    pub fn on_release(&mut self, keycode: KeyCode) -> bool {
+        self.held_keys.remove(&keycode)
+    }
+
+    /// Check for an expired leader timeout
+    /// Should be called periodically (e.g., every 1ms in the idle loop)
+    pub fn check_timeouts(&mut self) -> Option<LeaderEvent> {
+        let captured = self.captured.as_ref()?;
+        let elapsed = self.clock.elapsed(self.last_press_at);
+        if elapsed.as_millis() <= u128::from(self.config.leader_timeout_ms) {
+            return None;
+        }
+
+        let event = match self.sequences.iter().position(|s| &s.keys == captured) {
+            Some(index) => LeaderEvent::Matched(index),
+            None => LeaderEvent::Unmatched(captured.clone()),
+        };
+        Some(self.finish(event))
+    }
+
+    /// When the leader timeout may next expire (None = not capturing)
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.captured
+            .as_ref()
+            .map(|_| self.last_press_at + Duration::from_millis(self.config.leader_timeout_ms))
+    }
+
+    /// Stop capturing
+    fn finish(&mut self, event: LeaderEvent) -> LeaderEvent {
+        self.captured = None;
+        event
+    }
+}
@@ -0,0 +1,98 @@
+/// Macro player - plays SEQ macros without blocking the event loop
+///
+/// Playback Model:
+/// - Steps are emitted immediately up to the first Delay
+/// - A Delay pauses playback; the rest is emitted from check_timeouts once it expires
+/// - A macro started while another one is playing is queued behind it
+///
+/// While a macro is playing, the keymap holds back physical key events and
+/// processes them after playback, so they never interleave with macro events.
+use crate::config::{KeyCode, MacroStep};
+use crate::event_processor::clock::Clock;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Macro player - queue of macro steps waiting to be emitted
+pub struct MacroPlayer {
+    /// Steps not emitted yet
+    steps: VecDeque<MacroStep>,
+
+    /// Playback is paused by a Delay until this time
+    resume_at: Option<Instant>,
+
+    /// Time source
+    clock: Clock,
+}
+
+impl MacroPlayer {
+    /// Create new macro player
+    pub fn new() -> Self {
+        Self {
+            steps: VecDeque::new(),
+            resume_at: None,
+            clock: Clock::system(),
+        }
+    }
+
+    /// Use a shared clock (event timestamps, simulated time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Whether a macro is still playing (steps left or a Delay running)
+    pub fn is_playing(&self) -> bool {
+        !self.steps.is_empty() || self.resume_at.is_some()
+    }
+
+    /// Start playing a macro
+    /// Returns the key events that can be emitted right away
+    pub fn play(&mut self, steps: &[MacroStep]) -> Vec<(KeyCode, bool)> {
+        self.steps.extend(steps.iter().cloned());
+        self.advance()
+    }
+
+    /// Continue playback after a Delay
+    /// Should be called periodically (e.g., every 1ms in the idle loop)
+    pub fn advance(&mut self) -> Vec<(KeyCode, bool)> {
+        let mut events = Vec::new();
+
+        loop {
+            if let Some(resume_at) = self.resume_at {
+                if self.clock.now() < resume_at {
+                    break;
+                }
+                self.resume_at = None;
+            }
+
+            match self.steps.pop_front() {
+                Some(MacroStep::Down(key)) => events.push((key, true)),
+                Some(MacroStep::Up(key)) => events.push((key, false)),
+                Some(MacroStep::Tap(key)) => {
+                    events.push((key, true));
+                    events.push((key, false));
+                }
+                Some(MacroStep::Delay(ms)) => {
+                    self.resume_at = Some(self.clock.now() + Duration::from_millis(ms));
+                }
+                None => break,
+            }
+        }
+
+        events
+    }
+
+    /// When playback continues (None = not playing)
+    pub fn next_deadline(&self) -> Option<Instant> {
+        match self.resume_at {
+            Some(resume_at) => Some(resume_at),
+            None => (!self.steps.is_empty()).then(|| self.clock.now()),
+        }
+    }
+}
+
+impl Default for MacroPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
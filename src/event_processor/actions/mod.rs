@@ -4,13 +4,28 @@
 //! - MT (Mod-Tap): Tap/hold dual-function keys
 //! - DT (Double-Tap): Tap dance with single/double-tap detection
 //! - OSM (OneShot Modifier): One-shot modifiers that auto-release
+//! - Combos: Chords of simultaneous keys that trigger an action
+//! - Macros: Non-blocking playback of SEQ macros
+//! - Leader: Key sequences typed after a LEADER key
+//! - Caps Word: Shifts letters until the end of the word
+//! - Mouse Keys: Cursor movement, scrolling and buttons from keys
 //! - SOCD (future): Simultaneous Opposite Cardinal Direction handling
 
+pub mod capsword;
+pub mod combo;
 pub mod doubletap;
+pub mod leader;
+pub mod macros;
 pub mod modtap;
+pub mod mousekeys;
 pub mod oneshot;
 
 // Re-export commonly used types
+pub use capsword::{CapsWordKey, CapsWordProcessor};
+pub use combo::{ComboConfig, ComboEvent, ComboProcessor};
 pub use doubletap::{DtConfig, DtProcessor, DtResolution};
+pub use leader::{LeaderConfig, LeaderEvent, LeaderProcessor};
+pub use macros::MacroPlayer;
 pub use modtap::{MtAction, MtConfig, MtProcessor, MtResolution, RollingStats};
+pub use mousekeys::{MouseButton, MouseEvent, MouseKeysProcessor};
 pub use oneshot::{OsmConfig, OsmProcessor, OsmResolution};
@@ -1,26 +1,23 @@
-use crate::config::KeyCode;
+use crate::config::{Action, Hand, KeyCode, KeyOptions, MtFlavor};
+use crate::event_processor::clock::Clock;
 use serde::{Deserialize, Serialize};
 /// Advanced Mod-Tap (MT) system inspired by QMK
 ///
 /// This module implements a comprehensive dual-role key system with:
-/// - Basic tap/hold timing (like QMK MT)
+/// - Basic tap/hold timing (like QMK MT), hold pressed once the tapping term expires
 /// - Same-hand roll detection (favors tap)
 /// - Opposite-hand chord detection (favors hold)
 /// - Multi-mod same-hand chord detection
-/// - Adaptive timing per-key/per-pair
+/// - Adaptive timing per-key and per-bigram (previous key → MT key)
+/// - Misfire penalties (corrected via Backspace) shifting the adaptive threshold
+/// - Per-key options (tapping term, permissive hold, ...) over the global config
+/// - Require-prior-idle (flow tap): MT keys pressed while typing are taps right away
+/// - QMK/ZMK hold-tap flavors as precise alternatives to the heuristics (see `MtFlavor`)
 /// - Predictive intent scoring
 /// - Configurable behavior options
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-/// Hand assignment for a key (for chord detection)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Hand {
-    Left,
-    Right,
-    Unknown,
-}
-
 /// State of an MT key
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MtKeyState {
@@ -43,10 +40,10 @@ pub enum MtKeyState {
 pub struct MtKey {
     /// Physical keycode
     pub keycode: KeyCode,
-    /// Tap output (what key to emit on tap)
-    pub tap_key: KeyCode,
-    /// Hold output (what modifier to emit on hold)
-    pub hold_key: KeyCode,
+    /// Tap output (what action to run on tap)
+    pub tap_action: Action,
+    /// Hold output (what action to run on hold, usually a modifier)
+    pub hold_action: Action,
     /// When this key was pressed
     pub pressed_at: Instant,
     /// Current state
@@ -55,33 +52,90 @@ pub struct MtKey {
     pub hold_intent_score: f32,
     /// Which hand this key is on
     pub hand: Hand,
+    /// Another key was pressed while this key was down
+    pub interrupted: bool,
+    /// Keys pressed while this key was undecided (balanced flavor)
+    pub other_presses: Vec<KeyCode>,
+    /// Tapping term for this key (per-key option or global)
+    pub tapping_term_ms: u32,
+    /// Permissive hold for this key (per-key option or global)
+    pub permissive_hold: bool,
+    /// Hold-do-nothing-emits-tap for this key (per-key option or global)
+    pub hold_do_nothing_emits_tap: bool,
+    /// Require-prior-idle window for this key (per-key option or global, 0 = off)
+    pub require_prior_idle_ms: u32,
+    /// Double-tap-then-hold for this key (per-key option or global)
+    pub double_tap_then_hold: bool,
+    /// Double-tap window for this key (per-key option or global)
+    pub double_tap_window_ms: u32,
+    /// Key pressed before this one and the time between the presses (ms), for bigram stats
+    pub prev_key: Option<(KeyCode, u32)>,
+    /// How long the previous key stayed held after this key's press (ms), once known
+    pub prev_overlap_ms: Option<u32>,
 }
 
 impl MtKey {
-    pub fn new(keycode: KeyCode, tap_key: KeyCode, hold_key: KeyCode, hand: Hand) -> Self {
+    pub fn new(
+        keycode: KeyCode,
+        tap_action: Action,
+        hold_action: Action,
+        hand: Hand,
+        pressed_at: Instant,
+        config: &MtConfig,
+        options: &KeyOptions,
+    ) -> Self {
         Self {
             keycode,
-            tap_key,
-            hold_key,
-            pressed_at: Instant::now(),
+            tap_action,
+            hold_action,
+            pressed_at,
             state: MtKeyState::Undecided,
             hold_intent_score: 0.0,
             hand,
+            interrupted: false,
+            other_presses: Vec::new(),
+            tapping_term_ms: options.tapping_term_ms.unwrap_or(config.tapping_term_ms),
+            permissive_hold: options.permissive_hold.unwrap_or(config.permissive_hold),
+            hold_do_nothing_emits_tap: options
+                .hold_do_nothing_emits_tap
+                .unwrap_or(config.hold_do_nothing_emits_tap),
+            require_prior_idle_ms: options
+                .require_prior_idle_ms
+                .unwrap_or(config.require_prior_idle_ms),
+            double_tap_then_hold: options
+                .double_tap_then_hold
+                .unwrap_or(config.double_tap_then_hold),
+            double_tap_window_ms: options
+                .double_tap_window_ms
+                .unwrap_or(config.double_tap_window_ms),
+            prev_key: None,
+            prev_overlap_ms: None,
         }
     }
 
     /// Get duration since press
-    pub fn duration(&self) -> Duration {
-        Instant::now() - self.pressed_at
+    pub fn duration(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.pressed_at)
     }
 
     /// Get duration in milliseconds
-    pub fn duration_ms(&self) -> u64 {
-        self.duration().as_millis() as u64
+    pub fn duration_ms(&self, now: Instant) -> u64 {
+        self.duration(now).as_millis() as u64
     }
 }
 
+/// Previous key pressed longer ago than this (ms) - an isolated press, not a bigram
+const BIGRAM_WINDOW_MS: u32 = 500;
+
+/// Bigram taps needed before a bigram's threshold is used over the key's own
+const MIN_BIGRAM_SAMPLES: u32 = 5;
+
+/// Threshold shift per net misfire (ms) and its limit
+const MISFIRE_SHIFT_MS: f32 = 10.0;
+const MAX_MISFIRE_SHIFT_MS: f32 = 100.0;
+
 /// Rolling statistics for adaptive timing
+/// Per key, or per bigram (previous key → MT key) with the interval and overlap
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RollingStats {
     /// Average tap duration for this key (ms) - when user taps quickly
@@ -90,6 +144,18 @@ pub struct RollingStats {
     pub tap_sample_count: u32,
     /// Adaptive threshold for this key (ms) - stays ~30ms above avg tap
     pub adaptive_threshold: f32,
+    /// Average time from the previous key's press to this key's press (ms, bigrams only)
+    #[serde(default)]
+    pub avg_interval_ms: f32,
+    /// Average time the previous key stayed held after this key's press (ms, bigrams only)
+    #[serde(default)]
+    pub avg_overlap_ms: f32,
+    /// Holds corrected to a tap (Backspace, then the key retyped) - raise the threshold
+    #[serde(default)]
+    pub hold_misfires: u32,
+    /// Taps corrected to a hold (Backspace, then the key held) - lower the threshold
+    #[serde(default)]
+    pub tap_misfires: u32,
 }
 
 impl RollingStats {
@@ -98,6 +164,30 @@ impl RollingStats {
             avg_tap_duration: 0.0,
             tap_sample_count: 0,
             adaptive_threshold: base_threshold,
+            avg_interval_ms: 0.0,
+            avg_overlap_ms: 0.0,
+            hold_misfires: 0,
+            tap_misfires: 0,
+        }
+    }
+
+    /// Threshold shift from the misfire penalties (ms)
+    /// Each hold misfire adds MISFIRE_SHIFT_MS, each tap misfire takes it away
+    pub fn misfire_shift_ms(&self) -> f32 {
+        let net_misfires = self.hold_misfires as f32 - self.tap_misfires as f32;
+        (net_misfires * MISFIRE_SHIFT_MS).clamp(-MAX_MISFIRE_SHIFT_MS, MAX_MISFIRE_SHIFT_MS)
+    }
+
+    /// Update bigram timing with a new interval and overlap (before `update_tap`)
+    pub fn update_bigram(&mut self, interval_ms: f32, overlap_ms: f32) {
+        const ALPHA: f32 = 0.02; // Same smoothing as the tap durations
+
+        if self.tap_sample_count == 0 {
+            self.avg_interval_ms = interval_ms;
+            self.avg_overlap_ms = overlap_ms;
+        } else {
+            self.avg_interval_ms = ALPHA * interval_ms + (1.0 - ALPHA) * self.avg_interval_ms;
+            self.avg_overlap_ms = ALPHA * overlap_ms + (1.0 - ALPHA) * self.avg_overlap_ms;
         }
     }
 
@@ -187,8 +277,17 @@ pub struct MtConfig {
 
     /// When holding an MT key and doing nothing, emit tap on release
     /// If true, holding then releasing without other action sends the tap key
+    /// (after the hold that was pressed at the tapping term is released, the keymap
+    /// neutralizes a lone Alt/GUI first)
     /// If false, holding then releasing without other action does nothing
     pub hold_do_nothing_emits_tap: bool,
+
+    /// Hold-tap flavor - Heuristic uses the options above, the others are precise
+    pub flavor: MtFlavor,
+
+    /// Require-prior-idle (flow tap) - an MT key pressed within this many ms of a
+    /// non-modifier key press resolves to tap right away (0 = off)
+    pub require_prior_idle_ms: u32,
 }
 
 impl Default for MtConfig {
@@ -209,6 +308,8 @@ impl Default for MtConfig {
             cross_hand_unwrap: true,
             adaptive_target_margin_ms: 30,
             hold_do_nothing_emits_tap: true,
+            flavor: MtFlavor::Heuristic,
+            require_prior_idle_ms: 0,
         }
     }
 }
@@ -225,27 +326,40 @@ pub struct MtProcessor {
     held_keys: HashMap<KeyCode, MtKey>,
 
     /// Rolling statistics for adaptive timing
-    /// Key: (key1, key2) pair or single key
+    /// Key: (key, key) for a single key, (previous key, MT key) for a bigram
     rolling_stats: HashMap<(KeyCode, KeyCode), RollingStats>,
 
-    /// Hand assignment map
+    /// Hand assignment map (QWERTY default, the config's hands section on top)
     hand_map: HashMap<KeyCode, Hand>,
 
-    /// History of recent key presses (for pattern detection)
+    /// History of recent MT key presses (for pattern detection)
     /// Stores (keycode, timestamp) tuples
     recent_presses: Vec<(KeyCode, Instant)>,
 
+    /// History of recent key presses (for require-prior-idle and bigrams)
+    /// Stores (keycode, timestamp) tuples of every key press, MT keys included
+    press_history: Vec<(KeyCode, Instant)>,
+
     /// Maximum history to keep
     max_history: usize,
 
+    /// Last release of each key (for bigram overlap)
+    last_release: HashMap<KeyCode, Instant>,
+
     /// Last tap time for each key (for double-tap detection)
     last_tap_time: HashMap<KeyCode, Instant>,
 
-    /// Keys currently holding their tap key (double-tap-then-hold)
-    holding_tap_key: HashMap<KeyCode, KeyCode>,
+    /// Tapping term each MT key was last pressed with (seeds its misfire stats)
+    tapping_terms: HashMap<KeyCode, u32>,
+
+    /// Keys currently holding their tap action (double-tap-then-hold)
+    holding_tap_key: HashMap<KeyCode, Action>,
 
     /// Game mode active (when true, pause adaptive timing learning)
     game_mode_active: bool,
+
+    /// Time source
+    clock: Clock,
 }
 
 impl MtProcessor {
@@ -258,13 +372,23 @@ impl MtProcessor {
             rolling_stats: HashMap::new(),
             hand_map: Self::build_default_hand_map(),
             recent_presses: Vec::new(),
+            press_history: Vec::new(),
             max_history: 10,
+            last_release: HashMap::new(),
             last_tap_time: HashMap::new(),
+            tapping_terms: HashMap::new(),
             holding_tap_key: HashMap::new(),
             game_mode_active: false,
+            clock: Clock::system(),
         }
     }
 
+    /// Use a shared clock (event timestamps, simulated time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Build default hand assignment map (QWERTY layout)
     fn build_default_hand_map() -> HashMap<KeyCode, Hand> {
         let mut map = HashMap::new();
@@ -366,44 +490,84 @@ impl MtProcessor {
     }
 
     /// Add key press (MT key pressed)
+    /// `options` override the config for this key
     /// Returns Some(resolution) if double-tap detected or cross-hand unwrap triggered
     pub fn on_press(
         &mut self,
         keycode: KeyCode,
-        tap_key: KeyCode,
-        hold_key: KeyCode,
+        tap_action: Action,
+        hold_action: Action,
+        options: &KeyOptions,
     ) -> Option<MtResolution> {
         tracing::info!(
             "ADAPTIVE: MT key pressed: {:?} (tap={:?}, hold={:?})",
             keycode,
-            tap_key,
-            hold_key
+            tap_action,
+            hold_action
         );
 
+        let hand = self.get_hand(keycode);
+        let mut mt_key = MtKey::new(
+            keycode,
+            tap_action.clone(),
+            hold_action,
+            hand,
+            self.clock.now(),
+            &self.config,
+            options,
+        );
+        self.tapping_terms.insert(keycode, mt_key.tapping_term_ms);
+
         // Check for double-tap
-        if self.config.double_tap_then_hold {
+        if mt_key.double_tap_then_hold {
             if let Some(last_tap) = self.last_tap_time.get(&keycode) {
-                let elapsed = Instant::now().duration_since(*last_tap).as_millis() as u32;
-                if elapsed < self.config.double_tap_window_ms {
-                    // Double-tap detected! Hold the tap key until released
-                    self.holding_tap_key.insert(keycode, tap_key);
+                let elapsed = self.clock.elapsed(*last_tap).as_millis() as u32;
+                if elapsed < mt_key.double_tap_window_ms {
+                    // Double-tap detected! Hold the tap action until released
+                    self.holding_tap_key.insert(keycode, tap_action.clone());
                     return Some(MtResolution {
                         keycode,
-                        action: MtAction::HoldPress(tap_key),
+                        action: MtAction::HoldPress(tap_action),
                     });
                 }
             }
         }
 
-        let hand = self.get_hand(keycode);
-        let mut mt_key = MtKey::new(keycode, tap_key, hold_key, hand);
+        // Bigram: the previous key, and whether it was already released
+        if let Some(&(prev, prev_pressed_at)) = self.prior_presses(keycode).next() {
+            let interval = mt_key.pressed_at.saturating_duration_since(prev_pressed_at);
+            mt_key.prev_key = Some((prev, interval.as_millis() as u32));
+            if self
+                .last_release
+                .get(&prev)
+                .is_some_and(|&released_at| released_at >= prev_pressed_at)
+            {
+                mt_key.prev_overlap_ms = Some(0);
+            }
+        }
+
+        // Require-prior-idle: pressed while typing - tap right away
+        if mt_key.require_prior_idle_ms > 0
+            && self.typed_within(keycode, mt_key.require_prior_idle_ms)
+        {
+            tracing::debug!(
+                "MT key {:?} pressed while typing, resolving to tap",
+                keycode
+            );
+            mt_key.state = MtKeyState::Tap;
+            self.held_keys.insert(keycode, mt_key);
+
+            return Some(MtResolution {
+                keycode,
+                action: MtAction::TapPress(tap_action),
+            });
+        }
 
         // Check for cross-hand unwrap
         if self.config.cross_hand_unwrap && hand != Hand::Unknown {
-            // Check if there are any held modifiers on the opposite hand
+            // Check if there are any held modifiers on the opposite hand (thumbs are either)
             let has_opposite_hand_mod = self.held_keys.values().any(|held_key| {
-                let held_hand = held_key.hand;
-                held_hand != Hand::Unknown && held_hand != hand
+                held_key.state != MtKeyState::Tap && held_key.hand.is_opposite(hand)
             });
 
             if has_opposite_hand_mod {
@@ -413,7 +577,7 @@ impl MtProcessor {
 
                 return Some(MtResolution {
                     keycode,
-                    action: MtAction::TapPress(tap_key),
+                    action: MtAction::TapPress(tap_action),
                 });
             }
         }
@@ -424,7 +588,7 @@ impl MtProcessor {
         }
 
         // Add to recent presses history
-        self.recent_presses.push((keycode, Instant::now()));
+        self.recent_presses.push((keycode, self.clock.now()));
         if self.recent_presses.len() > self.max_history {
             self.recent_presses.remove(0);
         }
@@ -434,10 +598,48 @@ impl MtProcessor {
     }
 
     /// Another key pressed while MT key is pending (permissive hold trigger)
+    /// Must be called for every key press, MT keys included (before their `on_press`),
+    /// as it also records the press history
     pub fn on_other_key_press(&mut self, other_keycode: KeyCode) -> Vec<MtResolution> {
         let mut resolutions = Vec::new();
 
-        if !self.config.permissive_hold
+        // Add to the press history (held back presses are added when replayed)
+        if !self.holds_back_other_keys() {
+            self.press_history.push((other_keycode, self.clock.now()));
+            if self.press_history.len() > self.max_history {
+                self.press_history.remove(0);
+            }
+        }
+
+        // Held and pending keys no longer count as "held and did nothing"
+        for mt_key in self
+            .undecided_keys
+            .values_mut()
+            .chain(self.held_keys.values_mut())
+        {
+            mt_key.interrupted = true;
+        }
+
+        match self.config.flavor {
+            MtFlavor::Heuristic => {}
+            MtFlavor::HoldPreferred | MtFlavor::TapUnlessInterrupted | MtFlavor::RetroTap => {
+                return self.resolve_all_to_hold();
+            }
+            MtFlavor::Balanced => {
+                // Decided when this key is released (hold) or the MT key is (tap)
+                for mt_key in self.undecided_keys.values_mut() {
+                    mt_key.other_presses.push(other_keycode);
+                }
+                return resolutions;
+            }
+            MtFlavor::TapPreferred => return resolutions,
+        }
+
+        let any_permissive_hold = self
+            .undecided_keys
+            .values()
+            .any(|mt_key| mt_key.permissive_hold);
+        if !any_permissive_hold
             && !self.config.same_hand_roll_detection
             && !self.config.opposite_hand_chord_detection
         {
@@ -445,18 +647,18 @@ impl MtProcessor {
         }
 
         let other_hand = self.get_hand(other_keycode);
-        let now = Instant::now();
+        let now = self.clock.now();
 
         // Check each undecided key
         let undecided: Vec<_> = self.undecided_keys.keys().copied().collect();
 
         for keycode in undecided {
             if let Some(mt_key) = self.undecided_keys.get(&keycode) {
-                let time_since_press = (now - mt_key.pressed_at).as_millis() as u32;
+                let time_since_press = mt_key.duration_ms(now) as u32;
 
                 // Check for same-hand roll
                 if self.config.same_hand_roll_detection
-                    && mt_key.hand != Hand::Unknown
+                    && mt_key.hand.is_side()
                     && mt_key.hand == other_hand
                     && time_since_press < self.config.roll_detection_window_ms
                 {
@@ -469,8 +671,8 @@ impl MtProcessor {
 
                 // Check for opposite-hand chord
                 if self.config.opposite_hand_chord_detection
-                    && mt_key.hand != Hand::Unknown
-                    && other_hand != Hand::Unknown
+                    && mt_key.hand.is_side()
+                    && other_hand.is_side()
                     && mt_key.hand != other_hand
                     && time_since_press < self.config.chord_detection_window_ms
                 {
@@ -482,7 +684,7 @@ impl MtProcessor {
                 }
 
                 // Standard permissive hold
-                if self.config.permissive_hold {
+                if mt_key.permissive_hold {
                     if let Some(resolved) = self.resolve_to_hold(keycode) {
                         resolutions.push(resolved);
                     }
@@ -499,27 +701,57 @@ impl MtProcessor {
         resolutions
     }
 
+    /// Another key released while MT keys are pending
+    /// Balanced flavor: an MT key held across another key's whole press resolves to hold
+    pub fn on_other_key_release(&mut self, other_keycode: KeyCode) -> Vec<MtResolution> {
+        if self.config.flavor != MtFlavor::Balanced {
+            return Vec::new();
+        }
+
+        let mut pressed_over: Vec<&MtKey> = self
+            .undecided_keys
+            .values()
+            .filter(|mt_key| mt_key.other_presses.contains(&other_keycode))
+            .collect();
+        pressed_over.sort_by_key(|mt_key| mt_key.pressed_at);
+        let pressed_over: Vec<KeyCode> = pressed_over.iter().map(|mt_key| mt_key.keycode).collect();
+
+        pressed_over
+            .into_iter()
+            .filter_map(|keycode| self.resolve_to_hold(keycode))
+            .collect()
+    }
+
+    /// Whether key presses must be held back until the pending MT keys are decided
+    /// (balanced and tap-preferred flavors decide after the other key was pressed)
+    pub fn holds_back_other_keys(&self) -> bool {
+        matches!(
+            self.config.flavor,
+            MtFlavor::Balanced | MtFlavor::TapPreferred
+        ) && !self.undecided_keys.is_empty()
+    }
+
     /// MT key released
     pub fn on_release(&mut self, keycode: KeyCode) -> Option<MtResolution> {
         // Check if this key is holding its tap key (double-tap-then-hold)
-        if let Some(tap_key) = self.holding_tap_key.remove(&keycode) {
-            // Release the held tap key
+        if let Some(tap_action) = self.holding_tap_key.remove(&keycode) {
+            // Release the held tap action
             return Some(MtResolution {
                 keycode,
-                action: MtAction::ReleaseHold(tap_key),
+                action: MtAction::ReleaseHold(tap_action),
             });
         }
 
         // Check if it's an undecided key
         if let Some(mt_key) = self.undecided_keys.remove(&keycode) {
-            let duration_ms = mt_key.duration_ms() as u32;
+            let duration_ms = mt_key.duration_ms(self.clock.now()) as u32;
+
+            if self.config.flavor != MtFlavor::Heuristic {
+                return Some(self.resolve_released(mt_key, duration_ms));
+            }
 
             // Decide based on timing and intent score
-            let effective_threshold = if self.config.adaptive_timing {
-                self.get_adaptive_threshold(keycode)
-            } else {
-                self.config.tapping_term_ms
-            };
+            let effective_threshold = self.effective_threshold(&mt_key);
 
             let should_hold = if self.config.predictive_scoring {
                 // Use intent score with timing
@@ -531,27 +763,15 @@ impl MtProcessor {
 
             // Check if we should emit tap instead of hold when held past threshold
             let is_hold_timing = duration_ms >= effective_threshold;
-            let emit_tap_on_hold_timeout = is_hold_timing
-                && self.config.hold_do_nothing_emits_tap
-                && mt_key.hold_intent_score <= 0.5; // No strong intent for hold
+            let emit_tap_on_hold_timeout = is_hold_timing && self.retro_taps(&mt_key);
 
             if emit_tap_on_hold_timeout {
                 // Hold-do-nothing-emits-tap: emit tap even though held past threshold
-                // Record tap time for double-tap detection
-                if self.config.double_tap_then_hold {
-                    self.last_tap_time.insert(keycode, Instant::now());
-                }
-
-                // Record ONLY taps (below threshold) for adaptive timing
-                // This prevents survivorship bias - only successful taps are tracked
-                // Skip recording when game mode is active
-                if self.config.adaptive_timing && !self.game_mode_active {
-                    self.update_tap_stats(keycode, duration_ms as f32);
-                }
+                self.record_tap(&mt_key, duration_ms);
 
                 let resolution = MtResolution {
                     keycode,
-                    action: MtAction::TapPressRelease(mt_key.tap_key),
+                    action: MtAction::TapPressRelease(mt_key.tap_action),
                 };
 
                 Some(resolution)
@@ -559,27 +779,17 @@ impl MtProcessor {
                 // Hold: emit modifier press and release
                 let resolution = MtResolution {
                     keycode,
-                    action: MtAction::HoldPressRelease(mt_key.hold_key),
+                    action: MtAction::HoldPressRelease(mt_key.hold_action),
                 };
 
                 Some(resolution)
             } else {
                 // Tap: emit tap key press and release
-                // Record tap time for double-tap detection
-                if self.config.double_tap_then_hold {
-                    self.last_tap_time.insert(keycode, Instant::now());
-                }
-
-                // Record ONLY taps (below threshold) for adaptive timing
-                // This prevents survivorship bias - only successful taps are tracked
-                // Skip recording when game mode is active
-                if self.config.adaptive_timing && !self.game_mode_active {
-                    self.update_tap_stats(keycode, duration_ms as f32);
-                }
+                self.record_tap(&mt_key, duration_ms);
 
                 let resolution = MtResolution {
                     keycode,
-                    action: MtAction::TapPressRelease(mt_key.tap_key),
+                    action: MtAction::TapPressRelease(mt_key.tap_action),
                 };
 
                 Some(resolution)
@@ -587,18 +797,26 @@ impl MtProcessor {
         }
         // Check if it's a held key
         else if let Some(mt_key) = self.held_keys.remove(&keycode) {
-            // Check if it was unwrapped
-            if mt_key.state == MtKeyState::Unwrapped {
-                // Release the unwrapped tap key
+            // Check if it was unwrapped (or tapped right away)
+            if matches!(mt_key.state, MtKeyState::Unwrapped | MtKeyState::Tap) {
+                // Release the unwrapped tap action
                 Some(MtResolution {
                     keycode,
-                    action: MtAction::ReleaseHold(mt_key.tap_key),
+                    action: MtAction::ReleaseHold(mt_key.tap_action),
+                })
+            } else if !mt_key.interrupted && self.config.flavor == MtFlavor::RetroTap {
+                // Retro tap: nothing was done with the hold pressed at the tapping term,
+                // so it releases itself first (neutralized by the keymap), then the tap
+                // is emitted. Not a tap for adaptive timing, it was held past the term
+                Some(MtResolution {
+                    keycode,
+                    action: MtAction::TapPressRelease(mt_key.tap_action),
                 })
             } else {
-                // Release the hold key
+                // Release the hold action
                 Some(MtResolution {
                     keycode,
-                    action: MtAction::ReleaseHold(mt_key.hold_key),
+                    action: MtAction::ReleaseHold(mt_key.hold_action),
                 })
             }
         } else {
@@ -606,6 +824,164 @@ impl MtProcessor {
         }
     }
 
+    /// Resolve an undecided key on release (precise flavors)
+    /// Past the term it's a hold, except for the flavors that never hold without
+    /// another key (normally resolved at the term already, unless the check was late)
+    fn resolve_released(&mut self, mt_key: MtKey, duration_ms: u32) -> MtResolution {
+        let past_term = duration_ms >= self.effective_threshold(&mt_key);
+        let hold = past_term
+            && matches!(
+                self.config.flavor,
+                MtFlavor::HoldPreferred | MtFlavor::Balanced | MtFlavor::TapPreferred
+            );
+
+        if hold {
+            MtResolution {
+                keycode: mt_key.keycode,
+                action: MtAction::HoldPressRelease(mt_key.hold_action),
+            }
+        } else {
+            self.record_tap(&mt_key, duration_ms);
+            MtResolution {
+                keycode: mt_key.keycode,
+                action: MtAction::TapPressRelease(mt_key.tap_action),
+            }
+        }
+    }
+
+    /// Whether releasing this key now releases the hold pressed at the tapping term
+    /// and sends the tap (retro tap, nothing was done with the hold)
+    pub fn releases_hold_for_tap(&self, keycode: KeyCode) -> bool {
+        self.config.flavor == MtFlavor::RetroTap
+            && self
+                .held_keys
+                .get(&keycode)
+                .is_some_and(|mt_key| mt_key.state == MtKeyState::Hold && !mt_key.interrupted)
+    }
+
+    /// Whether a key released past the term before its hold was pressed sends the tap
+    /// (heuristic flavor, no strong predicted intent for hold)
+    fn retro_taps(&self, mt_key: &MtKey) -> bool {
+        self.config.flavor == MtFlavor::Heuristic
+            && mt_key.hold_do_nothing_emits_tap
+            && mt_key.hold_intent_score <= 0.5
+    }
+
+    /// Whether a non-modifier key was pressed within `window_ms` before this MT key
+    /// The key's own press, recorded just before, doesn't count
+    fn typed_within(&self, keycode: KeyCode, window_ms: u32) -> bool {
+        self.prior_presses(keycode).any(|(k, pressed_at)| {
+            !k.is_modifier() && self.clock.elapsed(*pressed_at).as_millis() < u128::from(window_ms)
+        })
+    }
+
+    /// Presses before an MT key's own press, most recent first
+    /// MT keys still pending or held as a modifier aren't typing and are skipped
+    fn prior_presses(&self, keycode: KeyCode) -> impl Iterator<Item = &(KeyCode, Instant)> {
+        let mut prior = self.press_history.iter().rev().peekable();
+        if prior.peek().is_some_and(|(k, _)| *k == keycode) {
+            prior.next();
+        }
+        prior.filter(|(k, _)| !self.is_acting_as_modifier(*k))
+    }
+
+    /// Whether an MT key is pending or resolved to hold
+    fn is_acting_as_modifier(&self, keycode: KeyCode) -> bool {
+        self.undecided_keys.contains_key(&keycode)
+            || self
+                .held_keys
+                .get(&keycode)
+                .is_some_and(|mt_key| mt_key.state == MtKeyState::Hold)
+    }
+
+    /// Record a key release (for bigram overlap)
+    /// Must be called for every key release, MT keys included
+    pub fn record_release(&mut self, keycode: KeyCode) {
+        let now = self.clock.now();
+        self.last_release.insert(keycode, now);
+
+        for mt_key in self
+            .undecided_keys
+            .values_mut()
+            .chain(self.held_keys.values_mut())
+        {
+            if mt_key.prev_overlap_ms.is_none()
+                && mt_key.prev_key.is_some_and(|(prev, _)| prev == keycode)
+            {
+                let overlap = now.saturating_duration_since(mt_key.pressed_at);
+                mt_key.prev_overlap_ms = Some(overlap.as_millis() as u32);
+            }
+        }
+    }
+
+    /// Resolve all undecided keys to hold, in press order
+    fn resolve_all_to_hold(&mut self) -> Vec<MtResolution> {
+        let mut undecided: Vec<&MtKey> = self.undecided_keys.values().collect();
+        undecided.sort_by_key(|mt_key| mt_key.pressed_at);
+        let undecided: Vec<KeyCode> = undecided.iter().map(|mt_key| mt_key.keycode).collect();
+
+        undecided
+            .into_iter()
+            .filter_map(|keycode| self.resolve_to_hold(keycode))
+            .collect()
+    }
+
+    /// Resolve pending keys held past their tapping term
+    /// (to hold, or to tap for the tap-unless-interrupted flavor)
+    /// Should be called periodically (e.g., every 1ms in the idle loop)
+    /// so the hold is pressed without waiting for another key or the release
+    pub fn check_timeouts(&mut self) -> Vec<MtResolution> {
+        let now = self.clock.now();
+        let mut expired: Vec<&MtKey> = self
+            .undecided_keys
+            .values()
+            .filter(|mt_key| mt_key.duration_ms(now) >= u64::from(self.effective_threshold(mt_key)))
+            .collect();
+        // Resolve in press order so modifiers go down in the order they were pressed
+        expired.sort_by_key(|mt_key| mt_key.pressed_at);
+        let expired: Vec<KeyCode> = expired.iter().map(|mt_key| mt_key.keycode).collect();
+
+        expired
+            .into_iter()
+            .filter_map(|keycode| {
+                tracing::debug!("MT tapping term expired for {:?}", keycode);
+                if self.config.flavor == MtFlavor::TapUnlessInterrupted {
+                    self.resolve_to_tap(keycode)
+                } else {
+                    self.resolve_to_hold(keycode)
+                }
+            })
+            .collect()
+    }
+
+    /// Earliest tapping term expiry of the pending keys (None = none pending)
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.undecided_keys
+            .values()
+            .map(|mt_key| {
+                let threshold = u64::from(self.effective_threshold(mt_key));
+                mt_key.pressed_at + Duration::from_millis(threshold)
+            })
+            .min()
+    }
+
+    /// Record a resolved tap (double-tap detection and adaptive timing)
+    fn record_tap(&mut self, mt_key: &MtKey, duration_ms: u32) {
+        let keycode = mt_key.keycode;
+        // Record tap time for double-tap detection
+        if mt_key.double_tap_then_hold {
+            self.last_tap_time.insert(keycode, self.clock.now());
+        }
+
+        // Record ONLY taps (below threshold) for adaptive timing
+        // This prevents survivorship bias - only successful taps are tracked
+        // Skip recording when game mode is active
+        if self.config.adaptive_timing && !self.game_mode_active {
+            self.update_bigram_stats(mt_key, duration_ms);
+            self.update_tap_stats(keycode, duration_ms as f32, mt_key.tapping_term_ms);
+        }
+    }
+
     /// Resolve undecided key to tap
     fn resolve_to_tap(&mut self, keycode: KeyCode) -> Option<MtResolution> {
         if let Some(mut mt_key) = self.undecided_keys.remove(&keycode) {
@@ -614,7 +990,7 @@ impl MtProcessor {
             // Emit tap immediately
             Some(MtResolution {
                 keycode,
-                action: MtAction::TapPress(mt_key.tap_key),
+                action: MtAction::TapPress(mt_key.tap_action),
             })
         } else {
             None
@@ -625,12 +1001,13 @@ impl MtProcessor {
     fn resolve_to_hold(&mut self, keycode: KeyCode) -> Option<MtResolution> {
         if let Some(mut mt_key) = self.undecided_keys.remove(&keycode) {
             mt_key.state = MtKeyState::Hold;
-            self.held_keys.insert(keycode, mt_key.clone());
+            let hold_action = mt_key.hold_action.clone();
+            self.held_keys.insert(keycode, mt_key);
 
             // Emit hold key press
             Some(MtResolution {
                 keycode,
-                action: MtAction::HoldPress(mt_key.hold_key),
+                action: MtAction::HoldPress(hold_action),
             })
         } else {
             None
@@ -657,7 +1034,7 @@ impl MtProcessor {
                     right_count += 1;
                     right_keys.push(*keycode);
                 }
-                Hand::Unknown => {}
+                Hand::Thumb | Hand::Unknown => {}
             }
         }
 
@@ -692,13 +1069,13 @@ impl MtProcessor {
         }
 
         // Check recent key press patterns
-        let now = Instant::now();
+        let now = self.clock.now();
         let recent_same_hand = self
             .recent_presses
             .iter()
             .filter(|(keycode, timestamp)| {
                 let hand = self.get_hand(*keycode);
-                hand == mt_key.hand && (now - *timestamp).as_millis() < 200
+                hand == mt_key.hand && now.saturating_duration_since(*timestamp).as_millis() < 200
             })
             .count();
 
@@ -710,8 +1087,111 @@ impl MtProcessor {
         score.max(0.0).min(1.0)
     }
 
+    /// Tapping term in effect for a key (adaptive if enabled)
+    /// Uses the bigram's threshold when pressed right after a known previous key
+    fn effective_threshold(&self, mt_key: &MtKey) -> u32 {
+        if !self.config.adaptive_timing {
+            return mt_key.tapping_term_ms;
+        }
+
+        let threshold = match self.bigram_stats(mt_key) {
+            Some(stats) => stats.adaptive_threshold as u32,
+            None => self.get_adaptive_threshold(mt_key.keycode, mt_key.tapping_term_ms),
+        };
+
+        // Shifted by the key's misfire penalties
+        let shift = self
+            .rolling_stats
+            .get(&(mt_key.keycode, mt_key.keycode))
+            .map_or(0.0, RollingStats::misfire_shift_ms);
+        (threshold as f32 + shift).clamp(50.0, 500.0) as u32
+    }
+
+    /// Record a misfired MT decision (detected by the keymap via Backspace)
+    /// `was_hold`: the key resolved to hold but a tap was meant (or the reverse)
+    pub fn record_misfire(&mut self, keycode: KeyCode, was_hold: bool) {
+        if !self.config.adaptive_timing || self.game_mode_active {
+            return;
+        }
+
+        let base_threshold = self
+            .tapping_terms
+            .get(&keycode)
+            .copied()
+            .unwrap_or(self.config.tapping_term_ms) as f32;
+        let stats = self
+            .rolling_stats
+            .entry((keycode, keycode))
+            .or_insert_with(|| RollingStats::new(base_threshold));
+        if was_hold {
+            stats.hold_misfires += 1;
+        } else {
+            stats.tap_misfires += 1;
+        }
+
+        tracing::info!(
+            "ADAPTIVE: {} misfire on {:?} (threshold shift {:+.0}ms)",
+            if was_hold { "Hold" } else { "Tap" },
+            keycode,
+            stats.misfire_shift_ms()
+        );
+    }
+
+    /// Learned stats for the bigram an MT key was pressed in, if they apply
+    fn bigram_stats(&self, mt_key: &MtKey) -> Option<&RollingStats> {
+        let (prev, interval_ms) = mt_key.prev_key?;
+        if prev == mt_key.keycode {
+            return None;
+        }
+        let stats = self.rolling_stats.get(&(prev, mt_key.keycode))?;
+        if stats.tap_sample_count < MIN_BIGRAM_SAMPLES {
+            return None;
+        }
+
+        // Rolled into from the previous key (still held, or pressed about as quickly as
+        // usual) - a press well after it is an isolated press and uses the key's own stats
+        let overlapping = mt_key.prev_overlap_ms != Some(0);
+        let usual_interval = interval_ms as f32 <= 2.0 * stats.avg_interval_ms;
+        (interval_ms <= BIGRAM_WINDOW_MS && (overlapping || usual_interval)).then_some(stats)
+    }
+
+    /// Update bigram statistics (previous key → MT key) with a tap
+    /// New stats are seeded with the key's current threshold
+    fn update_bigram_stats(&mut self, mt_key: &MtKey, duration_ms: u32) {
+        let Some((prev, interval_ms)) = mt_key.prev_key else {
+            return;
+        };
+        // Same-key bigrams are double taps, the key's own stats cover them
+        if prev == mt_key.keycode || interval_ms > BIGRAM_WINDOW_MS {
+            return;
+        }
+
+        // Still held at the tap's end - overlapped the whole tap
+        let overlap_ms = mt_key.prev_overlap_ms.unwrap_or(duration_ms);
+        let base_threshold =
+            self.get_adaptive_threshold(mt_key.keycode, mt_key.tapping_term_ms) as f32;
+        let target_margin = self.config.adaptive_target_margin_ms as f32;
+
+        let stats = self
+            .rolling_stats
+            .entry((prev, mt_key.keycode))
+            .or_insert_with(|| RollingStats::new(base_threshold));
+        stats.update_bigram(interval_ms as f32, overlap_ms as f32);
+        stats.update_tap(duration_ms as f32, target_margin);
+
+        tracing::debug!(
+            "ADAPTIVE: Bigram {:?} → {:?}: interval={:.1}ms, overlap={:.1}ms, threshold={:.1}ms",
+            prev,
+            mt_key.keycode,
+            stats.avg_interval_ms,
+            stats.avg_overlap_ms,
+            stats.adaptive_threshold
+        );
+    }
+
     /// Get adaptive threshold for a key based on tap statistics
-    fn get_adaptive_threshold(&self, keycode: KeyCode) -> u32 {
+    /// Falls back to the key's own tapping term
+    fn get_adaptive_threshold(&self, keycode: KeyCode, tapping_term_ms: u32) -> u32 {
         // Look up stats for this key
         if let Some(stats) = self.rolling_stats.get(&(keycode, keycode)) {
             if stats.tap_sample_count >= 1 {
@@ -721,13 +1201,14 @@ impl MtProcessor {
         }
 
         // Fall back to default only if no samples at all
-        self.config.tapping_term_ms
+        tapping_term_ms
     }
 
     /// Update tap statistics - records actual tap durations and adjusts threshold
-    fn update_tap_stats(&mut self, keycode: KeyCode, duration_ms: f32) {
+    /// New stats are seeded with the key's tapping term
+    fn update_tap_stats(&mut self, keycode: KeyCode, duration_ms: f32, tapping_term_ms: u32) {
         let key = (keycode, keycode);
-        let base_threshold = self.config.tapping_term_ms as f32;
+        let base_threshold = tapping_term_ms as f32;
         let target_margin = self.config.adaptive_target_margin_ms as f32;
 
         tracing::info!(
@@ -767,7 +1248,8 @@ impl MtProcessor {
         self.rolling_stats
             .iter()
             .filter_map(|((k1, k2), stats)| {
-                if k1 == k2 && stats.tap_sample_count > 0 {
+                let misfires = stats.hold_misfires + stats.tap_misfires;
+                if k1 == k2 && (stats.tap_sample_count > 0 || misfires > 0) {
                     Some((*k1, stats))
                 } else {
                     None
@@ -887,16 +1369,480 @@ pub struct MtResolution {
 }
 
 /// MT action to emit
+/// Carries the resolved side of the MT key, which can be any (nested) action
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MtAction {
-    /// Emit tap key press (key is still held)
-    TapPress(KeyCode),
-    /// Emit tap key press and release immediately
-    TapPressRelease(KeyCode),
-    /// Emit hold key press
-    HoldPress(KeyCode),
-    /// Emit hold key press and release immediately
-    HoldPressRelease(KeyCode),
-    /// Release hold key
-    ReleaseHold(KeyCode),
+    /// Press tap action (key is still held)
+    TapPress(Action),
+    /// Press and release tap action immediately
+    TapPressRelease(Action),
+    /// Press hold action
+    HoldPress(Action),
+    /// Press and release hold action immediately
+    HoldPressRelease(Action),
+    /// Release the side that was pressed (tap or hold)
+    ReleaseHold(Action),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERM_MS: u64 = 200;
+
+    /// Processor on a manual clock (moved by `wait`)
+    fn processor(flavor: MtFlavor) -> MtProcessor {
+        with_config(MtConfig {
+            tapping_term_ms: TERM_MS as u32,
+            flavor,
+            ..MtConfig::default()
+        })
+    }
+
+    /// Processor with `config` on a manual clock
+    fn with_config(config: MtConfig) -> MtProcessor {
+        MtProcessor::new(config).with_clock(Clock::manual(Instant::now()))
+    }
+
+    fn key(keycode: KeyCode) -> Action {
+        Action::Key(keycode)
+    }
+
+    /// Press KC_A as MT(KC_A, KC_LSFT)
+    fn press_mt(mt: &mut MtProcessor) -> Option<MtResolution> {
+        mt.on_press(
+            KeyCode::KC_A,
+            key(KeyCode::KC_A),
+            key(KeyCode::KC_LSFT),
+            &KeyOptions::default(),
+        )
+    }
+
+    /// Let `ms` pass
+    fn wait(mt: &MtProcessor, ms: u64) {
+        mt.clock.advance(Duration::from_millis(ms));
+    }
+
+    fn actions(resolutions: Vec<MtResolution>) -> Vec<MtAction> {
+        resolutions.into_iter().map(|r| r.action).collect()
+    }
+
+    fn release(mt: &mut MtProcessor) -> Option<MtAction> {
+        mt.on_release(KeyCode::KC_A).map(|r| r.action)
+    }
+
+    fn tap() -> MtAction {
+        MtAction::TapPressRelease(key(KeyCode::KC_A))
+    }
+
+    fn hold() -> MtAction {
+        MtAction::HoldPress(key(KeyCode::KC_LSFT))
+    }
+
+    fn release_hold() -> MtAction {
+        MtAction::ReleaseHold(key(KeyCode::KC_LSFT))
+    }
+
+    #[test]
+    fn test_quick_tap_is_tap_in_every_flavor() {
+        for flavor in [
+            MtFlavor::Heuristic,
+            MtFlavor::HoldPreferred,
+            MtFlavor::Balanced,
+            MtFlavor::TapPreferred,
+            MtFlavor::TapUnlessInterrupted,
+            MtFlavor::RetroTap,
+        ] {
+            let mut mt = processor(flavor);
+            assert!(press_mt(&mut mt).is_none());
+            assert!(mt.check_timeouts().is_empty(), "{:?}", flavor);
+            assert_eq!(release(&mut mt), Some(tap()), "{:?}", flavor);
+        }
+    }
+
+    #[test]
+    fn test_hold_preferred() {
+        // Another key pressed → hold right away
+        let mut mt = processor(MtFlavor::HoldPreferred);
+        press_mt(&mut mt);
+        assert!(!mt.holds_back_other_keys());
+        assert_eq!(actions(mt.on_other_key_press(KeyCode::KC_J)), vec![hold()]);
+        assert_eq!(release(&mut mt), Some(release_hold()));
+
+        // Term reached → hold, no tap on release
+        let mut mt = processor(MtFlavor::HoldPreferred);
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+        assert_eq!(release(&mut mt), Some(release_hold()));
+    }
+
+    #[test]
+    fn test_balanced() {
+        // Other key pressed and released while held → hold
+        let mut mt = processor(MtFlavor::Balanced);
+        press_mt(&mut mt);
+        assert!(mt.on_other_key_press(KeyCode::KC_J).is_empty());
+        assert!(mt.holds_back_other_keys());
+        assert!(mt.on_other_key_release(KeyCode::KC_K).is_empty());
+        assert_eq!(
+            actions(mt.on_other_key_release(KeyCode::KC_J)),
+            vec![hold()]
+        );
+        assert!(!mt.holds_back_other_keys());
+        assert_eq!(release(&mut mt), Some(release_hold()));
+
+        // MT key released before the other key (roll) → tap
+        let mut mt = processor(MtFlavor::Balanced);
+        press_mt(&mut mt);
+        assert!(mt.on_other_key_press(KeyCode::KC_J).is_empty());
+        assert_eq!(release(&mut mt), Some(tap()));
+        assert!(mt.on_other_key_release(KeyCode::KC_J).is_empty());
+
+        // Term reached → hold
+        let mut mt = processor(MtFlavor::Balanced);
+        press_mt(&mut mt);
+        assert!(mt.on_other_key_press(KeyCode::KC_J).is_empty());
+        wait(&mt, TERM_MS);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+    }
+
+    #[test]
+    fn test_tap_preferred() {
+        // Other keys don't decide anything
+        let mut mt = processor(MtFlavor::TapPreferred);
+        press_mt(&mut mt);
+        assert!(mt.on_other_key_press(KeyCode::KC_J).is_empty());
+        assert!(mt.on_other_key_release(KeyCode::KC_J).is_empty());
+        assert!(mt.holds_back_other_keys());
+        assert_eq!(release(&mut mt), Some(tap()));
+
+        // Only the term makes a hold
+        let mut mt = processor(MtFlavor::TapPreferred);
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS - 1);
+        assert!(mt.check_timeouts().is_empty());
+        wait(&mt, 1);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+        assert_eq!(release(&mut mt), Some(release_hold()));
+    }
+
+    #[test]
+    fn test_tap_unless_interrupted() {
+        // Other key pressed before the term → hold
+        let mut mt = processor(MtFlavor::TapUnlessInterrupted);
+        press_mt(&mut mt);
+        assert_eq!(actions(mt.on_other_key_press(KeyCode::KC_J)), vec![hold()]);
+
+        // Term reached without another key → tap, held until release
+        let mut mt = processor(MtFlavor::TapUnlessInterrupted);
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS);
+        assert_eq!(
+            actions(mt.check_timeouts()),
+            vec![MtAction::TapPress(key(KeyCode::KC_A))]
+        );
+        assert!(mt.on_other_key_press(KeyCode::KC_J).is_empty());
+        assert_eq!(release(&mut mt), None);
+    }
+
+    #[test]
+    fn test_retro_tap() {
+        // Hold with nothing pressed → tap on release, even past the term
+        let mut mt = processor(MtFlavor::RetroTap);
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS * 5);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+        assert_eq!(release(&mut mt), Some(tap()));
+
+        // Hold used for another key → plain hold release
+        let mut mt = processor(MtFlavor::RetroTap);
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+        assert!(mt.on_other_key_press(KeyCode::KC_J).is_empty());
+        assert_eq!(release(&mut mt), Some(release_hold()));
+    }
+
+    #[test]
+    fn test_precise_flavors_ignore_hold_do_nothing_emits_tap() {
+        let mut mt = processor(MtFlavor::TapPreferred);
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+        assert_eq!(release(&mut mt), Some(release_hold()));
+
+        // Heuristic flavor: once the hold was pressed at the term, release only releases it
+        let mut mt = processor(MtFlavor::Heuristic);
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+        assert_eq!(release(&mut mt), Some(release_hold()));
+
+        // ...but taps when released past the term before the hold was pressed
+        let mut mt = processor(MtFlavor::Heuristic);
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS);
+        assert_eq!(release(&mut mt), Some(tap()));
+    }
+
+    #[test]
+    fn test_per_key_tapping_term() {
+        let mut mt = processor(MtFlavor::HoldPreferred);
+        let options = KeyOptions {
+            tapping_term_ms: Some(300),
+            ..KeyOptions::default()
+        };
+        mt.on_press(
+            KeyCode::KC_A,
+            key(KeyCode::KC_A),
+            key(KeyCode::KC_LSFT),
+            &options,
+        );
+        wait(&mt, TERM_MS);
+        assert!(mt.check_timeouts().is_empty());
+        wait(&mt, 100);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+    }
+
+    #[test]
+    fn test_thumb_unwraps_either_hand() {
+        let mut mt = processor(MtFlavor::HoldPreferred);
+        mt.set_hand(KeyCode::KC_SPC, Hand::Thumb);
+
+        // Hold a thumb MT key
+        mt.on_press(
+            KeyCode::KC_SPC,
+            key(KeyCode::KC_SPC),
+            key(KeyCode::KC_LCTL),
+            &KeyOptions::default(),
+        );
+        wait(&mt, TERM_MS);
+        assert_eq!(mt.check_timeouts().len(), 1);
+
+        // A left-hand MT key is unwrapped to its tap
+        let resolution = press_mt(&mut mt).map(|r| r.action);
+        assert_eq!(resolution, Some(MtAction::TapPress(key(KeyCode::KC_A))));
+    }
+
+    #[test]
+    fn test_bigram_adaptive_timing() {
+        let adaptive_processor = || {
+            with_config(MtConfig {
+                tapping_term_ms: TERM_MS as u32,
+                adaptive_timing: true,
+                flavor: MtFlavor::HoldPreferred,
+                ..MtConfig::default()
+            })
+        };
+        // Press KC_S, then KC_A (MT) `interval_ms` later
+        let press_after_s = |mt: &mut MtProcessor, interval_ms: u64| {
+            mt.on_other_key_press(KeyCode::KC_S);
+            wait(mt, interval_ms);
+            mt.on_other_key_press(KeyCode::KC_A);
+            press_mt(mt);
+        };
+
+        // Taps rolled from S record the S → A bigram, with S held over the tap
+        let mut mt = adaptive_processor();
+        press_after_s(&mut mt, 40);
+        wait(&mt, 60);
+        assert_eq!(release(&mut mt), Some(tap()));
+        let stats = &mt.rolling_stats[&(KeyCode::KC_S, KeyCode::KC_A)];
+        assert_eq!(stats.tap_sample_count, 1);
+        assert!((40.0..45.0).contains(&stats.avg_interval_ms));
+        assert!(stats.avg_overlap_ms >= 60.0);
+
+        // The bigram's threshold applies to rolls, not to isolated presses
+        let mut mt = adaptive_processor();
+        let mut stats = RollingStats::new(300.0);
+        stats.tap_sample_count = MIN_BIGRAM_SAMPLES;
+        stats.avg_interval_ms = 50.0;
+        mt.rolling_stats
+            .insert((KeyCode::KC_S, KeyCode::KC_A), stats);
+
+        press_after_s(&mut mt, 40);
+        wait(&mt, TERM_MS);
+        assert!(mt.check_timeouts().is_empty());
+        wait(&mt, 100);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+        release(&mut mt);
+
+        mt.record_release(KeyCode::KC_S);
+        press_after_s(&mut mt, 400);
+        wait(&mt, TERM_MS);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+
+        // Bigram stats survive a save and load
+        let file_name = format!("keymux-bigram-{}.json", std::process::id());
+        let path = std::env::temp_dir().join(file_name);
+        mt.save_stats(&path).unwrap();
+        let mut loaded = adaptive_processor();
+        loaded.load_stats(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let stats = &loaded.rolling_stats[&(KeyCode::KC_S, KeyCode::KC_A)];
+        assert_eq!(stats.avg_interval_ms, 50.0);
+    }
+
+    #[test]
+    fn test_misfires_shift_threshold() {
+        let mut mt = with_config(MtConfig {
+            tapping_term_ms: TERM_MS as u32,
+            adaptive_timing: true,
+            flavor: MtFlavor::HoldPreferred,
+            ..MtConfig::default()
+        });
+        for _ in 0..3 {
+            mt.record_misfire(KeyCode::KC_A, true);
+        }
+        mt.record_misfire(KeyCode::KC_A, false);
+        let stats = &mt.rolling_stats[&(KeyCode::KC_A, KeyCode::KC_A)];
+        assert_eq!((stats.hold_misfires, stats.tap_misfires), (3, 1));
+        assert_eq!(stats.misfire_shift_ms(), 2.0 * MISFIRE_SHIFT_MS);
+
+        // Two net hold misfires → held 20ms later than the tapping term
+        press_mt(&mut mt);
+        wait(&mt, TERM_MS + 10);
+        assert!(mt.check_timeouts().is_empty());
+        wait(&mt, 10);
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+
+        // The stats of a key with its own tapping term start from that term
+        let options = KeyOptions {
+            tapping_term_ms: Some(300),
+            ..KeyOptions::default()
+        };
+        mt.on_press(
+            KeyCode::KC_S,
+            key(KeyCode::KC_S),
+            key(KeyCode::KC_LALT),
+            &options,
+        );
+        mt.record_misfire(KeyCode::KC_S, true);
+        let stats = &mt.rolling_stats[&(KeyCode::KC_S, KeyCode::KC_S)];
+        assert_eq!(stats.adaptive_threshold, 300.0);
+    }
+
+    #[test]
+    fn test_simulated_time() {
+        let clock = Clock::manual(Instant::now());
+        let mut mt = processor(MtFlavor::HoldPreferred).with_clock(clock.clone());
+
+        // Decided exactly at the tapping term, however long the test takes
+        press_mt(&mut mt);
+        clock.advance(Duration::from_millis(TERM_MS - 1));
+        assert!(mt.check_timeouts().is_empty());
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(actions(mt.check_timeouts()), vec![hold()]);
+        assert_eq!(release(&mut mt), Some(release_hold()));
+
+        // Time never goes backwards
+        let before = clock.now();
+        clock.set(before - Duration::from_millis(50));
+        assert_eq!(clock.now(), before);
+        press_mt(&mut mt);
+        clock.advance(Duration::from_millis(TERM_MS - 1));
+        assert_eq!(release(&mut mt), Some(tap()));
+    }
+
+    #[test]
+    fn test_require_prior_idle() {
+        let idle_processor = || {
+            with_config(MtConfig {
+                require_prior_idle_ms: 150,
+                ..MtConfig::default()
+            })
+        };
+        let typed_tap = Some(MtAction::TapPress(key(KeyCode::KC_A)));
+
+        // Typing just before → tap right away, released with the key
+        let mut mt = idle_processor();
+        mt.on_other_key_press(KeyCode::KC_J);
+        mt.on_other_key_press(KeyCode::KC_A);
+        assert_eq!(press_mt(&mut mt).map(|r| r.action), typed_tap);
+        assert_eq!(
+            release(&mut mt),
+            Some(MtAction::ReleaseHold(key(KeyCode::KC_A)))
+        );
+
+        // Only a modifier before, or typing outside the window → decided as usual
+        let mut mt = idle_processor();
+        mt.on_other_key_press(KeyCode::KC_LCTL);
+        assert!(press_mt(&mut mt).is_none());
+
+        let mut mt = idle_processor();
+        mt.on_other_key_press(KeyCode::KC_J);
+        wait(&mt, 150);
+        assert!(press_mt(&mut mt).is_none());
+
+        // Another MT key held as a modifier isn't typing
+        let mut mt = idle_processor();
+        mt.on_other_key_press(KeyCode::KC_SPC);
+        mt.on_press(
+            KeyCode::KC_SPC,
+            key(KeyCode::KC_SPC),
+            key(KeyCode::KC_LCTL),
+            &KeyOptions::default(),
+        );
+        assert_eq!(
+            actions(mt.on_other_key_press(KeyCode::KC_A)),
+            vec![MtAction::HoldPress(key(KeyCode::KC_LCTL))]
+        );
+        assert!(press_mt(&mut mt).is_none());
+
+        // Per-key override turns it off
+        let mut mt = idle_processor();
+        mt.on_other_key_press(KeyCode::KC_J);
+        let options = KeyOptions {
+            require_prior_idle_ms: Some(0),
+            ..KeyOptions::default()
+        };
+        let resolution = mt.on_press(
+            KeyCode::KC_A,
+            key(KeyCode::KC_A),
+            key(KeyCode::KC_LSFT),
+            &options,
+        );
+        assert!(resolution.is_none());
+    }
+
+    #[test]
+    fn test_per_key_double_tap_then_hold() {
+        let mut mt = processor(MtFlavor::Heuristic);
+        let options = KeyOptions {
+            double_tap_then_hold: Some(true),
+            double_tap_window_ms: Some(100),
+            ..KeyOptions::default()
+        };
+        let press = |mt: &mut MtProcessor| {
+            mt.on_press(
+                KeyCode::KC_A,
+                key(KeyCode::KC_A),
+                key(KeyCode::KC_LSFT),
+                &options,
+            )
+            .map(|r| r.action)
+        };
+
+        // Globally off, on for this key → the second tap within its window holds the tap key
+        assert!(press(&mut mt).is_none());
+        wait(&mt, 30);
+        assert_eq!(release(&mut mt), Some(tap()));
+        wait(&mt, 30);
+        assert_eq!(
+            press(&mut mt),
+            Some(MtAction::HoldPress(key(KeyCode::KC_A)))
+        );
+        wait(&mt, 30);
+        assert_eq!(
+            release(&mut mt),
+            Some(MtAction::ReleaseHold(key(KeyCode::KC_A)))
+        );
+
+        // Outside the key's window → decided as usual
+        wait(&mt, 100);
+        assert!(press(&mut mt).is_none());
+        wait(&mt, 30);
+        assert_eq!(release(&mut mt), Some(tap()));
+    }
 }
@@ -0,0 +1,228 @@
+/// Mouse keys processor - QMK-inspired mouse emulation
+///
+/// Motion Model:
+/// - Pressing a movement or wheel key emits one step right away
+/// - While held, further steps are emitted from `tick` every interval
+/// - Cursor speed ramps up linearly from move_delta to max_move_delta over time_to_max_ms
+/// - Opposite directions held together cancel out
+///
+/// Buttons are pressed and released with their keys.
+use crate::config::{MouseAction, MouseKeysConfig};
+use crate::event_processor::clock::Clock;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Mouse button (evdev BTN_LEFT..BTN_EXTRA)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Side,
+    Extra,
+}
+
+/// Mouse event to emit on the virtual device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseEvent {
+    /// Press (true) or release (false) a button
+    Button(MouseButton, bool),
+    /// Move the cursor by (x, y) - positive is right/down
+    Move(i32, i32),
+    /// Scroll by (vertical, horizontal) wheel clicks - positive is up/right
+    Wheel(i32, i32),
+}
+
+/// Mouse keys processor - turns held mouse keys into REL/BTN events
+pub struct MouseKeysProcessor {
+    /// Config
+    config: MouseKeysConfig,
+
+    /// Movement and wheel keys currently held
+    held: HashSet<MouseAction>,
+
+    /// When the cursor started moving (for acceleration)
+    move_started_at: Option<Instant>,
+    /// When the last movement step was emitted
+    last_move_at: Instant,
+    /// When the last wheel step was emitted
+    last_wheel_at: Instant,
+
+    /// Time source
+    clock: Clock,
+}
+
+impl MouseKeysProcessor {
+    /// Create new mouse keys processor
+    pub fn new(config: MouseKeysConfig) -> Self {
+        Self {
+            config,
+            held: HashSet::new(),
+            move_started_at: None,
+            last_move_at: Instant::now(),
+            last_wheel_at: Instant::now(),
+            clock: Clock::system(),
+        }
+    }
+
+    /// Use a shared clock (event timestamps, simulated time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Handle mouse key press
+    pub fn on_press(&mut self, action: MouseAction) -> Vec<MouseEvent> {
+        if let Some(button) = Self::button(action) {
+            return vec![MouseEvent::Button(button, true)];
+        }
+
+        self.held.insert(action);
+        if Self::is_movement(action) {
+            if self.move_started_at.is_none() {
+                self.move_started_at = Some(self.clock.now());
+            }
+            self.last_move_at = self.clock.now();
+            self.move_step().into_iter().collect()
+        } else {
+            self.last_wheel_at = self.clock.now();
+            self.wheel_step().into_iter().collect()
+        }
+    }
+
+    /// Handle mouse key release
+    pub fn on_release(&mut self, action: MouseAction) -> Vec<MouseEvent> {
+        if let Some(button) = Self::button(action) {
+            return vec![MouseEvent::Button(button, false)];
+        }
+
+        self.held.remove(&action);
+        if !self.held.iter().any(|&held| Self::is_movement(held)) {
+            self.move_started_at = None;
+        }
+        Vec::new()
+    }
+
+    /// Emit the movement and wheel steps that are due
+    /// Should be called periodically (e.g., every 1ms in the idle loop)
+    pub fn tick(&mut self) -> Vec<MouseEvent> {
+        let mut events = Vec::new();
+        if self.held.is_empty() {
+            return events;
+        }
+
+        if self.move_started_at.is_some()
+            && self.clock.elapsed(self.last_move_at).as_millis()
+                >= u128::from(self.config.interval_ms)
+        {
+            self.last_move_at = self.clock.now();
+            events.extend(self.move_step());
+        }
+
+        let since_wheel = self.clock.elapsed(self.last_wheel_at);
+        if since_wheel.as_millis() >= u128::from(self.config.wheel_interval_ms) {
+            self.last_wheel_at = self.clock.now();
+            events.extend(self.wheel_step());
+        }
+
+        events
+    }
+
+    /// When the next movement or wheel step is due (None = no keys held)
+    pub fn next_deadline(&self) -> Option<Instant> {
+        if self.held.is_empty() {
+            return None;
+        }
+        let wheel_at = self.last_wheel_at + Duration::from_millis(self.config.wheel_interval_ms);
+        let move_at = self
+            .move_started_at
+            .map(|_| self.last_move_at + Duration::from_millis(self.config.interval_ms));
+        Some(move_at.map_or(wheel_at, |move_at| move_at.min(wheel_at)))
+    }
+
+    /// One movement step at the current speed
+    fn move_step(&self) -> Option<MouseEvent> {
+        let (x, y) = self.direction(
+            MouseAction::MS_LEFT,
+            MouseAction::MS_RGHT,
+            MouseAction::MS_UP,
+            MouseAction::MS_DOWN,
+        );
+        if x == 0 && y == 0 {
+            return None;
+        }
+
+        let speed = self.current_move_delta() as i32;
+        Some(MouseEvent::Move(x * speed, y * speed))
+    }
+
+    /// One wheel step
+    fn wheel_step(&self) -> Option<MouseEvent> {
+        let (horizontal, vertical) = self.direction(
+            MouseAction::MS_WHLL,
+            MouseAction::MS_WHLR,
+            MouseAction::MS_WHLD,
+            MouseAction::MS_WHLU,
+        );
+        if horizontal == 0 && vertical == 0 {
+            return None;
+        }
+
+        let delta = self.config.wheel_delta as i32;
+        Some(MouseEvent::Wheel(vertical * delta, horizontal * delta))
+    }
+
+    /// Net (x, y) direction of the held keys, each -1, 0 or 1
+    fn direction(
+        &self,
+        negative_x: MouseAction,
+        positive_x: MouseAction,
+        negative_y: MouseAction,
+        positive_y: MouseAction,
+    ) -> (i32, i32) {
+        let axis = |negative, positive| {
+            i32::from(self.held.contains(&positive)) - i32::from(self.held.contains(&negative))
+        };
+        (axis(negative_x, positive_x), axis(negative_y, positive_y))
+    }
+
+    /// Cursor movement per step, accelerating while movement keys are held
+    fn current_move_delta(&self) -> u32 {
+        let Some(started_at) = self.move_started_at else {
+            return self.config.move_delta;
+        };
+        if self.config.time_to_max_ms == 0 {
+            return self.config.max_move_delta;
+        }
+
+        let progress = (self.clock.elapsed(started_at).as_millis() as f64
+            / self.config.time_to_max_ms as f64)
+            .min(1.0);
+        let range = f64::from(
+            self.config
+                .max_move_delta
+                .saturating_sub(self.config.move_delta),
+        );
+        self.config.move_delta + (range * progress) as u32
+    }
+
+    /// Whether an action moves the cursor
+    fn is_movement(action: MouseAction) -> bool {
+        matches!(
+            action,
+            MouseAction::MS_UP | MouseAction::MS_DOWN | MouseAction::MS_LEFT | MouseAction::MS_RGHT
+        )
+    }
+
+    /// Button of a button action
+    fn button(action: MouseAction) -> Option<MouseButton> {
+        match action {
+            MouseAction::MS_BTN1 => Some(MouseButton::Left),
+            MouseAction::MS_BTN2 => Some(MouseButton::Right),
+            MouseAction::MS_BTN3 => Some(MouseButton::Middle),
+            MouseAction::MS_BTN4 => Some(MouseButton::Side),
+            MouseAction::MS_BTN5 => Some(MouseButton::Extra),
+            _ => None,
+        }
+    }
+}
@@ -10,9 +10,12 @@
 /// - Auto-releases after next non-modifier keypress
 /// - Can stack multiple one-shots
 /// - Timeout prevents accidental stuck modifiers
-use crate::config::KeyCode;
+///
+/// OSM keys can override tapping_term_ms and oneshot_timeout_ms per key.
+use crate::config::{KeyCode, KeyOptions};
+use crate::event_processor::clock::Clock;
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// State of a one-shot modifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,22 +43,36 @@ pub struct OsmKey {
     pub state: OsmState,
     /// Has the modifier been emitted yet?
     pub modifier_emitted: bool,
+    /// Tapping term for this key (per-key option or global)
+    pub tapping_term_ms: u32,
+    /// One-shot timeout for this key (per-key option or global)
+    pub oneshot_timeout_ms: u64,
 }
 
 impl OsmKey {
-    pub fn new(keycode: KeyCode, modifier_key: KeyCode) -> Self {
+    pub fn new(
+        keycode: KeyCode,
+        modifier_key: KeyCode,
+        activated_at: Instant,
+        config: &OsmConfig,
+        options: &KeyOptions,
+    ) -> Self {
         Self {
             keycode,
             modifier_key,
-            activated_at: Instant::now(),
+            activated_at,
             state: OsmState::Pressed,
             modifier_emitted: false,
+            tapping_term_ms: options.tapping_term_ms.unwrap_or(config.tapping_term_ms),
+            oneshot_timeout_ms: options
+                .oneshot_timeout_ms
+                .unwrap_or(config.oneshot_timeout_ms),
         }
     }
 
     /// Time since activation
-    pub fn elapsed(&self) -> u128 {
-        self.activated_at.elapsed().as_millis()
+    pub fn elapsed(&self, now: Instant) -> u128 {
+        now.saturating_duration_since(self.activated_at).as_millis()
     }
 }
 
@@ -98,6 +115,9 @@ pub struct OsmProcessor {
 
     /// Active one-shots waiting for next keypress (modifier_key -> OsmKey)
     active_oneshots: HashMap<KeyCode, OsmKey>,
+
+    /// Time source
+    clock: Clock,
 }
 
 impl OsmProcessor {
@@ -107,12 +127,31 @@ impl OsmProcessor {
             config,
             tracked_keys: HashMap::new(),
             active_oneshots: HashMap::new(),
+            clock: Clock::system(),
         }
     }
 
+    /// Use a shared clock (event timestamps, simulated time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Handle OSM key press
-    pub fn on_press(&mut self, keycode: KeyCode, modifier_key: KeyCode) -> OsmResolution {
-        let osm_key = OsmKey::new(keycode, modifier_key);
+    /// `options` override the config for this key
+    pub fn on_press(
+        &mut self,
+        keycode: KeyCode,
+        modifier_key: KeyCode,
+        options: &KeyOptions,
+    ) -> OsmResolution {
+        let osm_key = OsmKey::new(
+            keycode,
+            modifier_key,
+            self.clock.now(),
+            &self.config,
+            options,
+        );
         self.tracked_keys.insert(keycode, osm_key);
 
         // Don't emit yet - wait to see if it's a tap or hold
@@ -122,42 +161,58 @@ impl OsmProcessor {
     /// Handle OSM key release
     pub fn on_release(&mut self, keycode: KeyCode) -> OsmResolution {
         if let Some(mut osm_key) = self.tracked_keys.remove(&keycode) {
-            let duration_ms = osm_key.elapsed();
+            let now = self.clock.now();
+            let duration_ms = osm_key.elapsed(now);
+
+            // Held while another key was pressed - release the normal modifier
+            if osm_key.state == OsmState::Held {
+                return OsmResolution::ReleaseModifier(osm_key.modifier_key);
+            }
 
             // Tapped (released quickly) - activate one-shot
-            if duration_ms < self.config.tapping_term_ms as u128 {
+            if duration_ms < osm_key.tapping_term_ms as u128 {
                 osm_key.state = OsmState::Active;
-                osm_key.activated_at = Instant::now(); // Reset timer for timeout
+                osm_key.activated_at = now; // Reset timer for timeout
                 let modifier_key = osm_key.modifier_key;
                 self.active_oneshots.insert(modifier_key, osm_key);
 
                 // Emit modifier press
                 return OsmResolution::ActivateModifier(modifier_key);
-            } else {
-                // Held (released after long press) - was acting as normal modifier
-                // Release it now
-                return OsmResolution::ReleaseModifier(osm_key.modifier_key);
             }
+
+            // Held past tapping term on its own - nothing was emitted, nothing to release
         }
 
         OsmResolution::None
     }
 
     /// Called when ANY other key is pressed
-    /// Returns modifiers that should be released after this key
+    /// Returns modifiers that must be pressed before this key (OSM keys still held
+    /// act as normal modifiers). Active one-shots are queued for release after this key.
     pub fn on_other_key_press(&mut self, keycode: KeyCode) -> Vec<(KeyCode, OsmResolution)> {
         let mut resolutions = Vec::new();
 
+        // OSM keys still physically held become normal modifiers
+        for osm_key in self.tracked_keys.values_mut() {
+            if osm_key.state == OsmState::Pressed {
+                osm_key.state = OsmState::Held;
+                osm_key.modifier_emitted = true;
+                resolutions.push((
+                    osm_key.modifier_key,
+                    OsmResolution::ActivateModifier(osm_key.modifier_key),
+                ));
+            }
+        }
+
         // Don't consume one-shot on modifier keys
         if self.is_modifier(keycode) {
             return resolutions;
         }
 
         // Mark all active one-shots for release after this key
-        for (modifier_key, osm_key) in &mut self.active_oneshots {
+        for osm_key in self.active_oneshots.values_mut() {
             if osm_key.state == OsmState::Active {
                 osm_key.state = OsmState::QueuedRelease;
-                resolutions.push((*modifier_key, OsmResolution::None));
             }
         }
 
@@ -193,14 +248,14 @@ impl OsmProcessor {
     /// Check for timeouts and deactivate expired one-shots
     pub fn check_timeouts(&mut self) -> Vec<(KeyCode, OsmResolution)> {
         let mut resolutions = Vec::new();
-        let timeout_ms = self.config.oneshot_timeout_ms;
 
         // Find expired one-shots
+        let now = self.clock.now();
         let expired: Vec<KeyCode> = self
             .active_oneshots
             .iter()
             .filter_map(|(modifier_key, osm_key)| {
-                if osm_key.elapsed() > timeout_ms as u128 {
+                if osm_key.elapsed(now) > osm_key.oneshot_timeout_ms as u128 {
                     Some(*modifier_key)
                 } else {
                     None
@@ -217,6 +272,14 @@ impl OsmProcessor {
         resolutions
     }
 
+    /// Earliest one-shot timeout (None = no one-shots active)
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.active_oneshots
+            .values()
+            .map(|osm_key| osm_key.activated_at + Duration::from_millis(osm_key.oneshot_timeout_ms))
+            .min()
+    }
+
     /// Check if a keycode is a modifier key
     fn is_modifier(&self, keycode: KeyCode) -> bool {
         matches!(
@@ -0,0 +1,93 @@
+/// Time source for the keymap processors
+///
+/// Clock Model:
+/// - `Clock::system()`: real time. While an input event is processed, time is pinned
+///   to the event's timestamp, so decisions use when the key was actually pressed
+///   rather than when the event was read
+/// - `Clock::manual(start)`: simulated time that only moves when set (replay, tests)
+///
+/// Time never goes backwards: pinning to a time before one already seen keeps the
+/// later one. Clones share the same time, so one clock drives all processors of a keymap.
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Shared time source (see module docs)
+#[derive(Debug, Clone)]
+pub struct Clock {
+    /// Simulated time - never falls back to real time
+    manual: bool,
+    /// Time pinned by the event being processed (always set for manual clocks)
+    pinned: Rc<Cell<Option<Instant>>>,
+    /// Latest time handed out (keeps time monotonic)
+    latest: Rc<Cell<Instant>>,
+}
+
+impl Clock {
+    /// Real time, pinned to event timestamps while events are processed
+    pub fn system() -> Self {
+        Self {
+            manual: false,
+            pinned: Rc::new(Cell::new(None)),
+            latest: Rc::new(Cell::new(Instant::now())),
+        }
+    }
+
+    /// Simulated time starting at `start`, moved only by `set` and `advance`
+    pub fn manual(start: Instant) -> Self {
+        Self {
+            manual: true,
+            pinned: Rc::new(Cell::new(Some(start))),
+            latest: Rc::new(Cell::new(start)),
+        }
+    }
+
+    /// Current time
+    pub fn now(&self) -> Instant {
+        let now = self
+            .pinned
+            .get()
+            .unwrap_or_else(Instant::now)
+            .max(self.latest.get());
+        self.latest.set(now);
+        now
+    }
+
+    /// Time passed since `since` (zero if `since` is in the future)
+    pub fn elapsed(&self, since: Instant) -> Duration {
+        self.now().saturating_duration_since(since)
+    }
+
+    /// Pin the time (system clock: until `release`, manual clock: move it)
+    pub fn set(&self, time: Instant) {
+        self.pinned.set(Some(time));
+    }
+
+    /// Move a manual clock forward
+    #[cfg(test)]
+    pub fn advance(&self, duration: Duration) {
+        self.set(self.now() + duration);
+    }
+
+    /// Return a system clock to real time (no-op for manual clocks)
+    pub fn release(&self) {
+        if !self.manual {
+            self.pinned.set(None);
+        }
+    }
+
+    /// Convert an event timestamp (evdev uses the realtime clock) to an `Instant`
+    pub fn instant_from(timestamp: SystemTime) -> Instant {
+        let now = Instant::now();
+        let age = SystemTime::now()
+            .duration_since(timestamp)
+            .unwrap_or_default();
+        now.checked_sub(age).unwrap_or(now)
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::system()
+    }
+}
@@ -1,14 +1,21 @@
 use evdev::Key;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::time::{Duration, Instant};
 use tracing::warn;
 
-use crate::config::{Action as ConfigAction, Config, KeyCode, Layer};
+use crate::config::{
+    Action as ConfigAction, AltRepeatKey, AutoShiftGroup, ConditionalLayer, Config, KeyCode,
+    KeyOverride, Layer, MouseAction, TapDanceStep,
+};
 
 // Import action processors from the actions submodule
 use super::actions::{
-    DtConfig, DtProcessor, DtResolution, MtAction, MtConfig as ModtapConfig, MtProcessor,
-    MtResolution, OsmConfig, OsmProcessor, OsmResolution, RollingStats,
+    CapsWordKey, CapsWordProcessor, ComboConfig, ComboEvent, ComboProcessor, DtConfig, DtProcessor,
+    DtResolution, LeaderConfig, LeaderEvent, LeaderProcessor, MacroPlayer, MouseEvent,
+    MouseKeysProcessor, MtAction, MtConfig as ModtapConfig, MtProcessor, MtResolution, OsmConfig,
+    OsmProcessor, OsmResolution, RollingStats,
 };
+use super::clock::Clock;
 
 /// What a key press is doing (recorded on press, replayed on release)
 #[derive(Debug, Clone)]
@@ -17,18 +24,96 @@ enum KeyAction {
     RegularKey(KeyCode),
     /// Activated a modifier
     Modifier(KeyCode),
-    /// Layer activation
+    /// Released a held modifier for a key override (restored on release)
+    SuppressedModifier(KeyCode),
+    /// Momentary layer activation (MO/TO)
     Layer(Layer),
+    /// Tap-toggle layer (TT) - momentary while held, toggles on a clean tap
+    TapToggleLayer {
+        layer: Layer,
+        press_id: u64,
+        pressed_at: Instant,
+    },
+    /// One-shot layer (OSL) - momentary while held, one-shot on a clean tap
+    OneShotLayer {
+        layer: Layer,
+        press_id: u64,
+        pressed_at: Instant,
+    },
+    /// Mouse key (button held or cursor/wheel moving)
+    MouseManaged(MouseAction),
     /// MT key managed by MT processor
     MtManaged,
     /// SOCD managed key
     SocdManaged,
     /// DT key managed by DT processor
     DtManaged,
+    /// TD (multi-tap dance) key managed by DT processor
+    TdManaged,
     /// OSM key managed by OSM processor
     OsmManaged,
 }
 
+/// How a layer on the layer stack was activated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayerActivation {
+    /// Active while the physical key is held (MO/TO/TT/OSL)
+    Held(KeyCode),
+    /// Toggled on (TG/TT)
+    Toggled,
+    /// Active for the next key press only (OSL), since the given time
+    OneShot(Instant),
+}
+
+/// A key event held back until pending MT keys are decided
+#[derive(Debug, Clone)]
+enum HeldBackKey {
+    /// Key press, with the combo action it triggers (if any)
+    Press(KeyCode, Option<ConfigAction>),
+    /// Key release
+    Release(KeyCode),
+}
+
+/// Corrections within this window (ms) of an MT decision count as misfires
+const MISFIRE_WINDOW_MS: u128 = 1000;
+
+/// Tapped before releasing a lone Alt/GUI hold (QMK's dummy mod neutralizer), so the bare
+/// modifier press and release doesn't open a menu
+const MOD_NEUTRALIZER_KEY: KeyCode = KeyCode::KC_RCTL;
+
+/// Last MT decision, watched for a correction (misfire detection)
+#[derive(Debug, Clone, Copy)]
+struct MtDecision {
+    /// Physical MT key
+    keycode: KeyCode,
+    /// Resolved to hold (or tap)
+    hold: bool,
+    /// When it was decided
+    decided_at: Instant,
+    /// When Backspace was last pressed after it (nothing else typed since)
+    corrected_at: Option<Instant>,
+}
+
+/// MT decision logged for offline analysis (see `KeymapProcessor::log_mt_decisions`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MtDecisionLog {
+    /// Physical MT key
+    pub keycode: KeyCode,
+    /// When the key was pressed
+    pub pressed_at: Instant,
+    /// Resolved to hold (or tap)
+    pub hold: bool,
+    /// Corrected with Backspace and retyped the other way
+    pub misfired: bool,
+}
+
+/// An entry on the layer stack
+#[derive(Debug, Clone)]
+struct ActiveLayer {
+    layer: Layer,
+    activation: LayerActivation,
+}
+
 /// SOCD group configuration
 #[derive(Debug, Clone)]
 struct SocdGroup {
@@ -47,15 +132,59 @@ pub struct KeymapProcessor {
 
     /// MT (Mod-Tap) processor
     mt_processor: MtProcessor,
+    /// Key events held back while balanced/tap-preferred MT keys are undecided
+    mt_held_back: Vec<HeldBackKey>,
+    /// Last MT decision, for misfire detection
+    last_mt_decision: Option<MtDecision>,
+    /// All MT decisions, while logging is on (replay)
+    mt_decision_log: Option<Vec<MtDecisionLog>>,
 
     /// DT (Double-Tap) processor
     dt_processor: DtProcessor,
+    /// Steps of the TD keys tracked by the DT processor
+    dance_steps: HashMap<KeyCode, Vec<TapDanceStep>>,
 
     /// OSM (OneShot Modifier) processor
     osm_processor: OsmProcessor,
 
-    /// Current active layer
-    current_layer: Layer,
+    /// Combo processor (buffers presses ahead of action lookup)
+    combo_processor: ComboProcessor,
+    /// Keys of triggered combos, mapped to the key the combo action is tracked under
+    combo_members: HashMap<KeyCode, KeyCode>,
+
+    /// Leader processor (captures presses after a LEADER key, ahead of combos)
+    leader_processor: LeaderProcessor,
+    /// Replay unmatched leader sequences instead of swallowing them
+    leader_replay_unmatched: bool,
+
+    /// Mouse keys processor (REL/BTN events)
+    mouse_processor: MouseKeysProcessor,
+
+    /// Key groups that auto-shift
+    auto_shift_groups: Vec<AutoShiftGroup>,
+    /// Hold longer than this to type the shifted key
+    auto_shift_timeout_ms: u64,
+    /// Auto-shift keys waiting for release or the timeout (physical key -> output key)
+    auto_shift_pending: HashMap<KeyCode, KeyCode>,
+
+    /// Caps Word processor
+    caps_word_processor: CapsWordProcessor,
+    /// Whether Caps Word is holding Shift on the output
+    caps_word_shift: bool,
+
+    /// Macro player (SEQ playback)
+    macro_player: MacroPlayer,
+    /// Key events held back while a macro plays
+    deferred_events: VecDeque<(KeyCode, bool)>,
+
+    /// Active layers above the default layer (most recent at the end)
+    layer_stack: Vec<ActiveLayer>,
+    /// Default layer (bottom of the stack, set with DF)
+    default_layer: Layer,
+    /// Conditional layers from config (tri-layer)
+    conditional_layers: Vec<ConditionalLayer>,
+    /// Layers active because their conditions hold (on top of the stack)
+    conditional_active: Vec<Layer>,
     /// Base layer remaps from config
     base_remaps: HashMap<KeyCode, ConfigAction>,
     /// All layer remaps from config
@@ -73,14 +202,39 @@ pub struct KeymapProcessor {
 
     /// Track ALL keyboard key statistics (100% coverage)
     all_key_stats: HashMap<KeyCode, RollingStats>,
+    /// Key overrides from config (checked in order)
+    key_overrides: Vec<KeyOverride>,
+    /// Modifiers currently pressed on the output device (ordered, REPEAT replays them in order)
+    output_mods: BTreeSet<KeyCode>,
+    /// Last non-modifier key pressed on the output device, with the modifiers held then
+    last_output: Option<(KeyCode, Vec<KeyCode>)>,
+    /// Alternate keys for ALT_REPEAT
+    alt_repeat_keys: Vec<AltRepeatKey>,
+
     /// Track when each key was pressed (for measuring tap duration)
-    key_press_times: HashMap<KeyCode, std::time::Instant>,
+    key_press_times: HashMap<KeyCode, Instant>,
+    /// Number of key presses so far (detects interrupted TT/OSL taps)
+    press_counter: u64,
+
+    /// Tapping term for TT/OSL layer keys
+    tapping_term_ms: u32,
+    /// How long a one-shot layer stays active
+    oneshot_timeout_ms: u64,
+
+    /// Time source shared with all processors
+    clock: Clock,
 }
 
 impl KeymapProcessor {
     /// Create a new keymap processor from config
     #[must_use]
     pub fn new(config: &Config) -> Self {
+        Self::with_clock(config, Clock::system())
+    }
+
+    /// Create a keymap processor driven by the given clock (replay, simulated time)
+    #[must_use]
+    pub fn with_clock(config: &Config, clock: Clock) -> Self {
         let mut layers = HashMap::new();
         for (layer, layer_config) in &config.layers {
             layers.insert(layer.clone(), layer_config.remaps.clone());
@@ -152,8 +306,15 @@ impl KeymapProcessor {
             cross_hand_unwrap: config.mt_config.cross_hand_unwrap,
             adaptive_target_margin_ms: config.mt_config.adaptive_target_margin_ms,
             hold_do_nothing_emits_tap: config.mt_config.hold_do_nothing_emits_tap,
+            flavor: config.mt_config.flavor,
+            require_prior_idle_ms: config.mt_config.require_prior_idle_ms,
         };
 
+        let mut mt_processor = MtProcessor::new(mt_config).with_clock(clock.clone());
+        for (&keycode, &hand) in &config.hands {
+            mt_processor.set_hand(keycode, hand);
+        }
+
         // Build DT processor config
         let dt_config = DtConfig {
             tapping_term_ms: config.tapping_term_ms,
@@ -166,12 +327,45 @@ impl KeymapProcessor {
             tapping_term_ms: config.tapping_term_ms,
         };
 
+        // Build combo processor config
+        let combo_config = ComboConfig {
+            combo_term_ms: config.combo_term_ms.unwrap_or(50),
+        };
+
+        // Build leader processor config
+        let leader_config = LeaderConfig {
+            leader_timeout_ms: config.leader_timeout_ms.unwrap_or(500),
+        };
+
         Self {
             held_keys: HashMap::new(),
-            mt_processor: MtProcessor::new(mt_config),
-            dt_processor: DtProcessor::new(dt_config),
-            osm_processor: OsmProcessor::new(osm_config),
-            current_layer: Layer::base(),
+            mt_processor,
+            mt_held_back: Vec::new(),
+            last_mt_decision: None,
+            mt_decision_log: None,
+            dt_processor: DtProcessor::new(dt_config).with_clock(clock.clone()),
+            dance_steps: HashMap::new(),
+            osm_processor: OsmProcessor::new(osm_config).with_clock(clock.clone()),
+            combo_processor: ComboProcessor::new(combo_config, config.combos.clone())
+                .with_clock(clock.clone()),
+            combo_members: HashMap::new(),
+            leader_processor: LeaderProcessor::new(leader_config, config.leader_sequences.clone())
+                .with_clock(clock.clone()),
+            leader_replay_unmatched: config.leader_replay_unmatched,
+            mouse_processor: MouseKeysProcessor::new(config.mouse_keys.clone())
+                .with_clock(clock.clone()),
+            auto_shift_groups: config.auto_shift_groups.clone(),
+            auto_shift_timeout_ms: config.auto_shift_timeout_ms.unwrap_or(175),
+            auto_shift_pending: HashMap::new(),
+            caps_word_processor: CapsWordProcessor::new(config.caps_word.clone())
+                .with_clock(clock.clone()),
+            caps_word_shift: false,
+            macro_player: MacroPlayer::new().with_clock(clock.clone()),
+            deferred_events: VecDeque::new(),
+            layer_stack: Vec::new(),
+            default_layer: Layer::base(),
+            conditional_layers: config.conditional_layers.clone(),
+            conditional_active: Vec::new(),
             base_remaps: config.remaps.clone(),
             layers,
             game_mode_active: false,
@@ -179,31 +373,184 @@ impl KeymapProcessor {
             socd_key_to_group,
             socd_groups,
             all_key_stats: HashMap::new(),
+            key_overrides: config.key_overrides.clone(),
+            output_mods: BTreeSet::new(),
+            last_output: None,
+            alt_repeat_keys: config.alt_repeat_keys.clone(),
             key_press_times: HashMap::new(),
+            press_counter: 0,
+            tapping_term_ms: config.tapping_term_ms,
+            oneshot_timeout_ms: config.oneshot_timeout_ms.unwrap_or(5000),
+            clock,
         }
     }
 
+    /// Start logging MT decisions (collected with `take_mt_decisions`)
+    pub fn log_mt_decisions(&mut self) {
+        self.mt_decision_log.get_or_insert_with(Vec::new);
+    }
+
+    /// MT decisions logged since the last call
+    pub fn take_mt_decisions(&mut self) -> Vec<MtDecisionLog> {
+        self.mt_decision_log
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     /// Set game mode state
-    pub fn set_game_mode(&mut self, active: bool) {
+    /// Returns events to emit (the Shift of a Caps Word turned off by game mode)
+    pub fn set_game_mode(&mut self, active: bool) -> ProcessResult {
         self.game_mode_active = active;
         self.mt_processor.set_game_mode(active);
+        if !active {
+            return ProcessResult::None;
+        }
+
+        // Caps Word and leader capture turn off in game mode
+        self.caps_word_processor.deactivate();
+        self.leader_processor.cancel();
+        let caps_word_result = self.set_caps_word_shift(false);
+        self.track_output(caps_word_result)
     }
 
-    /// Check for DT timeouts and return events to emit
+    /// Check for macro, leader, combo, DT and OSM timeouts and return events to emit
     /// Should be called periodically (e.g., every 1ms in the idle loop)
     /// Returns ProcessResult that can be emitted directly
-    pub fn check_dt_timeouts(&mut self) -> ProcessResult {
+    pub fn check_timeouts(&mut self) -> ProcessResult {
+        // Continue macro playback, then process key events held back meanwhile
+        let macro_events = self.macro_player.advance();
+        let mut macro_result = self.track_output(ProcessResult::from_events(macro_events));
+        while !self.macro_player.is_playing() {
+            let Some((keycode, pressed)) = self.deferred_events.pop_front() else {
+                break;
+            };
+            let deferred_result = self.process_key(keycode, pressed);
+            macro_result = macro_result.then(deferred_result);
+        }
+
+        // Expired leader timeout - run the typed sequence or give up on it
+        let leader_result = match self.leader_processor.check_timeouts() {
+            Some(event) => self.apply_leader_event(event),
+            None => ProcessResult::None,
+        };
+
+        let timeout_result = self.check_processor_timeouts();
+
+        // Expired combo term - trigger or flush buffered presses
+        let active_layers = self.active_layers();
+        let events = self.combo_processor.check_timeouts(&active_layers);
+        let combo_result = self.apply_combo_events(events);
+
+        macro_result
+            .then(leader_result)
+            .then(timeout_result)
+            .then(combo_result)
+    }
+
+    /// Earliest time `check_timeouts` may have something to do
+    /// None = nothing pending, timeouts only need checking again after the next key event
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let auto_shift = self.auto_shift_pending.keys().filter_map(|keycode| {
+            self.key_press_times
+                .get(keycode)
+                .map(|&pressed_at| pressed_at + Duration::from_millis(self.auto_shift_timeout_ms))
+        });
+        let oneshot_layers = self
+            .layer_stack
+            .iter()
+            .filter_map(|entry| match entry.activation {
+                LayerActivation::OneShot(since) => {
+                    Some(since + Duration::from_millis(self.oneshot_timeout_ms))
+                }
+                _ => None,
+            });
+
+        [
+            self.macro_player.next_deadline(),
+            self.leader_processor.next_deadline(),
+            self.combo_processor.next_deadline(),
+            self.dt_processor.next_deadline(),
+            self.mt_processor.next_deadline(),
+            self.osm_processor.next_deadline(),
+            self.mouse_processor.next_deadline(),
+            self.caps_word_processor.next_deadline(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(auto_shift)
+        .chain(oneshot_layers)
+        .min()
+    }
+
+    /// Check timeouts of the processors behind the combo stage
+    fn check_processor_timeouts(&mut self) -> ProcessResult {
+        let mut events = Vec::new();
+
         let timeouts = self.dt_processor.check_timeouts();
-        if timeouts.is_empty() {
-            ProcessResult::None
-        } else {
-            let events = self.process_dt_timeouts(timeouts);
-            if events.is_empty() {
-                ProcessResult::None
-            } else {
-                ProcessResult::MultipleEvents(events)
+        let dt_result = self.process_dt_timeouts(timeouts);
+
+        // MT keys held past their tapping term resolve to hold
+        let mt_resolutions = self.mt_processor.check_timeouts();
+        let mt_result = self.apply_mt_resolutions(mt_resolutions);
+
+        // Expired one-shot modifiers get released
+        for (_keycode, resolution) in self.osm_processor.check_timeouts() {
+            if let OsmResolution::ReleaseModifier(mod_key) = resolution {
+                events.push((mod_key, false));
             }
         }
+
+        // Held mouse keys keep the cursor and wheel moving
+        let mouse_result = ProcessResult::from_mouse_events(self.mouse_processor.tick());
+
+        // Auto-shift keys held past the timeout are typed shifted
+        let auto_shift_result = self.check_auto_shift_timeouts();
+
+        // Caps Word timed out - release its Shift
+        self.caps_word_processor.check_timeouts();
+        if !self.caps_word_processor.is_active() {
+            if let ProcessResult::EmitKey(key, pressed) = self.set_caps_word_shift(false) {
+                events.push((key, pressed));
+            }
+        }
+
+        // Expired one-shot layers get dropped
+        let timeout_ms = u128::from(self.oneshot_timeout_ms);
+        self.layer_stack.retain(|entry| match entry.activation {
+            LayerActivation::OneShot(since) => self.clock.elapsed(since).as_millis() <= timeout_ms,
+            _ => true,
+        });
+        self.update_conditional_layers();
+
+        let result = dt_result
+            .then(mt_result)
+            .then(mouse_result)
+            .then(auto_shift_result)
+            .then(ProcessResult::from_events(events));
+        let result = self.track_output(result);
+
+        // MT keys decided at the term let the keys held back behind them through
+        let held_back_result = self.release_held_back_keys();
+        result.then(held_back_result)
+    }
+
+    /// Replay the key events held back behind MT keys that are now decided
+    fn release_held_back_keys(&mut self) -> ProcessResult {
+        if self.mt_held_back.is_empty() || self.mt_processor.holds_back_other_keys() {
+            return ProcessResult::None;
+        }
+
+        // Replayed presses of MT keys may start holding back the rest again
+        let mut result = ProcessResult::None;
+        for event in std::mem::take(&mut self.mt_held_back) {
+            let replayed = match event {
+                HeldBackKey::Press(keycode, action) => self.press_key(keycode, action),
+                HeldBackKey::Release(keycode) => self.release_key(keycode),
+            };
+            result = result.then(replayed);
+        }
+        result
     }
 
     /// Get all currently held keys (for graceful shutdown)
@@ -322,8 +669,36 @@ impl KeymapProcessor {
         }
     }
 
+    /// Process a key event that happened at `time` (e.g. the evdev event timestamp)
+    /// Tap/hold decisions use this time instead of when the event is processed
+    pub fn process_key_at(
+        &mut self,
+        keycode: KeyCode,
+        pressed: bool,
+        time: Instant,
+    ) -> ProcessResult {
+        self.clock.set(time);
+        let result = self.process_key(keycode, pressed);
+        self.clock.release();
+        result
+    }
+
+    /// Check timeouts as of `time` (replay, simulated time)
+    pub fn check_timeouts_at(&mut self, time: Instant) -> ProcessResult {
+        self.clock.set(time);
+        let result = self.check_timeouts();
+        self.clock.release();
+        result
+    }
+
     /// Process a key event
     pub fn process_key(&mut self, keycode: KeyCode, pressed: bool) -> ProcessResult {
+        // Hold back key events while a macro plays so they don't interleave with it
+        if self.macro_player.is_playing() {
+            self.deferred_events.push_back((keycode, pressed));
+            return ProcessResult::None;
+        }
+
         if pressed {
             self.process_key_press(keycode)
         } else {
@@ -331,215 +706,156 @@ impl KeymapProcessor {
         }
     }
 
-    fn process_key_press(&mut self, keycode: KeyCode) -> ProcessResult {
-        let mut actions = Vec::new();
+    /// Process a key repeat (evdev value 2) from the physical keyboard
+    /// Repeats the regular key the physical key is currently emitting, if any, so
+    /// remapped and layered keys autorepeat as the output key. Keys that are
+    /// undecided or emit only modifiers, layers, strings etc. don't repeat.
+    pub fn process_key_repeat(&self, keycode: KeyCode) -> ProcessResult {
+        if self.macro_player.is_playing() {
+            return ProcessResult::None;
+        }
+
+        // Any key of a triggered combo repeats the combo action
+        let keycode = self.combo_members.get(&keycode).copied().unwrap_or(keycode);
+        self.held_keys
+            .get(&keycode)
+            .and_then(|actions| {
+                actions.iter().rev().find_map(|action| match action {
+                    KeyAction::RegularKey(key) if !key.is_modifier() => Some(*key),
+                    _ => None,
+                })
+            })
+            .map_or(ProcessResult::None, ProcessResult::RepeatKey)
+    }
 
+    fn process_key_press(&mut self, keycode: KeyCode) -> ProcessResult {
         // Track press time for ALL keys (100% keyboard coverage)
-        self.key_press_times
-            .insert(keycode, std::time::Instant::now());
+        self.key_press_times.insert(keycode, self.clock.now());
+
+        // Leader stage - presses after a LEADER key are captured for the sequence
+        if self.leader_processor.is_capturing() {
+            let event = self.leader_processor.on_press(keycode);
+            return self.apply_leader_event(event);
+        }
+
+        // Combos are skipped in game mode (no buffering delay)
+        if self.game_mode_active && !self.combo_processor.is_buffering() {
+            return self.press_key(keycode, None);
+        }
 
-        // Check DT timeouts at the start of every key press
+        // Combo stage - key presses that may be part of a combo are held back
+        let active_layers = self.active_layers();
+        let events = self.combo_processor.on_press(keycode, &active_layers);
+        self.apply_combo_events(events)
+    }
+
+    /// Press pipeline for a key that made it past the combo stage
+    /// `action` overrides the keymap lookup (used for combo actions)
+    fn press_key(&mut self, keycode: KeyCode, action: Option<ConfigAction>) -> ProcessResult {
+        // Check timeouts at the start of every key press
         // This ensures pending taps are emitted even if user is typing other keys
-        let dt_timeout_events = {
-            let timeouts = self.dt_processor.check_timeouts();
-            if !timeouts.is_empty() {
-                self.process_dt_timeouts(timeouts)
-            } else {
-                Vec::new()
-            }
+        let timeout_result = self.check_processor_timeouts();
+
+        // KC_NO is a true no-op: it doesn't decide pending keys or consume one-shots
+        let blocked = match &action {
+            Some(action) => *action == ConfigAction::NO,
+            None => self.lookup_action(keycode) == Some(ConfigAction::NO),
         };
+        if blocked {
+            return timeout_result;
+        }
 
-        // Look up action for this key
-        let action = self.lookup_action(keycode);
+        self.press_counter += 1;
 
-        match action {
-            Some(ConfigAction::Key(output_key)) => {
-                // Simple key remap
-                actions.push(KeyAction::RegularKey(output_key));
-                self.held_keys.insert(keycode, actions);
-
-                // Notify MT processor (for permissive hold)
-                let mt_resolutions = self.mt_processor.on_other_key_press(output_key);
-                let result = if !mt_resolutions.is_empty() {
-                    let mut events = vec![(output_key, true)];
-                    events.extend(self.apply_mt_resolutions(mt_resolutions));
-                    ProcessResult::MultipleEvents(events)
-                } else {
-                    ProcessResult::EmitKey(output_key, true)
-                };
-                self.combine_with_timeouts(dt_timeout_events.clone(), result)
+        // Balanced/tap-preferred MT keys are decided after this key - hold it back
+        if self.mt_processor.holds_back_other_keys() {
+            let mt_resolutions = self.mt_processor.on_other_key_press(keycode);
+            self.mt_held_back.push(HeldBackKey::Press(keycode, action));
+            if mt_resolutions.is_empty() {
+                return timeout_result;
             }
-            Some(ConfigAction::MT(tap_action, hold_action)) => {
-                // MT now uses Box<Action> - extract KeyCode if it's a simple Key action
-                let tap_key_opt = match tap_action.as_ref() {
-                    ConfigAction::Key(kc) => Some(*kc),
-                    _ => None,
-                };
-                let hold_key_opt = match hold_action.as_ref() {
-                    ConfigAction::Key(kc) => Some(*kc),
-                    _ => None,
-                };
 
-                if let (Some(tap_key), Some(hold_key)) = (tap_key_opt, hold_key_opt) {
-                    // MT key - first check if other MT keys need to be resolved
-                    let mt_resolutions = self.mt_processor.on_other_key_press(tap_key);
-
-                    // Then register this MT key
-                    let result = if let Some(resolution) =
-                        self.mt_processor.on_press(keycode, tap_key, hold_key)
-                    {
-                        // Double-tap detected, emit the hold immediately
-                        actions.push(KeyAction::MtManaged);
-                        self.held_keys.insert(keycode, actions);
-
-                        if !mt_resolutions.is_empty() {
-                            let mut events = self.apply_mt_resolutions(mt_resolutions);
-                            events.extend(self.mt_resolution_to_events(&resolution));
-                            ProcessResult::MultipleEvents(events)
-                        } else {
-                            self.apply_mt_resolution_single(resolution)
-                        }
-                    } else {
-                        // Normal MT processing
-                        actions.push(KeyAction::MtManaged);
-                        self.held_keys.insert(keycode, actions);
-
-                        if !mt_resolutions.is_empty() {
-                            ProcessResult::MultipleEvents(self.apply_mt_resolutions(mt_resolutions))
-                        } else {
-                            ProcessResult::None
-                        }
-                    };
-                    self.combine_with_timeouts(dt_timeout_events.clone(), result)
-                } else {
-                    // MT with complex nested actions not yet supported
-                    warn!("MT with non-Key actions not yet supported (e.g., MT(TO(...), ...))");
-                    self.combine_with_timeouts(dt_timeout_events.clone(), ProcessResult::None)
-                }
-            }
-            Some(ConfigAction::TO(layer)) => {
-                // Layer switch
-                self.current_layer = layer.clone();
-                actions.push(KeyAction::Layer(layer));
-                self.held_keys.insert(keycode, actions);
-                self.combine_with_timeouts(dt_timeout_events.clone(), ProcessResult::None)
-            }
-            Some(ConfigAction::SOCD(this_action, _opposing_actions)) => {
-                // SOCD handling - extract KeyCode from Action
-                let result = if let ConfigAction::Key(this_key) = this_action.as_ref() {
-                    actions.push(KeyAction::SocdManaged);
-                    self.held_keys.insert(keycode, actions);
-                    self.apply_socd_to_key_press(*this_key)
-                } else {
-                    warn!("SOCD with non-Key actions not yet supported");
-                    ProcessResult::None
-                };
-                self.combine_with_timeouts(dt_timeout_events.clone(), result)
-            }
-            Some(ConfigAction::CMD(command)) => {
-                // Run arbitrary command
-                self.combine_with_timeouts(
-                    dt_timeout_events.clone(),
-                    ProcessResult::RunCommand(command.clone()),
-                )
-            }
-            Some(ConfigAction::OSM(modifier_action)) => {
-                // OSM (OneShot Modifier) - extract KeyCode for simple cases
-                let result =
-                    if let Some(modifier_key) = Self::extract_keycode(modifier_action.as_ref()) {
-                        // Check OSM timeouts first
-                        let timeouts = self.osm_processor.check_timeouts();
-                        // Process timeouts if any (emit release events)
-                        if !timeouts.is_empty() {
-                            // These will be handled in the event loop
-                        }
+            // The press decided the MT keys after all - replay it behind the decision
+            let mt_result = self.apply_mt_resolutions(mt_resolutions);
+            let mt_result = self.track_output(mt_result);
+            let held_back_result = self.release_held_back_keys();
+            return timeout_result.then(mt_result).then(held_back_result);
+        }
 
-                        // Register this OSM key
-                        let _resolution = self.osm_processor.on_press(keycode, modifier_key);
+        // A pending auto-shift key is typed unshifted once another key is pressed
+        let auto_shift_result = self.interrupt_auto_shift();
 
-                        actions.push(KeyAction::OsmManaged);
-                        self.held_keys.insert(keycode, actions);
+        // Another key press ends running tap dances
+        let dance_resolutions = self.dt_processor.interrupt_dances(keycode);
+        let dance_result = self.process_dt_timeouts(dance_resolutions);
 
-                        // OSM doesn't emit on press, waits for release to determine tap/hold
-                        ProcessResult::None
-                    } else {
-                        // OSM with complex actions not yet supported
-                        warn!("OSM with non-Key actions not yet supported");
-                        ProcessResult::None
-                    };
-                self.combine_with_timeouts(dt_timeout_events.clone(), result)
-            }
-            Some(ConfigAction::DT(tap_action, double_tap_action)) => {
-                // DT (Double-Tap) - extract KeyCodes for simple cases
-                if let (Some(tap_key), Some(dtap_key)) = (
-                    Self::extract_keycode(tap_action.as_ref()),
-                    Self::extract_keycode(double_tap_action.as_ref()),
-                ) {
-                    // Register this DT key
-                    let resolution = self.dt_processor.on_press(keycode, tap_key, dtap_key);
+        // Resolve pending MT keys first (permissive hold, rolls, chords) so that an
+        // MT key resolving to a layer already applies to the key being pressed
+        let mt_resolutions = self.mt_processor.on_other_key_press(keycode);
+        let mt_result = self.apply_mt_resolutions(mt_resolutions);
+
+        // Look up action for this key
+        let action = action
+            .or_else(|| self.lookup_action(keycode))
+            .unwrap_or(ConfigAction::Key(keycode));
+        self.watch_misfire_correction(keycode, &action);
+
+        // A pending MT key resolved to a layer where this key is KC_NO
+        if action == ConfigAction::NO {
+            let prior_result =
+                self.track_output(auto_shift_result.then(dance_result).then(mt_result));
+            return timeout_result.then(prior_result);
+        }
 
-                    actions.push(KeyAction::DtManaged);
-                    self.held_keys.insert(keycode, actions);
+        // Held OSM keys turn into normal modifiers, active one-shots get consumed
+        let osm_result = match &action {
+            ConfigAction::OSM(..) => ProcessResult::None,
+            action if Self::is_layer_action(action) => ProcessResult::None,
+            ConfigAction::Key(output_key) => self.apply_osm_other_key_press(*output_key),
+            _ => self.apply_osm_other_key_press(keycode),
+        };
 
-                    let result = match resolution {
-                        DtResolution::PressSecond(key) => {
-                            // Double-tap detected! Press second action
-                            ProcessResult::EmitKey(key, true)
-                        }
-                        DtResolution::HoldFirst(key) => {
-                            // Held beyond tapping term - start holding first action
-                            ProcessResult::EmitKey(key, true)
-                        }
-                        DtResolution::TapFirst(key) => {
-                            // Single-tap timeout - emit first action as tap
-                            ProcessResult::TapKeyPressRelease(key)
-                        }
-                        DtResolution::Undecided => {
-                            // Still pending - no events for this key
-                            ProcessResult::None
-                        }
-                        _ => ProcessResult::None,
-                    };
+        // One-shot layers only apply to the first key pressed after them
+        if !Self::is_layer_action(&action) {
+            self.layer_stack
+                .retain(|entry| !matches!(entry.activation, LayerActivation::OneShot(_)));
+            self.update_conditional_layers();
+        }
 
-                    self.combine_with_timeouts(dt_timeout_events.clone(), result)
-                } else {
-                    // DT with complex actions not yet supported
-                    warn!("DT with non-Key actions not yet supported");
-                    ProcessResult::None
-                }
+        // Everything emitted so far counts for the key override modifiers
+        let prior_result = self.track_output(
+            auto_shift_result
+                .then(dance_result)
+                .then(mt_result)
+                .then(osm_result),
+        );
+
+        // Key overrides replace the action while their trigger modifiers are held
+        let press_result = match self.find_key_override(&action) {
+            Some((replacement, suppressed)) => {
+                self.press_key_override(keycode, &replacement, &suppressed)
             }
-            None => {
-                // No remap - check if MT keys are pending (permissive hold)
-                let mt_resolutions = self.mt_processor.on_other_key_press(keycode);
-
-                if !mt_resolutions.is_empty() {
-                    // MT keys resolved, emit them first then this key
-                    let mut events = self.apply_mt_resolutions(mt_resolutions);
-                    events.push((keycode, true));
-
-                    actions.push(KeyAction::RegularKey(keycode));
-                    self.held_keys.insert(keycode, actions);
-                    self.combine_with_timeouts(
-                        dt_timeout_events,
-                        ProcessResult::MultipleEvents(events),
-                    )
-                } else {
-                    // Pass through unchanged
-                    actions.push(KeyAction::RegularKey(keycode));
-                    self.held_keys.insert(keycode, actions);
-                    self.combine_with_timeouts(
-                        dt_timeout_events,
-                        ProcessResult::EmitKey(keycode, true),
-                    )
+            None => match self.auto_shift_key(&action) {
+                Some(output_key) => {
+                    // Typed on release or at the timeout, depending on how long it's held
+                    self.auto_shift_pending.insert(keycode, output_key);
+                    ProcessResult::None
                 }
-            }
-        }
+                None => self.press_action(keycode, &action),
+            },
+        };
+        let press_result = self.track_output(press_result);
+
+        timeout_result.then(prior_result).then(press_result)
     }
 
     fn process_key_release(&mut self, keycode: KeyCode) -> ProcessResult {
+        // Bigram overlap for MT keys pressed while this key was held
+        self.mt_processor.record_release(keycode);
+
         // Track tap duration for ALL keys (100% keyboard coverage)
-        if let Some(press_time) = self.key_press_times.remove(&keycode) {
-            let duration_ms = press_time.elapsed().as_millis() as f32;
+        if let Some(press_time) = self.key_press_times.get(&keycode) {
+            let duration_ms = self.clock.elapsed(*press_time).as_millis() as f32;
 
             // Only record taps below threshold (not holds)
             // This prevents survivorship bias - only successful taps are tracked
@@ -553,94 +869,433 @@ impl KeymapProcessor {
             }
         }
 
-        // Check DT timeouts on release too
-        // This is CRITICAL - without this, single-taps never emit!
-        let dt_timeout_events = {
-            let timeouts = self.dt_processor.check_timeouts();
-            if !timeouts.is_empty() {
-                self.process_dt_timeouts(timeouts)
-            } else {
-                Vec::new()
+        // Releases of keys captured by the leader stage are swallowed
+        let result = if self.leader_processor.on_release(keycode) {
+            ProcessResult::None
+        } else {
+            // Combo stage - flushes buffered presses, swallows releases of combo keys
+            let active_layers = self.active_layers();
+            let events = self.combo_processor.on_release(keycode, &active_layers);
+            self.apply_combo_events(events)
+        };
+
+        // Press time is kept until here for the auto-shift timeout
+        self.key_press_times.remove(&keycode);
+        result
+    }
+
+    /// Release pipeline for a key that made it past the combo stage
+    fn release_key(&mut self, keycode: KeyCode) -> ProcessResult {
+        // Check timeouts on release too
+        // This is CRITICAL - without this, DT single-taps never emit!
+        let mut timeout_result = self.check_processor_timeouts();
+
+        // Keys held back behind pending MT keys: a balanced MT key held across the
+        // key's whole press becomes a hold, otherwise the release is held back too
+        if self.mt_processor.holds_back_other_keys() {
+            let mt_resolutions = self.mt_processor.on_other_key_release(keycode);
+            if !mt_resolutions.is_empty() {
+                let mt_result = self.apply_mt_resolutions(mt_resolutions);
+                let mt_result = self.track_output(mt_result);
+                let held_back_result = self.release_held_back_keys();
+                timeout_result = timeout_result.then(mt_result).then(held_back_result);
+            } else if self
+                .mt_held_back
+                .iter()
+                .any(|event| matches!(event, HeldBackKey::Press(k, _) if *k == keycode))
+            {
+                self.mt_held_back.push(HeldBackKey::Release(keycode));
+                return timeout_result;
             }
+        }
+
+        // Auto-shift key released before the timeout - type it unshifted
+        let auto_shift_result = match self.auto_shift_pending.remove(&keycode) {
+            Some(output_key) => self.tap_action(keycode, &ConfigAction::Key(output_key)),
+            None => ProcessResult::None,
         };
 
-        if let Some(actions) = self.held_keys.remove(&keycode) {
-            let mut events = Vec::new();
+        // A lone MT hold released for its tap - neutralize it before the hold goes up
+        let neutralizer_result = self.neutralize_lone_hold(keycode);
+        let release_result = neutralizer_result.then(self.release_held_actions(keycode));
 
-            for action in actions {
-                match action {
-                    KeyAction::RegularKey(key) => {
-                        events.push((key, false));
-                    }
-                    KeyAction::Modifier(key) => {
-                        events.push((key, false));
-                    }
-                    KeyAction::Layer(_prev_layer) => {
-                        // Switch back to base layer
-                        self.current_layer = Layer::base();
-                    }
-                    KeyAction::MtManaged => {
-                        // Let MT processor handle the release
-                        if let Some(resolution) = self.mt_processor.on_release(keycode) {
-                            return self.apply_mt_resolution_single(resolution);
-                        }
-                    }
-                    KeyAction::SocdManaged => {
-                        // Apply SOCD release logic
-                        return self.apply_socd_to_key_release(keycode);
-                    }
-                    KeyAction::DtManaged => {
-                        // Let DT processor handle the release
-                        let resolution = self.dt_processor.on_release(keycode);
-                        let result = match resolution {
-                            DtResolution::ReleaseFirst(key) => {
-                                // Was holding first action - release it
-                                ProcessResult::EmitKey(key, false)
-                            }
-                            DtResolution::ReleaseSecond(key) => {
-                                // Was holding second action (double-tap-hold) - release it
-                                ProcessResult::EmitKey(key, false)
-                            }
-                            DtResolution::Undecided => {
-                                // Still in Pending or Tapped state, waiting
-                                ProcessResult::None
-                            }
-                            _ => {
-                                // Other resolutions shouldn't come from on_release
-                                ProcessResult::None
-                            }
-                        };
-                        return self.combine_with_timeouts(dt_timeout_events.clone(), result);
+        // Releasing the key that consumed a one-shot releases the one-shot modifier
+        let osm_result = self.apply_osm_other_key_release(keycode);
+
+        let result = self.track_output(auto_shift_result.then(release_result).then(osm_result));
+
+        // A released MT key lets the keys held back behind it through
+        let held_back_result = self.release_held_back_keys();
+        timeout_result.then(result).then(held_back_result)
+    }
+
+    /// Apply combo stage results in order
+    fn apply_combo_events(&mut self, events: Vec<ComboEvent>) -> ProcessResult {
+        let mut result = ProcessResult::None;
+
+        for event in events {
+            let applied = match event {
+                ComboEvent::PressKey(keycode) => self.press_key(keycode, None),
+                ComboEvent::ReleaseKey(keycode) => self.release_key(keycode),
+                ComboEvent::PressCombo(index) => {
+                    // The combo action is tracked under the first key of the combo,
+                    // every key of the combo refers to it
+                    let combo = self.combo_processor.combo(index);
+                    let (keycode, action) = (combo.keys[0], combo.action.clone());
+                    for &member in &combo.keys {
+                        self.combo_members.insert(member, keycode);
                     }
-                    KeyAction::OsmManaged => {
-                        // Let OSM processor handle the release
-                        let resolution = self.osm_processor.on_release(keycode);
-                        match resolution {
-                            OsmResolution::ActivateModifier(mod_key) => {
-                                // Tapped - activate one-shot
-                                return ProcessResult::EmitKey(mod_key, true);
-                            }
-                            OsmResolution::ReleaseModifier(mod_key) => {
-                                // Held - release normal modifier
-                                return ProcessResult::EmitKey(mod_key, false);
-                            }
-                            OsmResolution::None => {
-                                return ProcessResult::None;
-                            }
-                        }
+                    self.press_key(keycode, Some(action))
+                }
+                ComboEvent::ReleaseCombo(index) => {
+                    // Released by whichever key of the combo is released first
+                    let keycode = self.combo_processor.combo(index).keys[0];
+                    self.combo_members.retain(|_, owner| *owner != keycode);
+                    self.release_key(keycode)
+                }
+            };
+            result = result.then(applied);
+        }
+
+        result
+    }
+
+    /// Apply a leader stage result
+    fn apply_leader_event(&mut self, event: LeaderEvent) -> ProcessResult {
+        match event {
+            LeaderEvent::Pending => ProcessResult::None,
+            LeaderEvent::Matched(index) => {
+                // The sequence action is tapped under the last key of the sequence
+                let sequence = self.leader_processor.sequence(index);
+                let Some(&keycode) = sequence.keys.last() else {
+                    return ProcessResult::None;
+                };
+                let action = sequence.action.clone();
+                let result = self.tap_action(keycode, &action);
+                self.track_output(result)
+            }
+            LeaderEvent::Unmatched(keys) if self.leader_replay_unmatched => {
+                // Replay the captured keys as normal presses (skipping the combo stage);
+                // keys still held get released later by their own release
+                let mut result = ProcessResult::None;
+                for keycode in keys {
+                    let pressed = self.press_key(keycode, None);
+                    result = result.then(pressed);
+                    if !self.leader_processor.on_release(keycode) {
+                        let released = self.release_key(keycode);
+                        result = result.then(released);
                     }
                 }
+                result
             }
+            LeaderEvent::Unmatched(_) => ProcessResult::None,
+        }
+    }
+
+    /// Layers currently active (default layer, the layer stack and conditional layers)
+    fn active_layers(&self) -> Vec<Layer> {
+        std::iter::once(&self.default_layer)
+            .chain(self.layer_stack.iter().map(|entry| &entry.layer))
+            .chain(self.conditional_active.iter())
+            .cloned()
+            .collect()
+    }
 
-            if events.is_empty() {
+    /// Run the press side of an action for a physical key
+    /// Records what the key is doing in `held_keys` so it can be replayed on release.
+    /// Used for top-level remaps and for the resolved side of MT keys alike.
+    fn press_action(&mut self, keycode: KeyCode, action: &ConfigAction) -> ProcessResult {
+        match action {
+            ConfigAction::Key(output_key) => {
+                let caps_word_result = self.apply_caps_word(*output_key);
+                self.record_held(keycode, KeyAction::RegularKey(*output_key));
+                caps_word_result.then(ProcessResult::EmitKey(*output_key, true))
+            }
+            ConfigAction::MT(tap_action, hold_action, options) => {
+                // Register the MT key - resolution happens later (other key, release, timeout)
+                // unless double-tap-then-hold or cross-hand unwrap resolves it right away
+                self.record_held(keycode, KeyAction::MtManaged);
+                match self.mt_processor.on_press(
+                    keycode,
+                    tap_action.as_ref().clone(),
+                    hold_action.as_ref().clone(),
+                    options,
+                ) {
+                    Some(resolution) => self.apply_mt_resolution(resolution),
+                    None => ProcessResult::None,
+                }
+            }
+            ConfigAction::TO(layer) | ConfigAction::MO(layer) => {
+                // Momentary layer switch
+                self.push_layer(layer, LayerActivation::Held(keycode));
+                self.record_held(keycode, KeyAction::Layer(layer.clone()));
                 ProcessResult::None
-            } else if events.len() == 1 {
-                ProcessResult::EmitKey(events[0].0, events[0].1)
-            } else {
-                ProcessResult::MultipleEvents(events)
             }
-        } else {
-            ProcessResult::None
+            ConfigAction::TG(layer) => {
+                self.toggle_layer(layer);
+                ProcessResult::None
+            }
+            ConfigAction::TT(layer) => {
+                // Momentary until released, toggles if it turns out to be a tap
+                self.push_layer(layer, LayerActivation::Held(keycode));
+                self.record_held(
+                    keycode,
+                    KeyAction::TapToggleLayer {
+                        layer: layer.clone(),
+                        press_id: self.press_counter,
+                        pressed_at: self.clock.now(),
+                    },
+                );
+                ProcessResult::None
+            }
+            ConfigAction::OSL(layer) => {
+                // Momentary until released, one-shot if it turns out to be a tap
+                self.push_layer(layer, LayerActivation::Held(keycode));
+                self.record_held(
+                    keycode,
+                    KeyAction::OneShotLayer {
+                        layer: layer.clone(),
+                        press_id: self.press_counter,
+                        pressed_at: self.clock.now(),
+                    },
+                );
+                ProcessResult::None
+            }
+            ConfigAction::DF(layer) => {
+                self.default_layer = layer.clone();
+                self.update_conditional_layers();
+                ProcessResult::None
+            }
+            ConfigAction::STR(text) => {
+                // Typed out all at once by the event loop
+                ProcessResult::TypeString(text.clone(), false)
+            }
+            ConfigAction::SEQ(steps) => {
+                // Emit what's due now, the rest plays from check_timeouts
+                ProcessResult::from_events(self.macro_player.play(steps))
+            }
+            ConfigAction::CAPS_WORD => {
+                self.caps_word_processor.toggle();
+                if self.caps_word_processor.is_active() {
+                    ProcessResult::None
+                } else {
+                    self.set_caps_word_shift(false)
+                }
+            }
+            ConfigAction::MOUSE(mouse_action) => {
+                self.record_held(keycode, KeyAction::MouseManaged(*mouse_action));
+                ProcessResult::from_mouse_events(self.mouse_processor.on_press(*mouse_action))
+            }
+            ConfigAction::REPEAT => match self.last_output.clone() {
+                Some((key, mods)) => self.press_repeat(keycode, key, &mods),
+                None => ProcessResult::None,
+            },
+            ConfigAction::ALT_REPEAT => {
+                let alternate = self
+                    .last_output
+                    .clone()
+                    .and_then(|(key, mods)| Some((self.alt_repeat_key(key, &mods)?, mods)));
+                match alternate {
+                    Some((alt, mods)) => self.press_repeat(keycode, alt, &mods),
+                    None => ProcessResult::None,
+                }
+            }
+            ConfigAction::LEADER => {
+                // Following presses are captured by the leader stage (not in game mode)
+                if !self.game_mode_active {
+                    self.leader_processor.start();
+                }
+                ProcessResult::None
+            }
+            ConfigAction::TRNS | ConfigAction::NO => {
+                // TRNS is resolved during lookup, anything left over does nothing
+                ProcessResult::None
+            }
+            ConfigAction::SOCD(this_action, _opposing_actions) => {
+                // SOCD handling - extract KeyCode from Action
+                if let ConfigAction::Key(this_key) = this_action.as_ref() {
+                    self.record_held(keycode, KeyAction::SocdManaged);
+                    self.apply_socd_to_key_press(*this_key)
+                } else {
+                    warn!("SOCD with non-Key actions not yet supported");
+                    ProcessResult::None
+                }
+            }
+            ConfigAction::CMD(command) => {
+                // Run arbitrary command
+                ProcessResult::RunCommand(command.clone())
+            }
+            ConfigAction::OSM(modifier_action, options) => {
+                // OSM (OneShot Modifier) - extract KeyCode for simple cases
+                if let Some(modifier_key) = Self::extract_keycode(modifier_action.as_ref()) {
+                    // Register this OSM key
+                    let _resolution = self.osm_processor.on_press(keycode, modifier_key, options);
+                    self.record_held(keycode, KeyAction::OsmManaged);
+
+                    // OSM doesn't emit on press, waits for release to determine tap/hold
+                    ProcessResult::None
+                } else {
+                    // OSM with complex actions not yet supported
+                    warn!("OSM with non-Key actions not yet supported");
+                    ProcessResult::None
+                }
+            }
+            ConfigAction::DT(tap_action, double_tap_action, options) => {
+                // Register this DT key
+                self.record_held(keycode, KeyAction::DtManaged);
+                let resolution = self.dt_processor.on_press(
+                    keycode,
+                    tap_action.as_ref().clone(),
+                    double_tap_action.as_ref().clone(),
+                    options,
+                );
+                self.apply_dt_resolution(keycode, resolution)
+            }
+            ConfigAction::LCTL(inner)
+            | ConfigAction::LSFT(inner)
+            | ConfigAction::LALT(inner)
+            | ConfigAction::LGUI(inner)
+            | ConfigAction::RCTL(inner)
+            | ConfigAction::RSFT(inner)
+            | ConfigAction::RALT(inner)
+            | ConfigAction::RGUI(inner)
+            | ConfigAction::HYPR(inner)
+            | ConfigAction::MEH(inner) => {
+                // Press the modifiers, then the wrapped action
+                // (released in reverse order on release)
+                let mut result = ProcessResult::None;
+                for &modifier in action.wrap_modifiers() {
+                    self.record_held(keycode, KeyAction::RegularKey(modifier));
+                    result = result.then(ProcessResult::EmitKey(modifier, true));
+                }
+                let inner_result = self.press_action(keycode, inner);
+                result.then(inner_result)
+            }
+            ConfigAction::TD(steps) => {
+                if steps.is_empty() {
+                    return ProcessResult::None;
+                }
+
+                // Register this TD key - resolves on release, timeout or another key
+                self.dance_steps.insert(keycode, steps.clone());
+                let resolution = self.dt_processor.on_dance_press(keycode, steps.len());
+                self.record_held(keycode, KeyAction::TdManaged);
+                self.apply_dance_resolution(keycode, resolution)
+            }
+        }
+    }
+
+    /// Press and immediately release an action (used for resolved taps)
+    fn tap_action(&mut self, keycode: KeyCode, action: &ConfigAction) -> ProcessResult {
+        // Keep whatever the physical key is still doing out of the way of the tap
+        let outer = self.held_keys.remove(&keycode);
+        let press_result = self.press_action(keycode, action);
+        let release_result = self.release_held_actions(keycode);
+        if let Some(outer) = outer {
+            self.held_keys.insert(keycode, outer);
+        }
+        press_result.then(release_result)
+    }
+
+    /// Record what a physical key is doing (replayed in reverse order on release)
+    fn record_held(&mut self, keycode: KeyCode, action: KeyAction) {
+        self.held_keys.entry(keycode).or_default().push(action);
+    }
+
+    /// Replay everything recorded for a physical key as releases
+    fn release_held_actions(&mut self, keycode: KeyCode) -> ProcessResult {
+        let Some(actions) = self.held_keys.remove(&keycode) else {
+            return ProcessResult::None;
+        };
+
+        let mut result = ProcessResult::None;
+        for action in actions.into_iter().rev() {
+            let released = self.release_key_action(keycode, action);
+            result = result.then(released);
+        }
+        result
+    }
+
+    /// Release a single recorded key action
+    fn release_key_action(&mut self, keycode: KeyCode, action: KeyAction) -> ProcessResult {
+        match action {
+            KeyAction::RegularKey(key) => ProcessResult::EmitKey(key, false),
+            KeyAction::Modifier(key) => ProcessResult::EmitKey(key, false),
+            KeyAction::SuppressedModifier(key) => {
+                // Restore the modifier the key override suppressed
+                if self.is_modifier_held(key) {
+                    ProcessResult::EmitKey(key, true)
+                } else {
+                    ProcessResult::None
+                }
+            }
+            KeyAction::Layer(_layer) => {
+                // Drop the layer this key was holding
+                self.release_held_layer(keycode);
+                ProcessResult::None
+            }
+            KeyAction::TapToggleLayer {
+                layer,
+                press_id,
+                pressed_at,
+            } => {
+                self.release_held_layer(keycode);
+                if self.is_clean_tap(press_id, pressed_at) {
+                    self.toggle_layer(&layer);
+                }
+                ProcessResult::None
+            }
+            KeyAction::OneShotLayer {
+                layer,
+                press_id,
+                pressed_at,
+            } => {
+                self.release_held_layer(keycode);
+                if self.is_clean_tap(press_id, pressed_at) {
+                    self.push_layer(&layer, LayerActivation::OneShot(self.clock.now()));
+                }
+                ProcessResult::None
+            }
+            KeyAction::MouseManaged(mouse_action) => {
+                ProcessResult::from_mouse_events(self.mouse_processor.on_release(mouse_action))
+            }
+            KeyAction::MtManaged => {
+                // Let MT processor handle the release
+                match self.mt_processor.on_release(keycode) {
+                    Some(resolution) => self.apply_mt_resolution(resolution),
+                    None => ProcessResult::None,
+                }
+            }
+            KeyAction::SocdManaged => {
+                // Apply SOCD release logic
+                self.apply_socd_to_key_release(keycode)
+            }
+            KeyAction::DtManaged => {
+                // Let DT processor handle the release
+                // A held first/second action was recorded after this and releases itself
+                let resolution = self.dt_processor.on_release(keycode);
+                self.apply_dt_resolution(keycode, resolution)
+            }
+            KeyAction::TdManaged => {
+                // Let DT processor handle the release (tap on the last step)
+                // A held step action was recorded after this and releases itself
+                let resolution = self.dt_processor.on_dance_release(keycode);
+                self.apply_dance_resolution(keycode, resolution)
+            }
+            KeyAction::OsmManaged => {
+                // Let OSM processor handle the release
+                match self.osm_processor.on_release(keycode) {
+                    OsmResolution::ActivateModifier(mod_key) => {
+                        // Tapped - activate one-shot
+                        ProcessResult::EmitKey(mod_key, true)
+                    }
+                    OsmResolution::ReleaseModifier(mod_key) => {
+                        // Held - release normal modifier
+                        ProcessResult::EmitKey(mod_key, false)
+                    }
+                    OsmResolution::None => ProcessResult::None,
+                }
+            }
         }
     }
 
@@ -649,119 +1304,553 @@ impl KeymapProcessor {
         // Check game mode first if active
         if self.game_mode_active {
             if let Some(action) = self.game_mode_remaps.get(&keycode) {
-                return Some(action.clone());
+                if *action != ConfigAction::TRNS {
+                    return Some(action.clone());
+                }
             }
         }
 
-        // Check current layer next (if not base)
-        if !self.current_layer.is_base() {
-            if let Some(layer_remaps) = self.layers.get(&self.current_layer) {
-                if let Some(action) = layer_remaps.get(&keycode) {
-                    return Some(action.clone());
+        // Walk conditional layers, the layer stack from the top, then the default layer
+        let active_layers = self
+            .conditional_active
+            .iter()
+            .rev()
+            .chain(self.layer_stack.iter().rev().map(|entry| &entry.layer))
+            .chain(std::iter::once(&self.default_layer));
+        for layer in active_layers {
+            // Transparent keys fall through to the next layer down
+            match self.layer_remaps(layer).and_then(|r| r.get(&keycode)) {
+                Some(ConfigAction::TRNS) | None => {}
+                Some(action) => return Some(action.clone()),
+            }
+        }
+
+        // Fall back to base layer (transparent there means pass through)
+        self.base_remaps
+            .get(&keycode)
+            .filter(|action| **action != ConfigAction::TRNS)
+            .cloned()
+    }
+
+    /// Remaps of a layer (`base` refers to the top-level remaps)
+    fn layer_remaps(&self, layer: &Layer) -> Option<&HashMap<KeyCode, ConfigAction>> {
+        if layer.is_base() {
+            Some(&self.base_remaps)
+        } else {
+            self.layers.get(layer)
+        }
+    }
+
+    // === Layer Stack Helpers ===
+
+    /// Whether an action only changes layers (doesn't consume one-shots)
+    fn is_layer_action(action: &ConfigAction) -> bool {
+        matches!(
+            action,
+            ConfigAction::TO(_)
+                | ConfigAction::MO(_)
+                | ConfigAction::TG(_)
+                | ConfigAction::TT(_)
+                | ConfigAction::OSL(_)
+                | ConfigAction::DF(_)
+        )
+    }
+
+    /// Push a layer onto the top of the layer stack
+    fn push_layer(&mut self, layer: &Layer, activation: LayerActivation) {
+        self.layer_stack.push(ActiveLayer {
+            layer: layer.clone(),
+            activation,
+        });
+        self.update_conditional_layers();
+    }
+
+    /// Toggle a layer on or off
+    fn toggle_layer(&mut self, layer: &Layer) {
+        let toggled = self.layer_stack.iter().position(|entry| {
+            &entry.layer == layer && entry.activation == LayerActivation::Toggled
+        });
+        match toggled {
+            Some(index) => {
+                self.layer_stack.remove(index);
+                self.update_conditional_layers();
+            }
+            None => self.push_layer(layer, LayerActivation::Toggled),
+        }
+    }
+
+    /// Remove the layer held by a physical key (other activations stay)
+    fn release_held_layer(&mut self, keycode: KeyCode) {
+        self.layer_stack
+            .retain(|entry| entry.activation != LayerActivation::Held(keycode));
+        self.update_conditional_layers();
+    }
+
+    /// Recompute conditional layers after the layer state changed
+    /// Conditions may depend on other conditional layers (validation rules out cycles)
+    fn update_conditional_layers(&mut self) {
+        self.conditional_active.clear();
+        if self.conditional_layers.is_empty() {
+            return;
+        }
+
+        let mut active = self.active_layers();
+        loop {
+            let newly_active: Vec<Layer> = self
+                .conditional_layers
+                .iter()
+                .filter(|conditional| !active.contains(&conditional.then))
+                .filter(|conditional| conditional.when.iter().all(|l| active.contains(l)))
+                .map(|conditional| conditional.then.clone())
+                .collect();
+            if newly_active.is_empty() {
+                break;
+            }
+            for layer in newly_active {
+                if !active.contains(&layer) {
+                    active.push(layer.clone());
+                    self.conditional_active.push(layer);
                 }
             }
         }
+    }
 
-        // Fall back to base layer
-        self.base_remaps.get(&keycode).cloned()
+    /// Whether a TT/OSL key was tapped without any other key pressed meanwhile
+    fn is_clean_tap(&self, press_id: u64, pressed_at: Instant) -> bool {
+        press_id == self.press_counter
+            && self.clock.elapsed(pressed_at).as_millis() < u128::from(self.tapping_term_ms)
     }
 
-    /// Apply MT resolutions and return events to emit
-    fn apply_mt_resolutions(&mut self, resolutions: Vec<MtResolution>) -> Vec<(KeyCode, bool)> {
-        let mut events = Vec::new();
+    /// Tap the neutralizer key if this MT key is about to release a lone Alt/GUI hold
+    /// pressed at the tapping term (the tap follows right after)
+    fn neutralize_lone_hold(&self, keycode: KeyCode) -> ProcessResult {
+        if !self.mt_processor.releases_hold_for_tap(keycode)
+            || self.output_mods.contains(&MOD_NEUTRALIZER_KEY)
+        {
+            return ProcessResult::None;
+        }
+
+        let holds_alt_or_gui = self.held_keys.get(&keycode).is_some_and(|actions| {
+            actions.iter().any(|action| match action {
+                KeyAction::RegularKey(key) | KeyAction::Modifier(key) => matches!(
+                    key.unsided_modifier(),
+                    Some(KeyCode::KC_LALT | KeyCode::KC_LGUI)
+                ),
+                _ => false,
+            })
+        });
+        if !holds_alt_or_gui {
+            return ProcessResult::None;
+        }
+
+        ProcessResult::MultipleEvents(vec![
+            (MOD_NEUTRALIZER_KEY, true),
+            (MOD_NEUTRALIZER_KEY, false),
+        ])
+    }
+
+    /// Apply MT resolutions in order
+    fn apply_mt_resolutions(&mut self, resolutions: Vec<MtResolution>) -> ProcessResult {
+        let mut result = ProcessResult::None;
 
         for resolution in resolutions {
-            events.extend(self.mt_resolution_to_events(&resolution));
+            let applied = self.apply_mt_resolution(resolution);
+            result = result.then(applied);
         }
 
-        events
+        result
     }
 
-    /// Convert a single MT resolution to events
-    fn mt_resolution_to_events(&self, resolution: &MtResolution) -> Vec<(KeyCode, bool)> {
+    /// Apply a single MT resolution by running the resolved side through the action pipeline
+    fn apply_mt_resolution(&mut self, resolution: MtResolution) -> ProcessResult {
+        let keycode = resolution.keycode;
+        match resolution.action {
+            MtAction::TapPress(_) | MtAction::TapPressRelease(_) => {
+                self.record_mt_decision(keycode, false)
+            }
+            MtAction::HoldPress(_) | MtAction::HoldPressRelease(_) => {
+                self.record_mt_decision(keycode, true)
+            }
+            MtAction::ReleaseHold(_) => {}
+        }
         match resolution.action {
-            MtAction::TapPress(key) => vec![(key, true)],
-            MtAction::TapPressRelease(key) => vec![(key, true), (key, false)],
-            MtAction::HoldPress(key) => vec![(key, true)],
-            MtAction::HoldPressRelease(key) => vec![(key, true), (key, false)],
-            MtAction::ReleaseHold(key) => vec![(key, false)],
+            MtAction::TapPress(action) | MtAction::HoldPress(action) => {
+                if matches!(action, ConfigAction::MT(..)) {
+                    warn!("MT nested inside MT is not supported");
+                    return ProcessResult::None;
+                }
+                self.press_action(keycode, &action)
+            }
+            MtAction::TapPressRelease(action) | MtAction::HoldPressRelease(action) => {
+                if matches!(action, ConfigAction::MT(..)) {
+                    warn!("MT nested inside MT is not supported");
+                    return ProcessResult::None;
+                }
+                self.tap_action(keycode, &action)
+            }
+            MtAction::ReleaseHold(_action) => {
+                // The pressed side recorded itself in `held_keys` right after
+                // `MtManaged`, so it is released when those actions are replayed
+                ProcessResult::None
+            }
         }
     }
 
-    /// Apply single MT resolution
-    fn apply_mt_resolution_single(&self, resolution: MtResolution) -> ProcessResult {
-        match resolution.action {
-            MtAction::TapPress(key) => ProcessResult::EmitKey(key, true),
-            MtAction::TapPressRelease(key) => ProcessResult::TapKeyPressRelease(key),
-            MtAction::HoldPress(key) => ProcessResult::EmitKey(key, true),
-            MtAction::HoldPressRelease(key) => {
-                ProcessResult::MultipleEvents(vec![(key, true), (key, false)])
+    /// Record an MT decision - the other decision for the same key right after
+    /// Backspace means the previous one misfired
+    fn record_mt_decision(&mut self, keycode: KeyCode, hold: bool) {
+        if let Some(last) = self.last_mt_decision {
+            let corrected = last
+                .corrected_at
+                .is_some_and(|at| self.clock.elapsed(at).as_millis() < MISFIRE_WINDOW_MS);
+            if corrected && last.keycode == keycode && last.hold != hold {
+                self.mt_processor.record_misfire(keycode, last.hold);
+                if let Some(logged) = self.mt_decision_log.as_mut().and_then(|log| log.last_mut()) {
+                    logged.misfired = true;
+                }
             }
-            MtAction::ReleaseHold(key) => ProcessResult::EmitKey(key, false),
         }
+
+        if let Some(log) = &mut self.mt_decision_log {
+            log.push(MtDecisionLog {
+                keycode,
+                pressed_at: self
+                    .key_press_times
+                    .get(&keycode)
+                    .copied()
+                    .unwrap_or_else(|| self.clock.now()),
+                hold,
+                misfired: false,
+            });
+        }
+
+        self.last_mt_decision = Some(MtDecision {
+            keycode,
+            hold,
+            decided_at: self.clock.now(),
+            corrected_at: None,
+        });
     }
 
-    // === DT Helpers ===
+    /// Watch key presses after an MT decision: Backspace marks it as corrected,
+    /// typing anything but the MT key again ends the correction
+    fn watch_misfire_correction(&mut self, keycode: KeyCode, action: &ConfigAction) {
+        let Some(last) = &mut self.last_mt_decision else {
+            return;
+        };
 
-    /// Process DT timeout resolutions and return events to emit
-    fn process_dt_timeouts(&self, timeouts: Vec<(KeyCode, DtResolution)>) -> Vec<(KeyCode, bool)> {
-        let mut events = Vec::new();
+        if *action == ConfigAction::Key(KeyCode::KC_BSPC) {
+            if self.clock.elapsed(last.decided_at).as_millis() < MISFIRE_WINDOW_MS {
+                last.corrected_at = Some(self.clock.now());
+            }
+        } else if keycode != last.keycode && !self.held_keys.contains_key(&last.keycode) {
+            last.corrected_at = None;
+        }
+    }
 
-        for (_keycode, resolution) in timeouts {
-            match resolution {
-                DtResolution::HoldFirst(key) => {
-                    // Start holding first action
-                    events.push((key, true));
-                }
-                DtResolution::TapFirst(key) => {
-                    // Emit single-tap
-                    events.push((key, true));
-                    events.push((key, false));
-                }
-                _ => {
-                    // Other resolutions shouldn't come from check_timeouts
+    // === Key Override Helpers ===
+
+    /// Update the output modifier state and the last output key from a result
+    /// that is about to be emitted
+    /// Every result leaving the processor passes through here exactly once
+    fn track_output(&mut self, result: ProcessResult) -> ProcessResult {
+        // Typed strings and commands don't count
+        for (key, pressed) in result.all_key_events() {
+            if key.is_modifier() {
+                if pressed {
+                    self.output_mods.insert(key);
+                } else {
+                    self.output_mods.remove(&key);
                 }
+            } else if pressed {
+                let mods = self.output_mods.iter().copied().collect();
+                self.last_output = Some((key, mods));
             }
         }
+        result
+    }
 
-        events
+    /// Find a key override for an action given the current output modifiers
+    /// Returns the replacement and the held modifiers to suppress
+    fn find_key_override(&self, action: &ConfigAction) -> Option<(ConfigAction, Vec<KeyCode>)> {
+        let ConfigAction::Key(key) = action else {
+            return None;
+        };
+        if self.key_overrides.is_empty() {
+            return None;
+        }
+
+        let active_layers = self.active_layers();
+        self.key_overrides
+            .iter()
+            .filter(|key_override| key_override.key == *key)
+            .filter(|key_override| match &key_override.layers {
+                Some(layers) => layers.iter().any(|l| active_layers.contains(l)),
+                None => true,
+            })
+            .find_map(|key_override| {
+                // Every trigger modifier must be held (on either side)
+                let mut suppressed = Vec::new();
+                for trigger in &key_override.mods {
+                    let held: Vec<KeyCode> = self
+                        .output_mods
+                        .iter()
+                        .copied()
+                        .filter(|held| held.unsided_modifier() == trigger.unsided_modifier())
+                        // Caps Word shifting doesn't count as a held Shift
+                        .filter(|held| !(self.caps_word_shift && *held == KeyCode::KC_LSFT))
+                        .collect();
+                    if held.is_empty() {
+                        return None;
+                    }
+                    suppressed.extend(held);
+                }
+                Some((key_override.replacement.clone(), suppressed))
+            })
     }
 
-    /// Combine DT timeout events with a ProcessResult
-    fn combine_with_timeouts(
-        &self,
-        timeout_events: Vec<(KeyCode, bool)>,
-        result: ProcessResult,
+    /// Release the suppressed modifiers, then press the replacement
+    /// The modifiers come back on release if their keys are still held
+    fn press_key_override(
+        &mut self,
+        keycode: KeyCode,
+        replacement: &ConfigAction,
+        suppressed: &[KeyCode],
     ) -> ProcessResult {
-        if timeout_events.is_empty() {
-            return result;
+        let mut result = ProcessResult::None;
+        for &modifier in suppressed {
+            self.record_held(keycode, KeyAction::SuppressedModifier(modifier));
+            result = result.then(ProcessResult::EmitKey(modifier, false));
         }
+        let replacement_result = self.press_action(keycode, replacement);
+        result.then(replacement_result)
+    }
 
-        match result {
-            ProcessResult::None => {
-                if timeout_events.is_empty() {
-                    ProcessResult::None
-                } else {
-                    ProcessResult::MultipleEvents(timeout_events)
-                }
+    /// Whether any held key still emits this modifier
+    fn is_modifier_held(&self, modifier: KeyCode) -> bool {
+        self.held_keys.values().flatten().any(|action| {
+            matches!(action, KeyAction::RegularKey(key) | KeyAction::Modifier(key) if *key == modifier)
+        })
+    }
+
+    // === Repeat Key Helpers ===
+
+    /// Press a key with modifiers for REPEAT/ALT_REPEAT (released with the physical key)
+    /// Modifiers that are already held aren't pressed again
+    fn press_repeat(&mut self, keycode: KeyCode, key: KeyCode, mods: &[KeyCode]) -> ProcessResult {
+        let missing_mods: Vec<KeyCode> = mods
+            .iter()
+            .copied()
+            .filter(|modifier| !self.output_mods.contains(modifier))
+            .collect();
+        let action = ConfigAction::Key(key).with_modifiers(&missing_mods);
+        self.press_action(keycode, &action)
+    }
+
+    /// Alternate of a key sent with these modifiers (first matching alt_repeat_keys entry)
+    fn alt_repeat_key(&self, key: KeyCode, mods: &[KeyCode]) -> Option<KeyCode> {
+        self.alt_repeat_keys
+            .iter()
+            .find(|entry| {
+                entry.key == key
+                    && entry.mods.iter().all(|wanted| {
+                        mods.iter()
+                            .any(|held| held.unsided_modifier() == wanted.unsided_modifier())
+                    })
+            })
+            .map(|entry| entry.alt)
+    }
+
+    // === Auto-Shift Helpers ===
+
+    /// Output key of an action that auto-shifts, if any
+    /// Skipped in game mode and while modifiers are held (shortcuts aren't delayed)
+    fn auto_shift_key(&self, action: &ConfigAction) -> Option<KeyCode> {
+        let ConfigAction::Key(output_key) = action else {
+            return None;
+        };
+        if self.game_mode_active || !self.output_mods.is_empty() {
+            return None;
+        }
+        self.auto_shift_groups
+            .iter()
+            .any(|group| group.contains(*output_key))
+            .then_some(*output_key)
+    }
+
+    /// Type pending auto-shift keys held past the timeout, shifted
+    /// Uses the physical press times from `key_press_times`
+    fn check_auto_shift_timeouts(&mut self) -> ProcessResult {
+        if self.auto_shift_pending.is_empty() {
+            return ProcessResult::None;
+        }
+
+        let timeout_ms = u128::from(self.auto_shift_timeout_ms);
+        let expired: Vec<(KeyCode, KeyCode)> = self
+            .auto_shift_pending
+            .iter()
+            .filter(|(keycode, _)| {
+                self.key_press_times.get(keycode).is_some_and(|pressed_at| {
+                    self.clock.elapsed(*pressed_at).as_millis() >= timeout_ms
+                })
+            })
+            .map(|(&keycode, &output_key)| (keycode, output_key))
+            .collect();
+
+        let mut result = ProcessResult::None;
+        for (keycode, output_key) in expired {
+            self.auto_shift_pending.remove(&keycode);
+            let shifted = ConfigAction::LSFT(Box::new(ConfigAction::Key(output_key)));
+            let tapped = self.tap_action(keycode, &shifted);
+            result = result.then(tapped);
+        }
+        result
+    }
+
+    /// Press pending auto-shift keys unshifted (another key was pressed)
+    /// They stay pressed until their physical key is released
+    fn interrupt_auto_shift(&mut self) -> ProcessResult {
+        let mut result = ProcessResult::None;
+        let pending: Vec<(KeyCode, KeyCode)> = self.auto_shift_pending.drain().collect();
+        for (keycode, output_key) in pending {
+            let pressed = self.press_action(keycode, &ConfigAction::Key(output_key));
+            result = result.then(pressed);
+        }
+        result
+    }
+
+    // === Caps Word Helpers ===
+
+    /// Shift or unshift the output for a key about to be emitted while Caps Word is on
+    fn apply_caps_word(&mut self, output_key: KeyCode) -> ProcessResult {
+        let other_mods_held = self
+            .output_mods
+            .iter()
+            .any(|m| m.unsided_modifier() != Some(KeyCode::KC_LSFT));
+
+        match self.caps_word_processor.on_key(output_key, other_mods_held) {
+            CapsWordKey::Ignored => ProcessResult::None,
+            CapsWordKey::Shifted => self.set_caps_word_shift(true),
+            CapsWordKey::Unshifted | CapsWordKey::Break => self.set_caps_word_shift(false),
+        }
+    }
+
+    /// Press or release the Caps Word Shift
+    /// A Shift held by something else (a Shift key, a one-shot Shift) is left alone
+    fn set_caps_word_shift(&mut self, shift: bool) -> ProcessResult {
+        if shift == self.caps_word_shift {
+            return ProcessResult::None;
+        }
+        self.caps_word_shift = shift;
+
+        let shift_held_elsewhere = if shift {
+            self.output_mods
+                .iter()
+                .any(|m| m.unsided_modifier() == Some(KeyCode::KC_LSFT))
+        } else {
+            self.is_modifier_held(KeyCode::KC_LSFT)
+        };
+        if shift_held_elsewhere {
+            // The other Shift stays as it is
+            self.caps_word_shift = false;
+            return ProcessResult::None;
+        }
+
+        ProcessResult::EmitKey(KeyCode::KC_LSFT, shift)
+    }
+
+    // === OSM Helpers ===
+
+    /// Notify OSM processor of another key press
+    /// Returns modifier presses for OSM keys that are being held as normal modifiers
+    fn apply_osm_other_key_press(&mut self, output_key: KeyCode) -> ProcessResult {
+        let events = self
+            .osm_processor
+            .on_other_key_press(output_key)
+            .into_iter()
+            .filter_map(|(_, resolution)| match resolution {
+                OsmResolution::ActivateModifier(mod_key) => Some((mod_key, true)),
+                _ => None,
+            })
+            .collect();
+        ProcessResult::from_events(events)
+    }
+
+    /// Notify OSM processor of another key release
+    /// Returns releases for one-shot modifiers consumed by that key
+    fn apply_osm_other_key_release(&mut self, keycode: KeyCode) -> ProcessResult {
+        let events = self
+            .osm_processor
+            .on_other_key_release(keycode)
+            .into_iter()
+            .filter_map(|(_, resolution)| match resolution {
+                OsmResolution::ReleaseModifier(mod_key) => Some((mod_key, false)),
+                _ => None,
+            })
+            .collect();
+        ProcessResult::from_events(events)
+    }
+
+    // === DT Helpers ===
+
+    /// Apply DT timeout (or interrupt) resolutions and return events to emit
+    fn process_dt_timeouts(&mut self, timeouts: Vec<(KeyCode, DtResolution)>) -> ProcessResult {
+        let mut result = ProcessResult::None;
+
+        for (keycode, resolution) in timeouts {
+            let applied = self.apply_dt_resolution(keycode, resolution);
+            result = result.then(applied);
+        }
+
+        result
+    }
+
+    /// Apply a DT resolution by running the resolved action through the action pipeline
+    fn apply_dt_resolution(&mut self, keycode: KeyCode, resolution: DtResolution) -> ProcessResult {
+        match resolution {
+            DtResolution::HoldFirst(action) | DtResolution::PressSecond(action) => {
+                // Held beyond tapping term, or double-tap detected - press and keep held
+                self.press_action(keycode, &action)
             }
-            ProcessResult::EmitKey(key, pressed) => {
-                let mut all_events = timeout_events;
-                all_events.push((key, pressed));
-                ProcessResult::MultipleEvents(all_events)
+            DtResolution::TapFirst(action) => {
+                // Single-tap timeout - emit first action as tap
+                self.tap_action(keycode, &action)
             }
-            ProcessResult::TapKeyPressRelease(key) => {
-                let mut all_events = timeout_events;
-                all_events.push((key, true));
-                all_events.push((key, false));
-                ProcessResult::MultipleEvents(all_events)
+            DtResolution::ReleaseFirst(_) | DtResolution::ReleaseSecond(_) => {
+                // The pressed action is recorded in `held_keys` and releases itself
+                ProcessResult::None
             }
-            ProcessResult::MultipleEvents(mut events) => {
-                let mut all_events = timeout_events;
-                all_events.append(&mut events);
-                ProcessResult::MultipleEvents(all_events)
+            DtResolution::DanceTap(_) | DtResolution::DanceHold(_) => {
+                self.apply_dance_resolution(keycode, resolution)
             }
-            other => other, // Pass through TypeString etc unchanged
+            DtResolution::Undecided => ProcessResult::None,
+        }
+    }
+
+    /// Run the tap or hold action of the tap dance step a TD key resolved to
+    fn apply_dance_resolution(
+        &mut self,
+        keycode: KeyCode,
+        resolution: DtResolution,
+    ) -> ProcessResult {
+        let Some(steps) = self.dance_steps.get(&keycode) else {
+            return ProcessResult::None;
+        };
+
+        match resolution {
+            DtResolution::DanceTap(step) => {
+                let action = steps[step].tap.clone();
+                self.tap_action(keycode, &action)
+            }
+            DtResolution::DanceHold(step) => {
+                // Steps without a hold action hold their tap action instead
+                let action = steps[step]
+                    .hold
+                    .clone()
+                    .unwrap_or_else(|| steps[step].tap.clone());
+                self.press_action(keycode, &action)
+            }
+            _ => ProcessResult::None,
         }
     }
 
@@ -833,18 +1922,88 @@ impl KeymapProcessor {
 pub enum ProcessResult {
     /// Emit a single key event
     EmitKey(KeyCode, bool),
-    /// Emit a tap (press + release)
-    TapKeyPressRelease(KeyCode),
+    /// Emit a key repeat (evdev value 2) for a held output key
+    RepeatKey(KeyCode),
     /// Emit multiple events in sequence
     MultipleEvents(Vec<(KeyCode, bool)>),
     /// Type a string
     TypeString(String, bool),
     /// Run a shell command
     RunCommand(String),
+    /// Emit mouse events (buttons, movement, wheel)
+    Mouse(Vec<MouseEvent>),
+    /// Emit several results in order (when not all of them are key events)
+    Batch(Vec<ProcessResult>),
     /// Don't emit anything
     None,
 }
 
+impl ProcessResult {
+    /// Build a result from a list of key events
+    #[must_use]
+    pub fn from_events(events: Vec<(KeyCode, bool)>) -> Self {
+        match events.len() {
+            0 => Self::None,
+            1 => Self::EmitKey(events[0].0, events[0].1),
+            _ => Self::MultipleEvents(events),
+        }
+    }
+
+    /// Build a result from a list of mouse events
+    #[must_use]
+    pub fn from_mouse_events(events: Vec<MouseEvent>) -> Self {
+        if events.is_empty() {
+            Self::None
+        } else {
+            Self::Mouse(events)
+        }
+    }
+
+    /// Key events of this result, if it consists only of key events
+    fn key_events(&self) -> Option<Vec<(KeyCode, bool)>> {
+        match self {
+            Self::EmitKey(key, pressed) => Some(vec![(*key, *pressed)]),
+            Self::MultipleEvents(events) => Some(events.clone()),
+            Self::None => Some(Vec::new()),
+            _ => None,
+        }
+    }
+
+    /// All key events of this result, including those of nested results
+    fn all_key_events(&self) -> Vec<(KeyCode, bool)> {
+        match self {
+            Self::Batch(results) => results.iter().flat_map(Self::all_key_events).collect(),
+            other => other.key_events().unwrap_or_default(),
+        }
+    }
+
+    /// Append another result to be emitted after this one
+    #[must_use]
+    pub fn then(self, next: Self) -> Self {
+        match (self, next) {
+            (Self::None, next) => next,
+            (this, Self::None) => this,
+            (this, next) => match (this.key_events(), next.key_events()) {
+                (Some(mut events), Some(next_events)) => {
+                    events.extend(next_events);
+                    Self::MultipleEvents(events)
+                }
+                _ => {
+                    let mut results = match this {
+                        Self::Batch(results) => results,
+                        other => vec![other],
+                    };
+                    match next {
+                        Self::Batch(next_results) => results.extend(next_results),
+                        other => results.push(other),
+                    }
+                    Self::Batch(results)
+                }
+            },
+        }
+    }
+}
+
 // === evdev ↔ KeyCode Conversion ===
 
 #[must_use]
@@ -1206,3 +2365,838 @@ pub const fn keycode_to_evdev(keycode: KeyCode) -> Key {
         KeyCode::KC_INTL_RO => Key::KEY_RO,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_processor::actions::MouseButton;
+
+    /// Keymap processor on a manual clock
+    struct Keymap {
+        processor: KeymapProcessor,
+        clock: Clock,
+    }
+
+    impl Keymap {
+        fn new(config: &str) -> Self {
+            let config = Config::parse(config).unwrap();
+            let clock = Clock::manual(Instant::now());
+            Self {
+                processor: KeymapProcessor::with_clock(&config, clock.clone()),
+                clock,
+            }
+        }
+
+        fn press(&mut self, keycode: KeyCode) -> ProcessResult {
+            self.processor.process_key(keycode, true)
+        }
+
+        fn release(&mut self, keycode: KeyCode) -> ProcessResult {
+            self.processor.process_key(keycode, false)
+        }
+
+        /// Press and release, returning the key events of both
+        fn tap(&mut self, keycode: KeyCode) -> Vec<(KeyCode, bool)> {
+            let pressed = self.press(keycode);
+            let released = self.release(keycode);
+            pressed.then(released).all_key_events()
+        }
+
+        /// Let `ms` pass, checking timeouts every millisecond like the event loop
+        fn wait(&mut self, ms: u64) -> ProcessResult {
+            let mut result = ProcessResult::None;
+            for _ in 0..ms {
+                self.clock.advance(Duration::from_millis(1));
+                let checked = self.processor.check_timeouts();
+                result = result.then(checked);
+            }
+            result
+        }
+    }
+
+    fn down(keycode: KeyCode) -> (KeyCode, bool) {
+        (keycode, true)
+    }
+
+    fn up(keycode: KeyCode) -> (KeyCode, bool) {
+        (keycode, false)
+    }
+
+    #[test]
+    fn test_kc_no_is_a_no_op() {
+        let mut keymap = Keymap::new(
+            "(remaps: { KC_CAPS: OSM(KC_LSFT), KC_B: KC_NO, KC_F: MT(KC_F, KC_LCTL) })",
+        );
+
+        // Doesn't consume an active one-shot modifier
+        assert_eq!(keymap.tap(KeyCode::KC_CAPS), vec![down(KeyCode::KC_LSFT)]);
+        assert!(keymap.tap(KeyCode::KC_B).is_empty());
+        assert_eq!(
+            keymap.tap(KeyCode::KC_A),
+            vec![down(KeyCode::KC_A), up(KeyCode::KC_A), up(KeyCode::KC_LSFT)]
+        );
+
+        // Doesn't decide a pending MT key (permissive hold would make it a hold)
+        assert_eq!(keymap.press(KeyCode::KC_F), ProcessResult::None);
+        assert!(keymap.tap(KeyCode::KC_B).is_empty());
+        assert_eq!(
+            keymap.release(KeyCode::KC_F).all_key_events(),
+            vec![down(KeyCode::KC_F), up(KeyCode::KC_F)]
+        );
+    }
+
+    #[test]
+    fn test_combo() {
+        let mut keymap = Keymap::new("(combos: [(keys: [KC_J, KC_K], action: KC_ESC)])");
+
+        // Both keys within the combo term → the combo action
+        assert_eq!(keymap.press(KeyCode::KC_J), ProcessResult::None);
+        assert_eq!(
+            keymap.press(KeyCode::KC_K),
+            ProcessResult::EmitKey(KeyCode::KC_ESC, true)
+        );
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_K),
+            ProcessResult::RepeatKey(KeyCode::KC_ESC)
+        );
+
+        // Releasing any key of the combo releases it, the other release is swallowed
+        assert_eq!(
+            keymap.release(KeyCode::KC_K),
+            ProcessResult::EmitKey(KeyCode::KC_ESC, false)
+        );
+        assert_eq!(keymap.release(KeyCode::KC_J), ProcessResult::None);
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_K),
+            ProcessResult::None
+        );
+
+        // Combo term expires → the buffered key is pressed on its own
+        assert_eq!(keymap.press(KeyCode::KC_J), ProcessResult::None);
+        assert_eq!(keymap.wait(50), ProcessResult::None);
+        assert_eq!(keymap.wait(1), ProcessResult::EmitKey(KeyCode::KC_J, true));
+        assert_eq!(
+            keymap.tap(KeyCode::KC_K),
+            vec![down(KeyCode::KC_K), up(KeyCode::KC_K)]
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_J),
+            ProcessResult::EmitKey(KeyCode::KC_J, false)
+        );
+    }
+
+    #[test]
+    fn test_tap_dance() {
+        let mut keymap = Keymap::new(
+            "(remaps: { KC_LBRC: TD([(tap: KC_LBRC), (tap: KC_RBRC, hold: Some(KC_LCTL))]) })",
+        );
+        let tapped = |keycode| vec![down(keycode), up(keycode)];
+
+        // One tap → the first step once the window after the press expires
+        assert!(keymap.tap(KeyCode::KC_LBRC).is_empty());
+        assert_eq!(keymap.wait(250), ProcessResult::None);
+        assert_eq!(keymap.wait(1).all_key_events(), tapped(KeyCode::KC_LBRC));
+
+        // Two taps → the last step, right on release
+        assert!(keymap.tap(KeyCode::KC_LBRC).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_LBRC), tapped(KeyCode::KC_RBRC));
+
+        // Tap, then hold past the tapping term → the hold action of the second step
+        assert!(keymap.tap(KeyCode::KC_LBRC).is_empty());
+        assert_eq!(keymap.press(KeyCode::KC_LBRC), ProcessResult::None);
+        assert_eq!(keymap.wait(130), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(1),
+            ProcessResult::EmitKey(KeyCode::KC_LCTL, true)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_LBRC),
+            ProcessResult::EmitKey(KeyCode::KC_LCTL, false)
+        );
+
+        // Another key ends the dance right away
+        assert!(keymap.tap(KeyCode::KC_LBRC).is_empty());
+        assert_eq!(
+            keymap.press(KeyCode::KC_A).all_key_events(),
+            vec![
+                down(KeyCode::KC_LBRC),
+                up(KeyCode::KC_LBRC),
+                down(KeyCode::KC_A)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layer_stack() {
+        let mut keymap = Keymap::new(
+            r#"(
+                oneshot_timeout_ms: Some(500),
+                remaps: {
+                    KC_Q: MO("nav"), KC_W: MO("nav"), KC_U: MO("num"), KC_E: TG("nav"),
+                    KC_R: TT("num"), KC_T: OSL("sym"), KC_Y: DF("dvorak"), KC_J: KC_ESC,
+                },
+                layers: {
+                    "nav": (remaps: { KC_H: KC_LEFT, KC_J: TRNS, KC_K: TRNS }),
+                    "num": (remaps: { KC_H: KC_1, KC_J: TRNS, KC_K: TRNS }),
+                    "sym": (remaps: { KC_H: KC_MINS }),
+                    "dvorak": (remaps: { KC_H: KC_D, KC_K: KC_T }),
+                },
+            )"#,
+        );
+        let tapped = |keycode| vec![down(keycode), up(keycode)];
+
+        // MO held by two keys: the layer stays until the last of them is released
+        assert_eq!(keymap.press(KeyCode::KC_Q), ProcessResult::None);
+        assert_eq!(keymap.press(KeyCode::KC_W), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_LEFT));
+        assert_eq!(keymap.release(KeyCode::KC_Q), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_LEFT));
+        assert_eq!(keymap.release(KeyCode::KC_W), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_H));
+        assert!(keymap.processor.layer_stack.is_empty());
+
+        // TG turns the layer on and back off
+        assert!(keymap.tap(KeyCode::KC_E).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_LEFT));
+        assert!(keymap.tap(KeyCode::KC_E).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_H));
+
+        // TT tapped toggles, a second tap toggles back off
+        assert!(keymap.tap(KeyCode::KC_R).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_1));
+        assert!(keymap.tap(KeyCode::KC_R).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_H));
+
+        // TT held while another key is pressed, or past the tapping term, is momentary
+        assert_eq!(keymap.press(KeyCode::KC_R), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_1));
+        assert_eq!(keymap.release(KeyCode::KC_R), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_H));
+        assert_eq!(keymap.press(KeyCode::KC_R), ProcessResult::None);
+        keymap.wait(200);
+        assert_eq!(keymap.release(KeyCode::KC_R), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_H));
+
+        // OSL applies to exactly one key
+        assert!(keymap.tap(KeyCode::KC_T).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_MINS));
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_H));
+
+        // ... unless it expires first
+        assert!(keymap.tap(KeyCode::KC_T).is_empty());
+        keymap.wait(500);
+        assert_eq!(keymap.processor.layer_stack.len(), 1);
+        keymap.wait(1);
+        assert!(keymap.processor.layer_stack.is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_H));
+
+        // DF swaps the default layer under the stack
+        assert!(keymap.tap(KeyCode::KC_Y).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_D));
+
+        // TRNS falls through num and nav to the default layer, then to the base remaps
+        assert_eq!(keymap.press(KeyCode::KC_Q), ProcessResult::None);
+        assert_eq!(keymap.press(KeyCode::KC_U), ProcessResult::None);
+        assert_eq!(keymap.processor.layer_stack.len(), 2);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_1));
+        assert_eq!(keymap.tap(KeyCode::KC_K), tapped(KeyCode::KC_T));
+        assert_eq!(keymap.tap(KeyCode::KC_J), tapped(KeyCode::KC_ESC));
+        assert_eq!(keymap.release(KeyCode::KC_U), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_LEFT));
+        assert_eq!(keymap.release(KeyCode::KC_Q), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_H), tapped(KeyCode::KC_D));
+    }
+
+    #[test]
+    fn test_macro_holds_back_keys() {
+        let mut keymap = Keymap::new(
+            "(remaps: { KC_M: SEQ([Down(KC_LCTL), Tap(KC_C), Delay(20), Up(KC_LCTL)]) })",
+        );
+
+        // Steps before the delay play right away
+        assert_eq!(
+            keymap.press(KeyCode::KC_M).all_key_events(),
+            vec![
+                down(KeyCode::KC_LCTL),
+                down(KeyCode::KC_C),
+                up(KeyCode::KC_C)
+            ]
+        );
+
+        // Keys pressed meanwhile are held back instead of interleaving with Ctrl held
+        assert_eq!(keymap.press(KeyCode::KC_A), ProcessResult::None);
+        assert_eq!(keymap.release(KeyCode::KC_A), ProcessResult::None);
+        assert_eq!(keymap.release(KeyCode::KC_M), ProcessResult::None);
+        assert_eq!(keymap.processor.deferred_events.len(), 3);
+        assert!(keymap.wait(19).all_key_events().is_empty());
+
+        // ... and processed right after the last step
+        assert_eq!(
+            keymap.wait(1).all_key_events(),
+            vec![up(KeyCode::KC_LCTL), down(KeyCode::KC_A), up(KeyCode::KC_A)]
+        );
+        assert!(keymap.processor.deferred_events.is_empty());
+        assert_eq!(
+            keymap.tap(KeyCode::KC_A),
+            vec![down(KeyCode::KC_A), up(KeyCode::KC_A)]
+        );
+    }
+
+    #[test]
+    fn test_leader() {
+        let mut keymap = Keymap::new(
+            "(remaps: { KC_CAPS: LEADER }, leader_sequences: [
+                (keys: [KC_G, KC_S], action: KC_ESC),
+                (keys: [KC_G], action: KC_TAB),
+            ])",
+        );
+        let tapped = |keycode| vec![down(keycode), up(keycode)];
+
+        // Full sequence → its action, tapped under the last key
+        assert!(keymap.tap(KeyCode::KC_CAPS).is_empty());
+        assert!(keymap.tap(KeyCode::KC_G).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_S), tapped(KeyCode::KC_ESC));
+
+        // Timeout → the captured keys run as the sequence they complete
+        assert!(keymap.tap(KeyCode::KC_CAPS).is_empty());
+        assert!(keymap.tap(KeyCode::KC_G).is_empty());
+        assert_eq!(keymap.wait(500), ProcessResult::None);
+        assert_eq!(keymap.wait(1).all_key_events(), tapped(KeyCode::KC_TAB));
+
+        // Keys that can't start a sequence are swallowed
+        assert!(keymap.tap(KeyCode::KC_CAPS).is_empty());
+        assert!(keymap.tap(KeyCode::KC_A).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_S), tapped(KeyCode::KC_S));
+
+        // Game mode cancels a running capture and keeps the leader key from starting one
+        assert!(keymap.tap(KeyCode::KC_CAPS).is_empty());
+        assert_eq!(keymap.processor.set_game_mode(true), ProcessResult::None);
+        assert_eq!(keymap.tap(KeyCode::KC_G), tapped(KeyCode::KC_G));
+        assert!(keymap.tap(KeyCode::KC_CAPS).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_G), tapped(KeyCode::KC_G));
+    }
+
+    #[test]
+    fn test_key_override() {
+        let mut keymap = Keymap::new(
+            r#"(
+                remaps: { KC_Q: MO("nav") },
+                layers: { "nav": (remaps: {}) },
+                key_overrides: [
+                    (mods: [KC_LSFT], key: KC_BSPC, replacement: KC_DEL),
+                    (mods: [KC_LSFT], key: KC_DOT, replacement: KC_SCLN, layers: Some(["nav"])),
+                ],
+            )"#,
+        );
+
+        // Shift is released before the replacement, and comes back on release while held
+        assert_eq!(
+            keymap.press(KeyCode::KC_LSFT),
+            ProcessResult::EmitKey(KeyCode::KC_LSFT, true)
+        );
+        assert_eq!(
+            keymap.press(KeyCode::KC_BSPC).all_key_events(),
+            vec![up(KeyCode::KC_LSFT), down(KeyCode::KC_DEL)]
+        );
+        assert!(keymap.processor.held_keys[&KeyCode::KC_BSPC]
+            .iter()
+            .any(|action| matches!(action, KeyAction::SuppressedModifier(KeyCode::KC_LSFT))));
+        assert_eq!(
+            keymap.release(KeyCode::KC_BSPC).all_key_events(),
+            vec![up(KeyCode::KC_DEL), down(KeyCode::KC_LSFT)]
+        );
+
+        // Shift let go first isn't restored
+        assert_eq!(
+            keymap.press(KeyCode::KC_BSPC).all_key_events(),
+            vec![up(KeyCode::KC_LSFT), down(KeyCode::KC_DEL)]
+        );
+        keymap.release(KeyCode::KC_LSFT);
+        assert_eq!(
+            keymap.release(KeyCode::KC_BSPC).all_key_events(),
+            vec![up(KeyCode::KC_DEL)]
+        );
+
+        // Either Shift triggers it, no Shift leaves the key alone
+        assert_eq!(
+            keymap.press(KeyCode::KC_RSFT),
+            ProcessResult::EmitKey(KeyCode::KC_RSFT, true)
+        );
+        assert_eq!(
+            keymap.press(KeyCode::KC_BSPC).all_key_events(),
+            vec![up(KeyCode::KC_RSFT), down(KeyCode::KC_DEL)]
+        );
+        keymap.release(KeyCode::KC_BSPC);
+        keymap.release(KeyCode::KC_RSFT);
+        assert_eq!(
+            keymap.tap(KeyCode::KC_BSPC),
+            vec![down(KeyCode::KC_BSPC), up(KeyCode::KC_BSPC)]
+        );
+
+        // Layer-scoped overrides only apply while one of their layers is active
+        keymap.press(KeyCode::KC_LSFT);
+        let dot = ConfigAction::Key(KeyCode::KC_DOT);
+        assert_eq!(keymap.processor.find_key_override(&dot), None);
+        assert_eq!(
+            keymap.tap(KeyCode::KC_DOT),
+            vec![down(KeyCode::KC_DOT), up(KeyCode::KC_DOT)]
+        );
+        assert_eq!(keymap.press(KeyCode::KC_Q), ProcessResult::None);
+        assert_eq!(
+            keymap.processor.find_key_override(&dot),
+            Some((ConfigAction::Key(KeyCode::KC_SCLN), vec![KeyCode::KC_LSFT]))
+        );
+        assert_eq!(
+            keymap.tap(KeyCode::KC_DOT),
+            vec![
+                up(KeyCode::KC_LSFT),
+                down(KeyCode::KC_SCLN),
+                up(KeyCode::KC_SCLN),
+                down(KeyCode::KC_LSFT)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_caps_word() {
+        let mut keymap = Keymap::new("(remaps: { KC_CAPS: CAPS_WORD })");
+        let shifted = |keycode| vec![down(KeyCode::KC_LSFT), down(keycode), up(keycode)];
+        let unshifted = |keycode| vec![up(KeyCode::KC_LSFT), down(keycode), up(keycode)];
+
+        // Letters are shifted, digits continue the word unshifted, space ends it
+        assert!(keymap.tap(KeyCode::KC_CAPS).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_A), shifted(KeyCode::KC_A));
+        assert_eq!(keymap.tap(KeyCode::KC_1), unshifted(KeyCode::KC_1));
+        assert_eq!(keymap.tap(KeyCode::KC_B), shifted(KeyCode::KC_B));
+        assert_eq!(keymap.tap(KeyCode::KC_SPC), unshifted(KeyCode::KC_SPC));
+        assert_eq!(
+            keymap.tap(KeyCode::KC_C),
+            vec![down(KeyCode::KC_C), up(KeyCode::KC_C)]
+        );
+
+        // Idle timeout turns it off and releases its Shift
+        assert!(keymap.tap(KeyCode::KC_CAPS).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_A), shifted(KeyCode::KC_A));
+        assert_eq!(keymap.wait(5000), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(1),
+            ProcessResult::EmitKey(KeyCode::KC_LSFT, false)
+        );
+
+        // Game mode turns it off and releases its Shift right away
+        assert!(keymap.tap(KeyCode::KC_CAPS).is_empty());
+        assert_eq!(keymap.tap(KeyCode::KC_A), shifted(KeyCode::KC_A));
+        assert_eq!(
+            keymap.processor.set_game_mode(true),
+            ProcessResult::EmitKey(KeyCode::KC_LSFT, false)
+        );
+        assert_eq!(
+            keymap.tap(KeyCode::KC_A),
+            vec![down(KeyCode::KC_A), up(KeyCode::KC_A)]
+        );
+    }
+
+    #[test]
+    fn test_auto_shift() {
+        let mut keymap = Keymap::new("(auto_shift_groups: [Letters])");
+
+        // Released before the timeout → typed unshifted on release
+        assert_eq!(keymap.press(KeyCode::KC_A), ProcessResult::None);
+        assert_eq!(keymap.wait(100), ProcessResult::None);
+        assert_eq!(
+            keymap.release(KeyCode::KC_A).all_key_events(),
+            vec![down(KeyCode::KC_A), up(KeyCode::KC_A)]
+        );
+
+        // Held past the timeout → typed shifted, the release does nothing
+        assert_eq!(keymap.press(KeyCode::KC_A), ProcessResult::None);
+        assert_eq!(keymap.wait(174), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(1).all_key_events(),
+            vec![
+                down(KeyCode::KC_LSFT),
+                down(KeyCode::KC_A),
+                up(KeyCode::KC_A),
+                up(KeyCode::KC_LSFT)
+            ]
+        );
+        assert_eq!(keymap.release(KeyCode::KC_A), ProcessResult::None);
+
+        // Another key press types the pending key unshifted
+        assert_eq!(keymap.press(KeyCode::KC_A), ProcessResult::None);
+        assert_eq!(
+            keymap.press(KeyCode::KC_B),
+            ProcessResult::EmitKey(KeyCode::KC_A, true)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_A),
+            ProcessResult::EmitKey(KeyCode::KC_A, false)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_B).all_key_events(),
+            vec![down(KeyCode::KC_B), up(KeyCode::KC_B)]
+        );
+
+        // Keys outside the groups pass through
+        assert_eq!(
+            keymap.tap(KeyCode::KC_1),
+            vec![down(KeyCode::KC_1), up(KeyCode::KC_1)]
+        );
+    }
+
+    #[test]
+    fn test_repeat() {
+        let mut keymap = Keymap::new("(remaps: { KC_F1: REPEAT, KC_F2: ALT_REPEAT })");
+        let tapped = |keycode| vec![down(keycode), up(keycode)];
+        let tapped_with_mods = |keycode| {
+            vec![
+                down(KeyCode::KC_LCTL),
+                down(KeyCode::KC_LSFT),
+                down(keycode),
+                up(keycode),
+                up(KeyCode::KC_LSFT),
+                up(KeyCode::KC_LCTL),
+            ]
+        };
+
+        // Nothing sent yet → nothing to repeat
+        assert!(keymap.tap(KeyCode::KC_F1).is_empty());
+
+        // Modifiers held with the last key are replayed in a fixed order, not press order
+        assert_eq!(
+            keymap.press(KeyCode::KC_LSFT),
+            ProcessResult::EmitKey(KeyCode::KC_LSFT, true)
+        );
+        assert_eq!(
+            keymap.press(KeyCode::KC_LCTL),
+            ProcessResult::EmitKey(KeyCode::KC_LCTL, true)
+        );
+        assert_eq!(keymap.tap(KeyCode::KC_Z), tapped(KeyCode::KC_Z));
+        keymap.release(KeyCode::KC_LSFT);
+        keymap.release(KeyCode::KC_LCTL);
+        assert_eq!(keymap.tap(KeyCode::KC_F1), tapped_with_mods(KeyCode::KC_Z));
+        assert_eq!(keymap.tap(KeyCode::KC_F2), tapped_with_mods(KeyCode::KC_Y));
+
+        // ALT_REPEAT sends the alternate, REPEAT then repeats what was sent last
+        assert_eq!(keymap.tap(KeyCode::KC_LEFT), tapped(KeyCode::KC_LEFT));
+        assert_eq!(keymap.tap(KeyCode::KC_F2), tapped(KeyCode::KC_RGHT));
+        assert_eq!(keymap.tap(KeyCode::KC_F1), tapped(KeyCode::KC_RGHT));
+    }
+
+    #[test]
+    fn test_mouse_keys() {
+        let mut keymap = Keymap::new(
+            "(remaps: { KC_H: MOUSE(MS_LEFT), KC_J: MOUSE(MS_WHLD), KC_F: MOUSE(MS_BTN1) })",
+        );
+
+        // A movement key steps right away, then every interval while held, speeding up
+        assert_eq!(
+            keymap.press(KeyCode::KC_H),
+            ProcessResult::Mouse(vec![MouseEvent::Move(-4, 0)])
+        );
+        assert_eq!(keymap.wait(15), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(1),
+            ProcessResult::Mouse(vec![MouseEvent::Move(-4, 0)])
+        );
+        assert_eq!(
+            keymap.wait(16),
+            ProcessResult::Mouse(vec![MouseEvent::Move(-5, 0)])
+        );
+
+        // Releasing it stops the movement
+        assert_eq!(keymap.release(KeyCode::KC_H), ProcessResult::None);
+        assert_eq!(keymap.wait(100), ProcessResult::None);
+
+        // Wheel keys step on their own interval
+        assert_eq!(
+            keymap.press(KeyCode::KC_J),
+            ProcessResult::Mouse(vec![MouseEvent::Wheel(-1, 0)])
+        );
+        assert_eq!(keymap.wait(79), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(1),
+            ProcessResult::Mouse(vec![MouseEvent::Wheel(-1, 0)])
+        );
+        assert_eq!(keymap.release(KeyCode::KC_J), ProcessResult::None);
+
+        // Buttons follow their key
+        assert_eq!(
+            keymap.press(KeyCode::KC_F),
+            ProcessResult::Mouse(vec![MouseEvent::Button(MouseButton::Left, true)])
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_F),
+            ProcessResult::Mouse(vec![MouseEvent::Button(MouseButton::Left, false)])
+        );
+    }
+
+    #[test]
+    fn test_mt_lone_hold() {
+        let mut keymap =
+            Keymap::new("(remaps: { KC_A: MT(KC_A, KC_LALT), KC_F: MT(KC_F, KC_LCTL) })");
+
+        // Held alone past the term: the hold goes down at the term and release only
+        // releases it (Alt+scroll, Shift+click don't end with a stray letter)
+        assert_eq!(keymap.press(KeyCode::KC_A), ProcessResult::None);
+        assert_eq!(keymap.wait(129), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(1),
+            ProcessResult::EmitKey(KeyCode::KC_LALT, true)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_A),
+            ProcessResult::EmitKey(KeyCode::KC_LALT, false)
+        );
+
+        // Retro tap: on release Alt is neutralized before it goes up, then the tap is sent
+        let mut keymap = Keymap::new(
+            "(
+                remaps: { KC_A: MT(KC_A, KC_LALT), KC_F: MT(KC_F, KC_LCTL) },
+                mt_config: (flavor: RetroTap),
+            )",
+        );
+        assert_eq!(keymap.press(KeyCode::KC_A), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(200),
+            ProcessResult::EmitKey(KeyCode::KC_LALT, true)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_A).all_key_events(),
+            vec![
+                down(KeyCode::KC_RCTL),
+                up(KeyCode::KC_RCTL),
+                up(KeyCode::KC_LALT),
+                down(KeyCode::KC_A),
+                up(KeyCode::KC_A)
+            ]
+        );
+
+        // A lone Ctrl does nothing by itself, it isn't neutralized
+        assert_eq!(keymap.press(KeyCode::KC_F), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(200),
+            ProcessResult::EmitKey(KeyCode::KC_LCTL, true)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_F).all_key_events(),
+            vec![up(KeyCode::KC_LCTL), down(KeyCode::KC_F), up(KeyCode::KC_F)]
+        );
+
+        // A hold used with another key is just released
+        assert_eq!(keymap.press(KeyCode::KC_A), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(200),
+            ProcessResult::EmitKey(KeyCode::KC_LALT, true)
+        );
+        assert_eq!(
+            keymap.tap(KeyCode::KC_J),
+            vec![down(KeyCode::KC_J), up(KeyCode::KC_J)]
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_A),
+            ProcessResult::EmitKey(KeyCode::KC_LALT, false)
+        );
+    }
+
+    #[test]
+    fn test_key_repeat() {
+        let mut keymap = Keymap::new(
+            r#"(
+                remaps: { KC_CAPS: KC_ESC, KC_SPC: MO("nav"), KC_F: MT(KC_F, KC_LCTL) },
+                layers: { "nav": (remaps: { KC_H: KC_LEFT }) },
+                mt_config: (double_tap_then_hold: true),
+            )"#,
+        );
+
+        // Remapped keys repeat their output while held
+        assert_eq!(
+            keymap.press(KeyCode::KC_CAPS),
+            ProcessResult::EmitKey(KeyCode::KC_ESC, true)
+        );
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_CAPS),
+            ProcessResult::RepeatKey(KeyCode::KC_ESC)
+        );
+        keymap.release(KeyCode::KC_CAPS);
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_CAPS),
+            ProcessResult::None
+        );
+
+        // Layer keys repeat what they send on the layer, the layer key itself doesn't
+        assert_eq!(keymap.press(KeyCode::KC_SPC), ProcessResult::None);
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_SPC),
+            ProcessResult::None
+        );
+        assert_eq!(
+            keymap.press(KeyCode::KC_H),
+            ProcessResult::EmitKey(KeyCode::KC_LEFT, true)
+        );
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_H),
+            ProcessResult::RepeatKey(KeyCode::KC_LEFT)
+        );
+        keymap.release(KeyCode::KC_H);
+        keymap.release(KeyCode::KC_SPC);
+
+        // Pending MT keys and MT holds don't repeat
+        assert_eq!(keymap.press(KeyCode::KC_F), ProcessResult::None);
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_F),
+            ProcessResult::None
+        );
+        assert_eq!(
+            keymap.wait(200),
+            ProcessResult::EmitKey(KeyCode::KC_LCTL, true)
+        );
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_F),
+            ProcessResult::None
+        );
+        keymap.release(KeyCode::KC_F);
+        keymap.wait(300);
+
+        // Double-tap-then-hold holds the tap key, which repeats
+        assert_eq!(
+            keymap.tap(KeyCode::KC_F),
+            vec![down(KeyCode::KC_F), up(KeyCode::KC_F)]
+        );
+        assert_eq!(
+            keymap.press(KeyCode::KC_F),
+            ProcessResult::EmitKey(KeyCode::KC_F, true)
+        );
+        assert_eq!(
+            keymap.processor.process_key_repeat(KeyCode::KC_F),
+            ProcessResult::RepeatKey(KeyCode::KC_F)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_F),
+            ProcessResult::EmitKey(KeyCode::KC_F, false)
+        );
+    }
+
+    #[test]
+    fn test_mt_hold_back() {
+        let mut keymap =
+            Keymap::new("(remaps: { KC_F: MT(KC_F, KC_LCTL) }, mt_config: (flavor: Balanced))");
+
+        // Keys pressed while the MT key is pending are held back, then replayed in order
+        // behind the decision
+        assert_eq!(keymap.press(KeyCode::KC_F), ProcessResult::None);
+        assert_eq!(keymap.press(KeyCode::KC_J), ProcessResult::None);
+        assert_eq!(keymap.press(KeyCode::KC_K), ProcessResult::None);
+        assert_eq!(
+            keymap.release(KeyCode::KC_J).all_key_events(),
+            vec![
+                down(KeyCode::KC_LCTL),
+                down(KeyCode::KC_J),
+                down(KeyCode::KC_K),
+                up(KeyCode::KC_J)
+            ]
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_K),
+            ProcessResult::EmitKey(KeyCode::KC_K, false)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_F),
+            ProcessResult::EmitKey(KeyCode::KC_LCTL, false)
+        );
+
+        // MT key released while a key is held back → tap, then the held back key
+        assert_eq!(keymap.press(KeyCode::KC_F), ProcessResult::None);
+        assert_eq!(keymap.press(KeyCode::KC_J), ProcessResult::None);
+        assert_eq!(
+            keymap.release(KeyCode::KC_F).all_key_events(),
+            vec![down(KeyCode::KC_F), up(KeyCode::KC_F), down(KeyCode::KC_J)]
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_J),
+            ProcessResult::EmitKey(KeyCode::KC_J, false)
+        );
+
+        // Tapping term expires → hold, and the held back key is flushed
+        assert_eq!(keymap.press(KeyCode::KC_F), ProcessResult::None);
+        assert_eq!(keymap.press(KeyCode::KC_J), ProcessResult::None);
+        assert_eq!(keymap.wait(129), ProcessResult::None);
+        assert_eq!(
+            keymap.wait(1).all_key_events(),
+            vec![down(KeyCode::KC_LCTL), down(KeyCode::KC_J)]
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_J),
+            ProcessResult::EmitKey(KeyCode::KC_J, false)
+        );
+        assert_eq!(
+            keymap.release(KeyCode::KC_F),
+            ProcessResult::EmitKey(KeyCode::KC_LCTL, false)
+        );
+
+        // Tap-preferred: the release of a held back key is held back too
+        let mut keymap =
+            Keymap::new("(remaps: { KC_F: MT(KC_F, KC_LCTL) }, mt_config: (flavor: TapPreferred))");
+        assert_eq!(keymap.press(KeyCode::KC_F), ProcessResult::None);
+        assert_eq!(keymap.press(KeyCode::KC_J), ProcessResult::None);
+        assert_eq!(keymap.release(KeyCode::KC_J), ProcessResult::None);
+        assert_eq!(
+            keymap.release(KeyCode::KC_F).all_key_events(),
+            vec![
+                down(KeyCode::KC_F),
+                up(KeyCode::KC_F),
+                down(KeyCode::KC_J),
+                up(KeyCode::KC_J)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backspace_misfire() {
+        let config = "(remaps: { KC_F: MT(KC_F, KC_LCTL) }, mt_config: (adaptive_timing: true))";
+        let tap_misfires = |keymap: &Keymap| {
+            keymap
+                .processor
+                .mt_processor
+                .get_adaptive_stats()
+                .into_iter()
+                .find(|(keycode, _)| *keycode == KeyCode::KC_F)
+                .map_or(0, |(_, stats)| stats.tap_misfires)
+        };
+        // Tap F, run `between`, then hold F past the tapping term
+        let tap_then_hold = |between: &dyn Fn(&mut Keymap)| {
+            let mut keymap = Keymap::new(config);
+            assert_eq!(
+                keymap.tap(KeyCode::KC_F),
+                vec![down(KeyCode::KC_F), up(KeyCode::KC_F)]
+            );
+            between(&mut keymap);
+            keymap.press(KeyCode::KC_F);
+            assert_eq!(
+                keymap.wait(130),
+                ProcessResult::EmitKey(KeyCode::KC_LCTL, true)
+            );
+            keymap
+        };
+
+        // Tap corrected with Backspace, then held → tap misfire
+        let keymap = tap_then_hold(&|keymap| {
+            keymap.tap(KeyCode::KC_BSPC);
+        });
+        assert_eq!(tap_misfires(&keymap), 1);
+
+        // Backspace after the window → no penalty
+        let keymap = tap_then_hold(&|keymap| {
+            keymap.wait(1000);
+            keymap.tap(KeyCode::KC_BSPC);
+        });
+        assert_eq!(tap_misfires(&keymap), 0);
+
+        // Something else typed after Backspace → no penalty
+        let keymap = tap_then_hold(&|keymap| {
+            keymap.tap(KeyCode::KC_BSPC);
+            keymap.tap(KeyCode::KC_J);
+        });
+        assert_eq!(tap_misfires(&keymap), 0);
+    }
+}
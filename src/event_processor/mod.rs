@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
-use evdev::{AttributeSet, Device, EventType, InputEvent, Key};
+use evdev::{AttributeSet, Device, EventType, InputEvent, Key, RelativeAxisType};
 use std::os::unix::io::AsRawFd;
 use std::thread;
 use tracing::{debug, error, info, warn};
@@ -9,18 +9,31 @@ use tracing::{debug, error, info, warn};
 const SYN_REPORT: i32 = 0;
 const SYN_CODE: u16 = 0;
 
+// Mouse buttons registered on the virtual device (MS_BTN1..MS_BTN5)
+const MOUSE_BUTTONS: [Key; 5] = [
+    Key::BTN_LEFT,
+    Key::BTN_RIGHT,
+    Key::BTN_MIDDLE,
+    Key::BTN_SIDE,
+    Key::BTN_EXTRA,
+];
+
 use crate::config::Config;
 use crate::keyboard_id::KeyboardId;
 
 // Sub-modules in this directory
 pub mod actions;
+pub mod clock;
 pub mod keymap;
+pub mod recording;
+pub mod tuning;
 
 // Re-export for backwards compatibility
+pub use clock::Clock;
 pub use keymap::{evdev_to_keycode, keycode_to_evdev, KeymapProcessor, ProcessResult};
+pub use recording::{RecordedEvent, Recording, RecordingHeader, RecordingWriter};
 
-// Internal use
-use keymap::{keycode_to_evdev as kc_to_evdev, ProcessResult as ProcResult};
+use actions::{MouseButton, MouseEvent};
 
 /// Process events from a physical keyboard and output to virtual device
 /// Returns immediately after spawning thread
@@ -82,7 +95,8 @@ fn run_event_processor(
     info!("Grabbed device: {}", keyboard_name);
 
     // Create virtual uinput device
-    let mut virtual_device = create_virtual_device(device, keyboard_name)?;
+    let mut virtual_device =
+        create_virtual_device(device, keyboard_name, config.binds_mouse_keys())?;
     info!("Created virtual device for: {}", keyboard_name);
 
     // SAFETY: Release all keys immediately on startup to prevent stuck keys
@@ -135,7 +149,8 @@ fn run_event_processor(
                     if active { "enabled" } else { "disabled" },
                     keyboard_name
                 );
-                keymap.set_game_mode(active);
+                let result = keymap.set_game_mode(active);
+                emit_result(&mut virtual_device, result)?;
             }
             Err(std::sync::mpsc::TryRecvError::Empty) => {
                 // No game mode toggle, continue
@@ -178,91 +193,17 @@ fn run_event_processor(
                             let pressed = ev.value() == 1; // 1 = press, 0 = release, 2 = repeat
                             let repeat = ev.value() == 2;
 
-                            // Ignore repeat events
-                            if repeat {
-                                continue;
-                            }
-
-                            // Process key through keymap (QMK-inspired)
-                            let result = keymap.process_key(input_key, pressed);
-
-                            match result {
-                                ProcessResult::EmitKey(output_key, output_pressed) => {
-                                    // Convert back to evdev and emit
-                                    let output_evdev = keycode_to_evdev(output_key);
-                                    let output_event = InputEvent::new_now(
-                                        ev.event_type(),
-                                        output_evdev.code(),
-                                        i32::from(output_pressed),
-                                    );
-                                    virtual_device.emit(&[output_event])?;
-                                }
-                                ProcessResult::TypeString(text, add_enter) => {
-                                    // Type out the string character by character
-                                    type_string(&mut virtual_device, &text, add_enter)?;
-                                }
-                                ProcessResult::TapKeyPressRelease(tap_key) => {
-                                    // Emit tap key press and release
-                                    let key_evdev = keycode_to_evdev(tap_key);
-                                    let press_event =
-                                        InputEvent::new_now(ev.event_type(), key_evdev.code(), 1);
-                                    virtual_device.emit(&[press_event])?;
-
-                                    std::thread::sleep(std::time::Duration::from_millis(5));
-
-                                    let release_event =
-                                        InputEvent::new_now(ev.event_type(), key_evdev.code(), 0);
-                                    virtual_device.emit(&[release_event])?;
-                                }
-                                ProcessResult::MultipleEvents(events) => {
-                                    // Emit multiple events in sequence
-                                    for (key, pressed) in events {
-                                        let key_evdev = keycode_to_evdev(key);
-                                        let event = InputEvent::new_now(
-                                            ev.event_type(),
-                                            key_evdev.code(),
-                                            i32::from(pressed),
-                                        );
-                                        virtual_device.emit(&[event])?;
-                                        std::thread::sleep(std::time::Duration::from_millis(2));
-                                    }
-                                }
-                                ProcessResult::RunCommand(command) => {
-                                    // Run shell command in fully detached background process
-                                    // This ensures:
-                                    // 1. Command errors don't crash the middleware
-                                    // 2. Process is detached (daemon can shutdown cleanly)
-                                    // 3. Child processes don't block the event loop
-                                    std::thread::spawn(move || {
-                                        match std::process::Command::new("/bin/sh")
-                                            .arg("-c")
-                                            .arg(&command)
-                                            .stdin(std::process::Stdio::null())
-                                            .stdout(std::process::Stdio::null())
-                                            .stderr(std::process::Stdio::null())
-                                            .spawn()
-                                        {
-                                            Ok(mut child) => {
-                                                // Detach from child - don't wait for it
-                                                // This prevents zombie processes and allows clean shutdown
-                                                std::thread::spawn(move || {
-                                                    let _ = child.wait();
-                                                });
-                                            }
-                                            Err(e) => {
-                                                // Log error but don't crash middleware
-                                                error!(
-                                                    "Failed to execute command '{}': {}",
-                                                    command, e
-                                                );
-                                            }
-                                        }
-                                    });
-                                }
-                                ProcessResult::None => {
-                                    // Don't emit anything (consumed by layer switch, etc.)
-                                }
-                            }
+                            // Repeats are forwarded as repeats of the key's current output
+                            let result = if repeat {
+                                keymap.process_key_repeat(input_key)
+                            } else {
+                                // Process key through keymap (QMK-inspired), timed by
+                                // the event timestamp rather than when it was read
+                                let time = Clock::instant_from(ev.timestamp());
+                                keymap.process_key_at(input_key, pressed, time)
+                            };
+
+                            emit_result(&mut virtual_device, result)?;
                         } else {
                             // Unsupported key, pass through unchanged
                             virtual_device.emit(&[ev])?;
@@ -274,26 +215,10 @@ fn run_event_processor(
                 }
             }
             Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                // No events available - check for DT timeouts
+                // No events available - check for DT/MT/OSM timeouts
                 // This allows hold detection to work even when no keys are being pressed
-                let timeout_result = keymap.check_dt_timeouts();
-                match timeout_result {
-                    ProcResult::MultipleEvents(events) => {
-                        // Emit timeout events (hold first action, single-tap, etc.)
-                        for (key, pressed) in events {
-                            let key_evdev = kc_to_evdev(key);
-                            let event = InputEvent::new_now(
-                                EventType::KEY,
-                                key_evdev.code(),
-                                i32::from(pressed),
-                            );
-                            virtual_device.emit(&[event])?;
-                        }
-                    }
-                    _ => {
-                        // No timeouts to process
-                    }
-                }
+                let timeout_result = keymap.check_timeouts();
+                emit_result(&mut virtual_device, timeout_result)?;
 
                 // Sleep briefly to avoid CPU spinning
                 // 1ms sleep provides excellent responsiveness while preventing busy-wait
@@ -304,8 +229,130 @@ fn run_event_processor(
     }
 }
 
+/// Emit a keymap result to the virtual device
+fn emit_result(virtual_device: &mut VirtualDevice, result: ProcessResult) -> Result<()> {
+    match result {
+        ProcessResult::EmitKey(output_key, output_pressed) => {
+            // Convert back to evdev and emit
+            let output_evdev = keycode_to_evdev(output_key);
+            let output_event = InputEvent::new_now(
+                EventType::KEY,
+                output_evdev.code(),
+                i32::from(output_pressed),
+            );
+            virtual_device.emit(&[output_event])?;
+        }
+        ProcessResult::RepeatKey(output_key) => {
+            let output_evdev = keycode_to_evdev(output_key);
+            let output_event = InputEvent::new_now(EventType::KEY, output_evdev.code(), 2);
+            virtual_device.emit(&[output_event])?;
+        }
+        ProcessResult::TypeString(text, add_enter) => {
+            // Type out the string character by character
+            type_string(virtual_device, &text, add_enter)?;
+        }
+        ProcessResult::MultipleEvents(events) => {
+            // Emit multiple events in sequence
+            for (key, pressed) in events {
+                let key_evdev = keycode_to_evdev(key);
+                let event =
+                    InputEvent::new_now(EventType::KEY, key_evdev.code(), i32::from(pressed));
+                virtual_device.emit(&[event])?;
+                std::thread::sleep(std::time::Duration::from_millis(2));
+            }
+        }
+        ProcessResult::RunCommand(command) => run_command(command),
+        ProcessResult::Mouse(events) => {
+            // Each event is emitted with its own SYN_REPORT
+            for event in events {
+                virtual_device.emit(&mouse_input_events(event))?;
+            }
+        }
+        ProcessResult::Batch(results) => {
+            // Emit each result in order
+            for result in results {
+                emit_result(virtual_device, result)?;
+            }
+        }
+        ProcessResult::None => {
+            // Don't emit anything (consumed by layer switch, etc.)
+        }
+    }
+    Ok(())
+}
+
+/// Convert a mouse event to evdev BTN/REL events
+fn mouse_input_events(event: MouseEvent) -> Vec<InputEvent> {
+    match event {
+        MouseEvent::Button(button, pressed) => vec![InputEvent::new_now(
+            EventType::KEY,
+            mouse_button_to_evdev(button).code(),
+            i32::from(pressed),
+        )],
+        MouseEvent::Move(x, y) => [(RelativeAxisType::REL_X, x), (RelativeAxisType::REL_Y, y)]
+            .into_iter()
+            .filter(|(_, value)| *value != 0)
+            .map(|(axis, value)| InputEvent::new_now(EventType::RELATIVE, axis.0, value))
+            .collect(),
+        MouseEvent::Wheel(vertical, horizontal) => [
+            (RelativeAxisType::REL_WHEEL, vertical),
+            (RelativeAxisType::REL_HWHEEL, horizontal),
+        ]
+        .into_iter()
+        .filter(|(_, value)| *value != 0)
+        .map(|(axis, value)| InputEvent::new_now(EventType::RELATIVE, axis.0, value))
+        .collect(),
+    }
+}
+
+const fn mouse_button_to_evdev(button: MouseButton) -> Key {
+    match button {
+        MouseButton::Left => Key::BTN_LEFT,
+        MouseButton::Right => Key::BTN_RIGHT,
+        MouseButton::Middle => Key::BTN_MIDDLE,
+        MouseButton::Side => Key::BTN_SIDE,
+        MouseButton::Extra => Key::BTN_EXTRA,
+    }
+}
+
+/// Run shell command in fully detached background process
+/// This ensures:
+/// 1. Command errors don't crash the middleware
+/// 2. Process is detached (daemon can shutdown cleanly)
+/// 3. Child processes don't block the event loop
+fn run_command(command: String) {
+    std::thread::spawn(move || {
+        match std::process::Command::new("/bin/sh")
+            .arg("-c")
+            .arg(&command)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+        {
+            Ok(mut child) => {
+                // Detach from child - don't wait for it
+                // This prevents zombie processes and allows clean shutdown
+                std::thread::spawn(move || {
+                    let _ = child.wait();
+                });
+            }
+            Err(e) => {
+                // Log error but don't crash middleware
+                error!("Failed to execute command '{}': {}", command, e);
+            }
+        }
+    });
+}
+
 /// Create a virtual uinput device that mimics the physical keyboard
-fn create_virtual_device(physical_device: &Device, keyboard_name: &str) -> Result<VirtualDevice> {
+/// With `mouse_keys`, also registers mouse buttons and relative axes. Only then, since
+/// libinput treats a device with them as a pointer too
+fn create_virtual_device(
+    physical_device: &Device,
+    keyboard_name: &str,
+    mouse_keys: bool,
+) -> Result<VirtualDevice> {
     let mut keys = AttributeSet::<Key>::new();
 
     // Copy all supported keys from physical device
@@ -315,12 +362,37 @@ fn create_virtual_device(physical_device: &Device, keyboard_name: &str) -> Resul
         }
     }
 
+    if !mouse_keys {
+        let virtual_device = VirtualDeviceBuilder::new()?
+            .name(&format!(
+                "Keyboard Middleware Virtual Keyboard ({keyboard_name})"
+            ))
+            .with_keys(&keys)?
+            .build()?;
+        return Ok(virtual_device);
+    }
+
+    // Mouse buttons and axes for mouse keys
+    for button in MOUSE_BUTTONS {
+        keys.insert(button);
+    }
+    let mut axes = AttributeSet::<RelativeAxisType>::new();
+    for axis in [
+        RelativeAxisType::REL_X,
+        RelativeAxisType::REL_Y,
+        RelativeAxisType::REL_WHEEL,
+        RelativeAxisType::REL_HWHEEL,
+    ] {
+        axes.insert(axis);
+    }
+
     // Build virtual device
     let virtual_device = VirtualDeviceBuilder::new()?
         .name(&format!(
             "Keyboard Middleware Virtual Keyboard ({keyboard_name})"
         ))
         .with_keys(&keys)?
+        .with_relative_axes(&axes)?
         .build()?;
 
     Ok(virtual_device)
@@ -429,6 +501,12 @@ fn release_all_keys(virtual_device: &mut VirtualDevice, keymap: &KeymapProcessor
         let _ = virtual_device.emit(&[syn_event]);
     }
 
+    // Release mouse buttons in case a mouse key was held
+    for button in MOUSE_BUTTONS {
+        let event = InputEvent::new_now(EventType::KEY, button.code(), 0);
+        let _ = virtual_device.emit(&[event]);
+    }
+
     // Also release common modifiers as a safety measure
     let modifiers = [
         Key::KEY_LEFTCTRL,
@@ -0,0 +1,349 @@
+/// Recorded typing sessions and their offline replay
+///
+/// File Format (JSON Lines, one object per line):
+/// - First line: `RecordingHeader` (which keyboard was recorded)
+/// - Then one `RecordedEvent` per raw key event, in order
+///
+/// Every event is written as it happens, so a recording stopped with Ctrl+C is complete.
+/// MT key presses can be labeled by hand for `keymux tune`: `"intent":"hold"` or `"tap"`.
+///
+/// Replay feeds the events through a `KeymapProcessor` on a simulated clock, checking
+/// timeouts on the millisecond ticks of the daemon's idle loop. Ticks before the
+/// keymap's next deadline are skipped, so idle gaps cost nothing. The same recording
+/// and config always produce the same output.
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions, Permissions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, KeyCode};
+
+use super::clock::Clock;
+use super::keymap::{KeymapProcessor, ProcessResult};
+
+/// How long timeouts keep being checked after the last recorded event
+const REPLAY_TAIL_MS: u64 = 1000;
+
+/// Which keyboard a recording was made on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    /// Keyboard name
+    pub keyboard: String,
+    /// Hardware ID (selects per-keyboard overrides on replay)
+    pub keyboard_id: String,
+}
+
+/// A raw key event from the physical keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Microseconds since the first recorded event (from the evdev timestamps)
+    pub time_us: u64,
+    /// Physical key
+    pub key: KeyCode,
+    /// evdev value: 1 = press, 0 = release, 2 = repeat
+    pub value: i32,
+    /// What an MT key press was meant to do (added by hand)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<MtIntent>,
+}
+
+/// Intended outcome of an MT key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MtIntent {
+    Tap,
+    Hold,
+}
+
+impl MtIntent {
+    /// Intent matching a tap/hold decision
+    #[must_use]
+    pub const fn from_hold(hold: bool) -> Self {
+        if hold {
+            Self::Hold
+        } else {
+            Self::Tap
+        }
+    }
+}
+
+/// A recorded typing session
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// Recorded keyboard
+    pub header: RecordingHeader,
+    /// Key events in the order they happened
+    pub events: Vec<RecordedEvent>,
+}
+
+impl Recording {
+    /// Load a recording file
+    #[allow(clippy::missing_errors_doc)]
+    pub fn load(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+
+        let header_line = lines.next().context("Recording is empty")??;
+        let header = serde_json::from_str(&header_line).context("Invalid recording header")?;
+
+        let mut events = Vec::new();
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid event on line {}", index + 2))?;
+            events.push(event);
+        }
+
+        Ok(Self { header, events })
+    }
+
+    /// Save the recording to a file
+    #[cfg(test)]
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut writer = RecordingWriter::create(path, &self.header)?;
+        for event in &self.events {
+            writer.write(event)?;
+        }
+        Ok(())
+    }
+
+    /// Recording length
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.events
+            .last()
+            .map_or(Duration::ZERO, |event| Duration::from_micros(event.time_us))
+    }
+}
+
+/// Writes a recording event by event
+pub struct RecordingWriter {
+    writer: BufWriter<File>,
+}
+
+impl RecordingWriter {
+    /// Create the file, readable by the owner only since it logs keystrokes, and write the
+    /// header
+    #[allow(clippy::missing_errors_doc)]
+    pub fn create(path: &Path, header: &RecordingHeader) -> Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        // The mode only applies to new files
+        file.set_permissions(Permissions::from_mode(0o600))
+            .with_context(|| format!("Failed to restrict {}", path.display()))?;
+        let mut writer = Self {
+            writer: BufWriter::new(file),
+        };
+        writer.write_line(header)?;
+        Ok(writer)
+    }
+
+    /// Append an event (flushed right away)
+    #[allow(clippy::missing_errors_doc)]
+    pub fn write(&mut self, event: &RecordedEvent) -> Result<()> {
+        self.write_line(event)
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, value)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Keymap output at one point of a replay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayStep {
+    /// Microseconds since the first recorded event
+    pub time_us: u64,
+    /// Recorded event that produced the output (None for timeouts)
+    pub input: Option<RecordedEvent>,
+    /// What the keymap emitted
+    pub result: ProcessResult,
+}
+
+/// MT decision made during a replay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayDecision {
+    /// Physical MT key
+    pub key: KeyCode,
+    /// When the key was pressed (microseconds, matches its `RecordedEvent`)
+    pub press_us: u64,
+    /// What the keymap decided
+    pub decision: MtIntent,
+    /// Corrected with Backspace and retyped the other way
+    pub misfired: bool,
+}
+
+/// Feed a recording through a keymap built from `config` and collect its output
+/// Timeout checks with no output are left out, recorded events always have a step
+#[must_use]
+pub fn replay(recording: &Recording, config: &Config) -> Vec<ReplayStep> {
+    let start = Instant::now();
+    let mut keymap = KeymapProcessor::with_clock(config, Clock::manual(start));
+    run(recording, &mut keymap, start)
+}
+
+/// Feed a recording through a keymap built from `config` and collect its MT decisions
+#[must_use]
+pub fn replay_mt_decisions(recording: &Recording, config: &Config) -> Vec<ReplayDecision> {
+    let start = Instant::now();
+    let mut keymap = KeymapProcessor::with_clock(config, Clock::manual(start));
+    keymap.log_mt_decisions();
+    run(recording, &mut keymap, start);
+
+    keymap
+        .take_mt_decisions()
+        .into_iter()
+        .map(|logged| ReplayDecision {
+            key: logged.keycode,
+            press_us: logged
+                .pressed_at
+                .saturating_duration_since(start)
+                .as_micros() as u64,
+            decision: MtIntent::from_hold(logged.hold),
+            misfired: logged.misfired,
+        })
+        .collect()
+}
+
+fn run(recording: &Recording, keymap: &mut KeymapProcessor, start: Instant) -> Vec<ReplayStep> {
+    let mut steps = Vec::new();
+
+    let mut last_us = 0;
+    for &event in &recording.events {
+        check_timeouts(keymap, start, last_us, event.time_us, &mut steps);
+        last_us = event.time_us;
+
+        let result = if event.value == 2 {
+            keymap.process_key_repeat(event.key)
+        } else {
+            let time = start + Duration::from_micros(event.time_us);
+            keymap.process_key_at(event.key, event.value == 1, time)
+        };
+        steps.push(ReplayStep {
+            time_us: event.time_us,
+            input: Some(event),
+            result,
+        });
+    }
+    let end_us = last_us + REPLAY_TAIL_MS * 1000;
+    check_timeouts(keymap, start, last_us, end_us, &mut steps);
+
+    steps
+}
+
+/// Check timeouts on the millisecond ticks after `from_us` up to `to_us` (the daemon's
+/// idle loop), skipping the ticks before the keymap's next deadline
+fn check_timeouts(
+    keymap: &mut KeymapProcessor,
+    start: Instant,
+    from_us: u64,
+    to_us: u64,
+    steps: &mut Vec<ReplayStep>,
+) {
+    let mut time_us = from_us;
+    loop {
+        let Some(deadline) = keymap.next_deadline() else {
+            return;
+        };
+        // First tick at or after the deadline
+        let deadline_us = deadline.saturating_duration_since(start).as_micros() as u64;
+        let ticks = deadline_us.saturating_sub(from_us).div_ceil(1000);
+        time_us = (from_us + ticks * 1000).max(time_us + 1000);
+        if time_us > to_us {
+            return;
+        }
+
+        let result = keymap.check_timeouts_at(start + Duration::from_micros(time_us));
+        if result != ProcessResult::None {
+            steps.push(ReplayStep {
+                time_us,
+                input: None,
+                result,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(time_ms: u64, key: KeyCode, value: i32) -> RecordedEvent {
+        RecordedEvent {
+            time_us: time_ms * 1000,
+            key,
+            value,
+            intent: None,
+        }
+    }
+
+    #[test]
+    fn test_replay_is_timed_by_recording() {
+        let input = "(
+            remaps: { KC_A: MT(Key(KC_A), Key(KC_LSFT)) },
+            tapping_term_ms: 200,
+            mt_config: (flavor: HoldPreferred),
+        )";
+        let config: Config = ron::from_str(input).unwrap();
+        let recording = Recording {
+            header: RecordingHeader::default(),
+            events: vec![
+                // Quick tap → 'a'
+                event(0, KeyCode::KC_A, 1),
+                event(120, KeyCode::KC_A, 0),
+                // Held past the tapping term → Shift, decided by a timeout check
+                event(1000, KeyCode::KC_A, 1),
+                event(1400, KeyCode::KC_J, 1),
+                event(1450, KeyCode::KC_J, 0),
+                event(1500, KeyCode::KC_A, 0),
+                // An hour later - idle ticks are skipped
+                event(3_600_000, KeyCode::KC_A, 1),
+                event(3_600_100, KeyCode::KC_A, 0),
+            ],
+        };
+
+        // Round trip through a file
+        let path =
+            std::env::temp_dir().join(format!("keymux-recording-{}.jsonl", std::process::id()));
+        recording.save(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let recording = Recording::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(recording.events.len(), 8);
+
+        let outputs: Vec<(u64, ProcessResult)> = replay(&recording, &config)
+            .into_iter()
+            .filter(|step| step.result != ProcessResult::None)
+            .map(|step| (step.time_us / 1000, step.result))
+            .collect();
+        let a_tap = [(KeyCode::KC_A, true), (KeyCode::KC_A, false)];
+        assert_eq!(outputs[0].0, 120);
+        assert_eq!(outputs[0].1, ProcessResult::MultipleEvents(a_tap.to_vec()));
+        assert_eq!(
+            outputs[1..],
+            [
+                (1200, ProcessResult::EmitKey(KeyCode::KC_LSFT, true)),
+                (1400, ProcessResult::EmitKey(KeyCode::KC_J, true)),
+                (1450, ProcessResult::EmitKey(KeyCode::KC_J, false)),
+                (1500, ProcessResult::EmitKey(KeyCode::KC_LSFT, false)),
+                (3_600_100, ProcessResult::MultipleEvents(a_tap.to_vec())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,627 @@
+/// Tuning MT settings from recorded typing sessions
+///
+/// Intent Model:
+/// - A press labeled in the recording (`"intent":"hold"`) means what it says
+/// - Otherwise the current config's decision is taken as meant, unless it was
+///   corrected with Backspace and the key retyped the other way (a misfire)
+///
+/// Sweep:
+/// - Each setting in turn is set to every candidate value, for all MT keys at once,
+///   and the recordings are replayed
+/// - A value's score is the number of MT decisions that differ from the intents
+/// - The best value overall becomes the global setting. Keys that do strictly better
+///   with another value get it as a per-key option (where MT supports one). Per-key
+///   options already in the config are updated when another value does better
+/// - `tapping_term_ms` is also read by DT, TD and OSM, so it stays as is globally and
+///   every MT key that does better with another value gets it as a per-key option
+/// - Later settings are swept with the earlier ones already tuned
+///
+/// Per-Keyboard Overrides:
+/// - Recordings are replayed with the recorded keyboard's effective config, and the
+///   current values come from it too. Recordings to tune together need the same one
+/// - Global settings the keyboard's override sets are written into that override
+///
+/// Adaptive timing is off while tuning so every replay is deterministic.
+use std::collections::HashMap;
+
+use crate::config::{Config, ConfigEdit, KeyCode, KeyOptions, MtFlavor};
+
+use super::recording::{replay_mt_decisions, MtIntent, Recording};
+
+/// An MT setting `keymux tune` can sweep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TuneParam {
+    TappingTerm,
+    RequirePriorIdle,
+    PermissiveHold,
+    RollWindow,
+    ChordWindow,
+}
+
+impl TuneParam {
+    /// All settings, in sweep order
+    pub const ALL: [Self; 5] = [
+        Self::TappingTerm,
+        Self::RequirePriorIdle,
+        Self::PermissiveHold,
+        Self::RollWindow,
+        Self::ChordWindow,
+    ];
+
+    /// Config field name
+    #[must_use]
+    pub const fn field(self) -> &'static str {
+        match self {
+            Self::TappingTerm => "tapping_term_ms",
+            Self::RequirePriorIdle => "require_prior_idle_ms",
+            Self::PermissiveHold => "permissive_hold",
+            Self::RollWindow => "roll_detection_window_ms",
+            Self::ChordWindow => "chord_detection_window_ms",
+        }
+    }
+
+    /// Struct holding the global setting (None = top level)
+    #[must_use]
+    pub const fn section(self) -> Option<&'static str> {
+        match self {
+            Self::TappingTerm => None,
+            _ => Some("mt_config"),
+        }
+    }
+
+    /// Whether the keyboard's value of the setting comes from its per-keyboard override
+    #[must_use]
+    pub fn is_overridden(self, config: &Config, keyboard_id: &str) -> bool {
+        let Some(keyboard) = config.per_keyboard_overrides.get(keyboard_id) else {
+            return false;
+        };
+        if !config.per_keyboard_inherits_global_layout {
+            return true;
+        }
+        match self.section() {
+            None => keyboard.tapping_term_ms.is_some(),
+            Some(_) => keyboard.mt_config.is_some(),
+        }
+    }
+
+    /// Whether MT keys can override the setting per key
+    #[must_use]
+    pub const fn is_per_key(self) -> bool {
+        matches!(
+            self,
+            Self::TappingTerm | Self::RequirePriorIdle | Self::PermissiveHold
+        )
+    }
+
+    /// Whether features besides MT read the global setting (tuned per key only)
+    #[must_use]
+    pub const fn is_shared(self) -> bool {
+        matches!(self, Self::TappingTerm)
+    }
+
+    /// Whether the setting affects MT decisions with this config
+    #[must_use]
+    pub const fn is_relevant(self, config: &Config) -> bool {
+        let heuristic = matches!(config.mt_config.flavor, MtFlavor::Heuristic);
+        match self {
+            Self::TappingTerm | Self::RequirePriorIdle => true,
+            Self::PermissiveHold => heuristic,
+            Self::RollWindow => heuristic && config.mt_config.same_hand_roll_detection,
+            Self::ChordWindow => heuristic && config.mt_config.opposite_hand_chord_detection,
+        }
+    }
+
+    /// Candidate values (booleans are 0/1)
+    fn candidates(self) -> Vec<u32> {
+        match self {
+            Self::TappingTerm => (100..=300).step_by(10).collect(),
+            Self::RequirePriorIdle => vec![0, 50, 75, 100, 125, 150, 175, 200],
+            Self::PermissiveHold => vec![0, 1],
+            Self::RollWindow => (50..=300).step_by(25).collect(),
+            Self::ChordWindow => (20..=120).step_by(10).collect(),
+        }
+    }
+
+    /// Global value in a config
+    #[must_use]
+    pub fn current(self, config: &Config) -> u32 {
+        match self {
+            Self::TappingTerm => config.tapping_term_ms,
+            Self::RequirePriorIdle => config.mt_config.require_prior_idle_ms,
+            Self::PermissiveHold => u32::from(config.mt_config.permissive_hold),
+            Self::RollWindow => config.mt_config.roll_detection_window_ms,
+            Self::ChordWindow => config.mt_config.chord_detection_window_ms,
+        }
+    }
+
+    fn set_global(self, config: &mut Config, value: u32) {
+        match self {
+            Self::TappingTerm => config.tapping_term_ms = value,
+            Self::RequirePriorIdle => config.mt_config.require_prior_idle_ms = value,
+            Self::PermissiveHold => config.mt_config.permissive_hold = value != 0,
+            Self::RollWindow => config.mt_config.roll_detection_window_ms = value,
+            Self::ChordWindow => config.mt_config.chord_detection_window_ms = value,
+        }
+    }
+
+    /// Per-key value set on an MT action
+    fn option(self, options: &KeyOptions) -> Option<u32> {
+        match self {
+            Self::TappingTerm => options.tapping_term_ms,
+            Self::RequirePriorIdle => options.require_prior_idle_ms,
+            Self::PermissiveHold => options.permissive_hold.map(u32::from),
+            Self::RollWindow | Self::ChordWindow => None,
+        }
+    }
+
+    fn set_option(self, options: &mut KeyOptions, value: u32) {
+        match self {
+            Self::TappingTerm => options.tapping_term_ms = Some(value),
+            Self::RequirePriorIdle => options.require_prior_idle_ms = Some(value),
+            Self::PermissiveHold => options.permissive_hold = Some(value != 0),
+            Self::RollWindow | Self::ChordWindow => {}
+        }
+    }
+
+    /// Value as written in the config
+    #[must_use]
+    pub fn format(self, value: u32) -> String {
+        match self {
+            Self::PermissiveHold => (value != 0).to_string(),
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// Tuned settings, applied on top of a config
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tuning {
+    /// Global settings
+    pub global: Vec<(TuneParam, u32)>,
+    /// Per-key options
+    pub per_key: Vec<(KeyCode, TuneParam, u32)>,
+}
+
+impl Tuning {
+    /// Apply the settings to a config
+    pub fn apply(&self, config: &mut Config) {
+        for &(param, value) in &self.global {
+            param.set_global(config, value);
+        }
+        for (keycode, options) in config.mt_options_mut() {
+            for &(key, param, value) in &self.per_key {
+                if key == keycode {
+                    param.set_option(options, value);
+                }
+            }
+        }
+    }
+
+    /// Config file edits that apply the settings for a keyboard
+    /// `config` is the global config (with the per-keyboard overrides)
+    #[must_use]
+    pub fn edits(&self, config: &Config, keyboard_id: &str) -> Vec<ConfigEdit> {
+        let global = self.global.iter().map(|&(param, value)| ConfigEdit::Field {
+            keyboard: param
+                .is_overridden(config, keyboard_id)
+                .then(|| keyboard_id.to_string()),
+            section: param.section(),
+            field: param.field(),
+            value: param.format(value),
+        });
+        let per_key = self
+            .per_key
+            .iter()
+            .map(|&(key, param, value)| ConfigEdit::MtOption {
+                key,
+                field: param.field(),
+                value: param.format(value),
+            });
+        global.chain(per_key).collect()
+    }
+}
+
+/// MT decisions that differ from the intents
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Score {
+    /// Per MT key
+    pub per_key: HashMap<KeyCode, u32>,
+    /// All keys
+    pub total: u32,
+}
+
+impl Score {
+    /// Mismatches of one key
+    #[must_use]
+    pub fn key(&self, keycode: KeyCode) -> u32 {
+        self.per_key.get(&keycode).copied().unwrap_or(0)
+    }
+}
+
+/// A recording with the intended outcome of its MT presses
+#[derive(Debug, Clone)]
+pub struct TuningSession {
+    recording: Recording,
+    /// Effective config for the recorded keyboard, adaptive timing off
+    config: Config,
+    /// Intent per (key, press time)
+    intents: HashMap<(KeyCode, u64), MtIntent>,
+    /// Presses labeled in the recording
+    pub labeled: usize,
+    /// Presses whose intent comes from a correction
+    pub corrected: usize,
+}
+
+impl TuningSession {
+    /// Work out the intents of a recording's MT presses under `config`
+    #[must_use]
+    pub fn new(recording: Recording, config: &Config) -> Self {
+        let mut config = config.for_keyboard(&recording.header.keyboard_id);
+        config.mt_config.adaptive_timing = false;
+
+        let mut intents = HashMap::new();
+        let mut corrected = 0;
+        for decision in replay_mt_decisions(&recording, &config) {
+            let press = (decision.key, decision.press_us);
+            if intents.contains_key(&press) {
+                continue;
+            }
+            let intent = if decision.misfired {
+                corrected += 1;
+                MtIntent::from_hold(decision.decision == MtIntent::Tap)
+            } else {
+                decision.decision
+            };
+            intents.insert(press, intent);
+        }
+
+        let labels: Vec<_> = recording
+            .events
+            .iter()
+            .filter(|event| event.value == 1)
+            .filter_map(|event| Some(((event.key, event.time_us), event.intent?)))
+            .collect();
+        let labeled = labels.len();
+        intents.extend(labels);
+
+        Self {
+            recording,
+            config,
+            intents,
+            labeled,
+            corrected,
+        }
+    }
+
+    /// Effective config of the recorded keyboard (adaptive timing off)
+    #[must_use]
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Recorded keyboard
+    #[must_use]
+    pub fn keyboard_id(&self) -> &str {
+        &self.recording.header.keyboard_id
+    }
+
+    /// Number of MT presses with an intent
+    #[must_use]
+    pub fn presses(&self) -> usize {
+        self.intents.len()
+    }
+
+    /// Replay with `adjust` applied to the config and count mismatched decisions
+    fn score(&self, adjust: &dyn Fn(&mut Config), score: &mut Score) {
+        let mut config = self.config.clone();
+        adjust(&mut config);
+
+        let mut decisions = HashMap::new();
+        for decision in replay_mt_decisions(&self.recording, &config) {
+            decisions
+                .entry((decision.key, decision.press_us))
+                .or_insert(decision.decision);
+        }
+
+        for (&(keycode, press_us), &intent) in &self.intents {
+            if decisions.get(&(keycode, press_us)) != Some(&intent) {
+                *score.per_key.entry(keycode).or_default() += 1;
+                score.total += 1;
+            }
+        }
+    }
+}
+
+/// Mismatches of all sessions with `adjust` applied
+fn score(sessions: &[TuningSession], adjust: &dyn Fn(&mut Config)) -> Score {
+    let mut score = Score::default();
+    for session in sessions {
+        session.score(adjust, &mut score);
+    }
+    score
+}
+
+/// Value with the fewest mismatches, ties going to the one closest to `prefer`
+fn pick(scores: &[(u32, u32)], prefer: u32) -> u32 {
+    scores
+        .iter()
+        .min_by_key(|&&(value, mismatches)| (mismatches, value.abs_diff(prefer)))
+        .map_or(prefer, |&(value, _)| value)
+}
+
+/// Sweep result of one setting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamResult {
+    /// Swept setting
+    pub param: TuneParam,
+    /// Global value before tuning
+    pub current: u32,
+    /// Recommended global value
+    pub best: u32,
+    /// Total mismatches per candidate value
+    pub scores: Vec<(u32, u32)>,
+    /// Recommended per-key values: (key, value, mismatches before, after)
+    pub per_key: Vec<(KeyCode, u32, u32, u32)>,
+}
+
+impl ParamResult {
+    /// Total mismatches at a value
+    #[must_use]
+    pub fn mismatches(&self, value: u32) -> Option<u32> {
+        self.scores
+            .iter()
+            .find(|&&(candidate, _)| candidate == value)
+            .map(|&(_, mismatches)| mismatches)
+    }
+}
+
+/// Outcome of `tune`
+#[derive(Debug, Clone)]
+pub struct TuneReport {
+    /// Mismatches with the current settings
+    pub before: Score,
+    /// Mismatches with the recommended settings
+    pub after: Score,
+    /// Sweep results, in sweep order
+    pub params: Vec<ParamResult>,
+    /// Recommended settings
+    pub tuning: Tuning,
+}
+
+/// Sweep the MT settings over the sessions and recommend values
+/// `config` is the sessions' effective config (`TuningSession::config`)
+/// `progress` is called before each setting is swept
+pub fn tune(
+    config: &Config,
+    sessions: &[TuningSession],
+    mut progress: impl FnMut(TuneParam),
+) -> TuneReport {
+    let before = score(sessions, &|_| {});
+    let mut keys: Vec<KeyCode> = sessions
+        .iter()
+        .flat_map(|session| session.intents.keys().map(|&(keycode, _)| keycode))
+        .collect();
+    keys.sort_by_key(|keycode| format!("{keycode:?}"));
+    keys.dedup();
+
+    let mut tuning = Tuning::default();
+    let mut params = Vec::new();
+    for param in TuneParam::ALL {
+        if !param.is_relevant(config) {
+            continue;
+        }
+        progress(param);
+
+        // Per-key values already in the config
+        let mut existing = HashMap::new();
+        for (keycode, options) in config.clone().mt_options_mut() {
+            if let Some(value) = param.option(options) {
+                existing.entry(keycode).or_insert(value);
+            }
+        }
+
+        let current = param.current(config);
+        let mut candidates = param.candidates();
+        candidates.push(current);
+        candidates.extend(existing.values());
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        // Every MT key at the candidate value, overriding per-key options
+        let sweep: Vec<(u32, Score)> = candidates
+            .iter()
+            .map(|&value| {
+                let adjust = |config: &mut Config| {
+                    tuning.apply(config);
+                    param.set_global(config, value);
+                    for (_, options) in config.mt_options_mut() {
+                        param.set_option(options, value);
+                    }
+                };
+                (value, score(sessions, &adjust))
+            })
+            .collect();
+
+        let scores: Vec<(u32, u32)> = sweep
+            .iter()
+            .map(|(value, score)| (*value, score.total))
+            .collect();
+        let best = pick(&scores, current);
+
+        let mut per_key = Vec::new();
+        if param.is_per_key() {
+            for &keycode in &keys {
+                let key_scores: Vec<(u32, u32)> = sweep
+                    .iter()
+                    .map(|(value, score)| (*value, score.key(keycode)))
+                    .collect();
+                let mismatches = |value: u32| {
+                    key_scores
+                        .iter()
+                        .find(|&&(candidate, _)| candidate == value)
+                        .map_or(0, |&(_, mismatches)| mismatches)
+                };
+
+                // A key without its own value follows the global one
+                let prefer = existing.get(&keycode).copied().unwrap_or(best);
+                let key_best = pick(&key_scores, prefer);
+                // A shared setting keeps its global value, so keys need their own
+                let key_current = if param.is_shared() {
+                    existing.get(&keycode).copied().unwrap_or(current)
+                } else {
+                    prefer
+                };
+                if key_best != key_current {
+                    per_key.push((
+                        keycode,
+                        key_best,
+                        mismatches(key_current),
+                        mismatches(key_best),
+                    ));
+                }
+            }
+        }
+
+        if best != current && !param.is_shared() {
+            tuning.global.push((param, best));
+        }
+        tuning.per_key.extend(
+            per_key
+                .iter()
+                .map(|&(keycode, value, _, _)| (keycode, param, value)),
+        );
+        params.push(ParamResult {
+            param,
+            current,
+            best,
+            scores,
+            per_key,
+        });
+    }
+
+    let after = score(sessions, &|config| tuning.apply(config));
+    TuneReport {
+        before,
+        after,
+        params,
+        tuning,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_processor::recording::{RecordedEvent, RecordingHeader};
+
+    fn tap(key: KeyCode, press_ms: u64, release_ms: u64) -> [RecordedEvent; 2] {
+        let event = |time_ms: u64, value| RecordedEvent {
+            time_us: time_ms * 1000,
+            key,
+            value,
+            intent: None,
+        };
+        [event(press_ms, 1), event(release_ms, 0)]
+    }
+
+    #[test]
+    fn test_tune_tapping_term() {
+        let input = "(
+            tapping_term_ms: 130,
+            remaps: {
+                KC_A: MT(Key(KC_A), Key(KC_LGUI)),
+                KC_D: MT(Key(KC_D), Key(KC_LCTL)),
+            },
+            mt_config: (flavor: HoldPreferred),
+        )";
+        let config: Config = ron::from_str(input).unwrap();
+
+        let mut labeled_hold = tap(KeyCode::KC_A, 0, 120);
+        labeled_hold[0].intent = Some(MtIntent::Hold);
+        let events = [
+            // Meant as hold (labeled), but released before the term
+            labeled_hold,
+            tap(KeyCode::KC_A, 1000, 1090),
+            // Slow tap resolved to hold, corrected with Backspace and retyped
+            tap(KeyCode::KC_D, 2000, 2150),
+            tap(KeyCode::KC_BSPC, 2300, 2350),
+            tap(KeyCode::KC_D, 2400, 2480),
+        ];
+        let recording = Recording {
+            header: RecordingHeader::default(),
+            events: events.concat(),
+        };
+
+        let session = TuningSession::new(recording, &config);
+        assert_eq!(session.presses(), 4);
+        assert_eq!((session.labeled, session.corrected), (1, 1));
+
+        // KC_A needs a shorter term, KC_D a longer one - both per key, since
+        // the global term is shared with DT, TD and OSM
+        let report = tune(&config, &[session], |_| {});
+        assert_eq!(report.before.total, 2);
+        assert_eq!(report.params[0].best, 120);
+        assert!(report.tuning.global.is_empty());
+        assert_eq!(
+            report.tuning.per_key,
+            vec![
+                (KeyCode::KC_A, TuneParam::TappingTerm, 120),
+                (KeyCode::KC_D, TuneParam::TappingTerm, 160),
+            ]
+        );
+        assert_eq!(report.after.total, 0);
+    }
+
+    #[test]
+    fn test_tune_per_keyboard_override() {
+        let input = r#"(
+            tapping_term_ms: 130,
+            remaps: { KC_A: MT(Key(KC_A), Key(KC_LGUI)) },
+            mt_config: (flavor: HoldPreferred),
+            per_keyboard_overrides: {
+                "kb": (
+                    tapping_term_ms: Some(180),
+                    mt_config: Some((flavor: HoldPreferred)),
+                ),
+            },
+        )"#;
+        let config: Config = ron::from_str(input).unwrap();
+
+        // Meant as hold, but released before the keyboard's term
+        let mut labeled_hold = tap(KeyCode::KC_A, 0, 130);
+        labeled_hold[0].intent = Some(MtIntent::Hold);
+        let recording = Recording {
+            header: RecordingHeader {
+                keyboard: "Keyboard".to_string(),
+                keyboard_id: "kb".to_string(),
+            },
+            events: [labeled_hold, tap(KeyCode::KC_A, 1000, 1090)].concat(),
+        };
+        let session = TuningSession::new(recording, &config);
+        assert_eq!(session.config().tapping_term_ms, 180);
+
+        // The keyboard runs 180, so the best term is a change even though it's the global one
+        let effective = session.config().clone();
+        let report = tune(&effective, &[session], |_| {});
+        assert_eq!(report.params[0].current, 180);
+        assert_eq!(report.params[0].best, 130);
+        assert_eq!(
+            report.tuning.per_key,
+            vec![(KeyCode::KC_A, TuneParam::TappingTerm, 130)]
+        );
+        assert_eq!(report.after.total, 0);
+
+        // Global settings the override sets are written into it
+        let tuning = Tuning {
+            global: vec![(TuneParam::RequirePriorIdle, 100)],
+            per_key: Vec::new(),
+        };
+        let edit = |keyboard: Option<&str>| ConfigEdit::Field {
+            keyboard: keyboard.map(str::to_string),
+            section: Some("mt_config"),
+            field: "require_prior_idle_ms",
+            value: "100".to_string(),
+        };
+        assert_eq!(tuning.edits(&config, "kb"), vec![edit(Some("kb"))]);
+        assert_eq!(tuning.edits(&config, "other"), vec![edit(None)]);
+    }
+}
@@ -80,8 +80,10 @@ mod ipc;
 mod keyboard_id;
 mod list;
 mod niri;
+mod record;
 mod session_manager;
 mod toggle;
+mod tune;
 mod window;
 
 use daemon::AsyncDaemon;
@@ -148,6 +150,42 @@ enum Commands {
     /// Clear all adaptive timing statistics
     ClearStats,
 
+    /// Record raw key events of a keyboard to a file (stop with Ctrl+C)
+    Record {
+        /// Keyboard ID or part of its name (default: ask)
+        #[arg(short, long)]
+        keyboard: Option<String>,
+
+        /// Recording file to write
+        #[arg(short, long, default_value = "keymux-recording.jsonl")]
+        output: std::path::PathBuf,
+    },
+
+    /// Replay a recording through the keymap and print the output
+    Replay {
+        /// Recording file (from `keymux record`)
+        file: std::path::PathBuf,
+
+        /// Path to config file (default: ~/.config/keymux/config.ron)
+        #[arg(short, long)]
+        config: Option<std::path::PathBuf>,
+    },
+
+    /// Recommend MT settings from recordings (intent from corrections or labels)
+    Tune {
+        /// Recording files (from `keymux record`)
+        #[arg(required = true)]
+        files: Vec<std::path::PathBuf>,
+
+        /// Path to config file (default: ~/.config/keymux/config.ron)
+        #[arg(short, long)]
+        config: Option<std::path::PathBuf>,
+
+        /// Write the recommended settings into the config
+        #[arg(short, long)]
+        write: bool,
+    },
+
     /// Generate shell completions
     Completion {
         /// Shell to generate completions for
@@ -205,6 +243,19 @@ fn main() -> Result<()> {
         Some(Commands::ClearStats) => {
             clear_adaptive_stats()?;
         }
+        Some(Commands::Record { keyboard, output }) => {
+            record::run_record(keyboard.as_deref(), output)?;
+        }
+        Some(Commands::Replay { file, config }) => {
+            record::run_replay(file, config.as_deref())?;
+        }
+        Some(Commands::Tune {
+            files,
+            config,
+            write,
+        }) => {
+            tune::run_tune(files, config.as_deref(), *write)?;
+        }
         Some(Commands::Completion { shell }) => {
             generate_completion(*shell);
         }
@@ -434,7 +485,21 @@ fn show_adaptive_stats(config_path: Option<&std::path::Path>) -> Result<()> {
         Vec::new()
     };
 
-    if stats.is_empty() {
+    // MT key stats with misfire penalties (Backspace corrections)
+    let mt_stats_path = config_path.parent().unwrap().join("adaptive_stats.json");
+    let mut mt_processor = MtProcessor::new(ModtapConfig {
+        tapping_term_ms: config.tapping_term_ms,
+        adaptive_timing: true,
+        ..ModtapConfig::default()
+    });
+    if let Err(e) = mt_processor.load_stats(&mt_stats_path) {
+        println!();
+        println!("  {} Could not read MT stats: {}", "!".bright_yellow(), e);
+    }
+    let mut mt_stats = mt_processor.get_adaptive_stats();
+    mt_stats.sort_by_key(|(k, _)| format!("{:?}", k));
+
+    if stats.is_empty() && mt_stats.is_empty() {
         println!();
         println!(
             "  {} No adaptive statistics collected yet",
@@ -457,38 +522,74 @@ fn show_adaptive_stats(config_path: Option<&std::path::Path>) -> Result<()> {
     );
     println!();
 
-    // Sort stats by key name
-    let mut sorted_stats = stats;
-    sorted_stats.sort_by_key(|(k, _)| format!("{:?}", k));
-
-    // Compact table header
-    println!("  ┌───────┬────────┬─────────┬──────────┐");
-    println!(
-        "  │ {:^5} │ {:^6} │ {:^7} │ {:^8} │",
-        "Key".bright_white().bold(),
-        "Samples".bright_white().bold(),
-        "Avg(ms)".bright_white().bold(),
-        "Thresh(ms)".bright_white().bold()
-    );
-    println!("  ├───────┼────────┼─────────┼──────────┤");
-
-    // Table rows
-    for (keycode, key_stats) in sorted_stats {
-        let key_name = format!("{:?}", keycode).replace("KC_", "");
-        let samples = key_stats.tap_sample_count;
-        let avg_tap = key_stats.avg_tap_duration;
-        let threshold = key_stats.adaptive_threshold;
+    if !stats.is_empty() {
+        // Sort stats by key name
+        let mut sorted_stats = stats;
+        sorted_stats.sort_by_key(|(k, _)| format!("{:?}", k));
 
+        // Compact table header
+        println!("  ┌───────┬────────┬─────────┬──────────┐");
         println!(
             "  │ {:^5} │ {:^6} │ {:^7} │ {:^8} │",
-            key_name.bright_cyan(),
-            samples.to_string().bright_green(),
-            format!("{:.1}", avg_tap).bright_blue(),
-            format!("{:.1}", threshold).bright_yellow()
+            "Key".bright_white().bold(),
+            "Samples".bright_white().bold(),
+            "Avg(ms)".bright_white().bold(),
+            "Thresh(ms)".bright_white().bold()
         );
+        println!("  ├───────┼────────┼─────────┼──────────┤");
+
+        // Table rows
+        for (keycode, key_stats) in sorted_stats {
+            let key_name = format!("{:?}", keycode).replace("KC_", "");
+            let samples = key_stats.tap_sample_count;
+            let avg_tap = key_stats.avg_tap_duration;
+            let threshold = key_stats.adaptive_threshold;
+
+            println!(
+                "  │ {:^5} │ {:^6} │ {:^7} │ {:^8} │",
+                key_name.bright_cyan(),
+                samples.to_string().bright_green(),
+                format!("{:.1}", avg_tap).bright_blue(),
+                format!("{:.1}", threshold).bright_yellow()
+            );
+        }
+
+        println!("  └───────┴────────┴─────────┴──────────┘");
     }
 
-    println!("  └───────┴────────┴─────────┴──────────┘");
+    if !mt_stats.is_empty() {
+        println!();
+        println!(
+            "  {}",
+            "MT keys (misfires: hold meant tap / tap meant hold)".bright_white()
+        );
+        println!("  ┌───────┬────────┬──────────┬──────┬─────┬────────┐");
+        println!(
+            "  │ {:^5} │ {:^6} │ {:^8} │ {:^4} │ {:^3} │ {:^6} │",
+            "Key".bright_white().bold(),
+            "Samples".bright_white().bold(),
+            "Thresh(ms)".bright_white().bold(),
+            "Hold".bright_white().bold(),
+            "Tap".bright_white().bold(),
+            "Shift".bright_white().bold()
+        );
+        println!("  ├───────┼────────┼──────────┼──────┼─────┼────────┤");
+
+        for (keycode, key_stats) in mt_stats {
+            let key_name = format!("{:?}", keycode).replace("KC_", "");
+            println!(
+                "  │ {:^5} │ {:^6} │ {:^8} │ {:^4} │ {:^3} │ {:^6} │",
+                key_name.bright_cyan(),
+                key_stats.tap_sample_count.to_string().bright_green(),
+                format!("{:.1}", key_stats.adaptive_threshold).bright_yellow(),
+                key_stats.hold_misfires.to_string().bright_red(),
+                key_stats.tap_misfires.to_string().bright_red(),
+                format!("{:+.0}", key_stats.misfire_shift_ms()).bright_blue()
+            );
+        }
+
+        println!("  └───────┴────────┴──────────┴──────┴─────┴────────┘");
+    }
 
     println!(
         "{}",
@@ -619,21 +720,13 @@ fn validate_config(config_path: Option<&std::path::Path>) -> Result<()> {
 
     // Validation 2: Check timing values are reasonable
     print!("  {} Checking timing settings... ", "→".bright_blue());
-    if config.tapping_term_ms == 0 || config.tapping_term_ms > 1000 {
-        errors.push(format!(
-            "tapping_term_ms out of reasonable range (0-1000): {}",
-            config.tapping_term_ms
-        ));
-    }
-    // Validate MT config timing
-    let window = config.mt_config.double_tap_window_ms;
-    if window == 0 || window > 1000 {
-        errors.push(format!(
-            "mt_config.double_tap_window_ms out of reasonable range (0-1000): {}",
-            window
-        ));
+    let timing_errors = config.validate_timing();
+    if timing_errors.is_empty() {
+        println!("{}", "✓".bright_green().bold());
+    } else {
+        println!("{}", "✗".bright_red().bold());
+        errors.extend(timing_errors);
     }
-    println!("{}", "✓".bright_green().bold());
 
     // Validation 4: Check layer references
     print!("  {} Checking layer references... ", "→".bright_blue());
@@ -642,7 +735,7 @@ fn validate_config(config_path: Option<&std::path::Path>) -> Result<()> {
     let extract_layer_refs = |remaps: &HashMap<KeyCode, Action>| {
         let mut refs = Vec::new();
         for action in remaps.values() {
-            if let Action::TO(layer) = action {
+            for layer in action.referenced_layers() {
                 refs.push(layer.0.clone());
             }
         }
@@ -678,6 +771,98 @@ fn validate_config(config_path: Option<&std::path::Path>) -> Result<()> {
         }
     }
 
+    // Validation 5: Check combos
+    print!("  {} Checking combos... ", "→".bright_blue());
+    let combo_errors = config.validate_combos();
+    if combo_errors.is_empty() {
+        println!(
+            "{} {} combos",
+            "✓".bright_green().bold(),
+            config.combos.len()
+        );
+    } else {
+        println!("{}", "✗".bright_red().bold());
+        errors.extend(combo_errors);
+    }
+
+    // Validation 6: Check key overrides
+    print!("  {} Checking key overrides... ", "→".bright_blue());
+    let override_errors = config.validate_key_overrides();
+    if override_errors.is_empty() {
+        println!(
+            "{} {} key overrides",
+            "✓".bright_green().bold(),
+            config.key_overrides.len()
+        );
+    } else {
+        println!("{}", "✗".bright_red().bold());
+        errors.extend(override_errors);
+    }
+
+    // Validation 7: Check leader sequences
+    print!("  {} Checking leader sequences... ", "→".bright_blue());
+    let leader_errors = config.validate_leader_sequences();
+    if leader_errors.is_empty() {
+        println!(
+            "{} {} leader sequences",
+            "✓".bright_green().bold(),
+            config.leader_sequences.len()
+        );
+    } else {
+        println!("{}", "✗".bright_red().bold());
+        errors.extend(leader_errors);
+    }
+
+    // Validation 8: Check conditional layers
+    print!("  {} Checking conditional layers... ", "→".bright_blue());
+    let conditional_errors = config.validate_conditional_layers();
+    if conditional_errors.is_empty() {
+        println!(
+            "{} {} conditional layers",
+            "✓".bright_green().bold(),
+            config.conditional_layers.len()
+        );
+    } else {
+        println!("{}", "✗".bright_red().bold());
+        errors.extend(conditional_errors);
+    }
+
+    // Validation 9: Check per-key options
+    print!("  {} Checking per-key options... ", "→".bright_blue());
+    let key_option_errors = config.validate_key_options();
+    if key_option_errors.is_empty() {
+        println!("{}", "✓".bright_green().bold());
+    } else {
+        println!("{}", "✗".bright_red().bold());
+        errors.extend(key_option_errors);
+    }
+
+    // Validation 10: Check hands
+    print!("  {} Checking hands... ", "→".bright_blue());
+    let hand_errors = config.validate_hands();
+    if hand_errors.is_empty() {
+        println!("{} {} keys", "✓".bright_green().bold(), config.hands.len());
+    } else {
+        println!("{}", "✗".bright_red().bold());
+        errors.extend(hand_errors);
+    }
+    warnings.extend(
+        config
+            .hand_warnings()
+            .into_iter()
+            .map(|warning| format!("⚠️  {}", warning)),
+    );
+
+    // Validation 11: Check tap dances
+    print!("  {} Checking tap dances... ", "→".bright_blue());
+    let tap_dance_errors = config.validate_tap_dances();
+    if tap_dance_errors.is_empty() {
+        println!("{}", "✓".bright_green().bold());
+    } else {
+        println!("{}", "✗".bright_red().bold());
+        errors.extend(tap_dance_errors);
+    }
+
     // Print summary
     println!();
     println!(
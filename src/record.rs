@@ -0,0 +1,294 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use evdev::{EventType, Key};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::event_processor::recording::replay;
+use crate::event_processor::{
+    evdev_to_keycode, ProcessResult, RecordedEvent, Recording, RecordingHeader, RecordingWriter,
+};
+use crate::keyboard_id::{find_all_keyboards, LogicalKeyboard};
+
+/// Record raw key events of a keyboard to `output` until Ctrl+C
+pub fn run_record(keyboard: Option<&str>, output: &Path) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        "═══════════════════════════════════════".bright_cyan()
+    );
+    println!("  {}", "Record Typing Session".bright_cyan().bold());
+    println!(
+        "{}",
+        "═══════════════════════════════════════".bright_cyan()
+    );
+    println!();
+
+    let Some(mut keyboard) = select_keyboard(keyboard)? else {
+        return Ok(());
+    };
+
+    // The daemon grabs enabled keyboards, which hides their events from everyone else
+    for (path, device) in &mut keyboard.devices {
+        if device.grab().is_err() {
+            println!(
+                "  {} {} is grabbed by the daemon ({})",
+                "✗".bright_red().bold(),
+                keyboard.name.bright_white(),
+                path.display().to_string().dimmed()
+            );
+            println!(
+                "  {} Disable it with {} while recording",
+                "Tip:".bright_yellow().bold(),
+                "keymux toggle".bright_white()
+            );
+            println!();
+            return Ok(());
+        }
+        let _ = device.ungrab();
+
+        // Non-blocking reads so all event devices of the keyboard can be polled
+        let fd = device.as_raw_fd();
+        unsafe {
+            let flags = libc::fcntl(fd, libc::F_GETFL, 0);
+            libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+        }
+    }
+
+    let header = RecordingHeader {
+        keyboard: keyboard.name.clone(),
+        keyboard_id: keyboard.id.to_string(),
+    };
+    let mut writer = RecordingWriter::create(output, &header)?;
+
+    println!(
+        "  {} {}",
+        "Keyboard:".bright_yellow(),
+        keyboard.name.bright_white()
+    );
+    println!(
+        "  {} {}",
+        "Output:".bright_yellow(),
+        output.display().to_string().dimmed()
+    );
+    println!();
+    println!(
+        "  {}",
+        "Recording... type as usual, press Ctrl+C to stop".bright_green()
+    );
+    println!();
+
+    let mut started_at = None;
+    let mut last_us = 0;
+    let mut count = 0;
+    loop {
+        let mut idle = true;
+        for (_, device) in &mut keyboard.devices {
+            match device.fetch_events() {
+                Ok(events) => {
+                    for ev in events {
+                        if ev.event_type() != EventType::KEY {
+                            continue;
+                        }
+                        let Some(key) = evdev_to_keycode(Key::new(ev.code())) else {
+                            continue;
+                        };
+
+                        let started_at = *started_at.get_or_insert(ev.timestamp());
+                        let time_us = ev
+                            .timestamp()
+                            .duration_since(started_at)
+                            .unwrap_or_default()
+                            .as_micros() as u64;
+                        // Devices are polled in turn, so a later read can carry an earlier
+                        // timestamp: clamp to keep the recording monotonic for replay
+                        let time_us = time_us.max(last_us);
+                        last_us = time_us;
+                        writer.write(&RecordedEvent {
+                            time_us,
+                            key,
+                            value: ev.value(),
+                            intent: None,
+                        })?;
+
+                        count += 1;
+                        print!("\r  {} events recorded", count.to_string().bright_white());
+                        std::io::Write::flush(&mut std::io::stdout())?;
+                        idle = false;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if idle {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+}
+
+/// Pick the keyboard to record: by ID or name, the only one, or interactively
+fn select_keyboard(keyboard: Option<&str>) -> Result<Option<LogicalKeyboard>> {
+    let mut keyboards: Vec<LogicalKeyboard> = find_all_keyboards().into_values().collect();
+    keyboards.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if keyboards.is_empty() {
+        println!(
+            "  {} {}",
+            "✗".bright_red().bold(),
+            "No keyboards found!".red()
+        );
+        println!();
+        return Ok(None);
+    }
+
+    if let Some(wanted) = keyboard {
+        let wanted_lower = wanted.to_lowercase();
+        let index = keyboards.iter().position(|kb| {
+            kb.id.to_string() == wanted || kb.name.to_lowercase().contains(&wanted_lower)
+        });
+        let Some(index) = index else {
+            println!(
+                "  {} No keyboard matches {}",
+                "✗".bright_red().bold(),
+                wanted.bright_white()
+            );
+            println!(
+                "  {} Run {} to see detected keyboards",
+                "Tip:".bright_yellow().bold(),
+                "keymux list".bright_white()
+            );
+            println!();
+            return Ok(None);
+        };
+        return Ok(Some(keyboards.swap_remove(index)));
+    }
+
+    if keyboards.len() == 1 {
+        return Ok(keyboards.pop());
+    }
+
+    let names: Vec<&str> = keyboards.iter().map(|kb| kb.name.as_str()).collect();
+    let index = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt("Keyboard to record")
+        .items(&names)
+        .default(0)
+        .interact()?;
+    println!();
+    Ok(Some(keyboards.swap_remove(index)))
+}
+
+/// Replay a recording through the keymap and print what it emits
+pub fn run_replay(file: &Path, config_path: Option<&Path>) -> Result<()> {
+    let recording = Recording::load(file)?;
+
+    let config_path = match config_path {
+        Some(path) => path.to_path_buf(),
+        None => Config::default_path()?,
+    };
+    let config = load_config(&config_path)?.for_keyboard(&recording.header.keyboard_id);
+
+    println!();
+    println!(
+        "  {} {} {}",
+        "Recording:".bright_yellow(),
+        file.display().to_string().bright_white(),
+        format!(
+            "({}, {} events, {:.1}s)",
+            recording.header.keyboard,
+            recording.events.len(),
+            recording.duration().as_secs_f64()
+        )
+        .dimmed()
+    );
+    println!(
+        "  {} {}",
+        "Config:".bright_yellow(),
+        config_path.display().to_string().dimmed()
+    );
+    println!();
+
+    let mut output_count = 0;
+    for step in replay(&recording, &config) {
+        let time = format!("{:>9.3}s", step.time_us as f64 / 1_000_000.0);
+        match step.input {
+            Some(event) => {
+                let input = format!("{:?} {}", event.key, value_arrow(event.value));
+                println!("  {}  {}", time.dimmed(), input.dimmed());
+            }
+            None => println!("  {}  {}", time.dimmed(), "(timeout)".dimmed()),
+        }
+
+        let mut outputs = Vec::new();
+        describe_result(&step.result, &mut outputs);
+        for output in outputs {
+            println!(
+                "  {}  {} {}",
+                " ".repeat(time.len()),
+                "→".bright_blue(),
+                output
+            );
+            output_count += 1;
+        }
+    }
+
+    println!();
+    println!(
+        "  {} {} input events, {} output events",
+        "Done:".bright_green().bold(),
+        recording.events.len(),
+        output_count
+    );
+    println!();
+
+    Ok(())
+}
+
+/// Load and validate a config for offline use
+pub fn load_config(path: &Path) -> Result<Config> {
+    let config =
+        Config::load(path).with_context(|| format!("Failed to load config {}", path.display()))?;
+    config.validate_silent()?;
+    Ok(config)
+}
+
+const fn value_arrow(value: i32) -> &'static str {
+    match value {
+        0 => "↑",
+        1 => "↓",
+        _ => "⟳",
+    }
+}
+
+/// Human-readable output events of a keymap result
+fn describe_result(result: &ProcessResult, lines: &mut Vec<String>) {
+    match result {
+        ProcessResult::EmitKey(key, pressed) => {
+            lines.push(format!("{key:?} {}", value_arrow(i32::from(*pressed))));
+        }
+        ProcessResult::RepeatKey(key) => lines.push(format!("{key:?} {}", value_arrow(2))),
+        ProcessResult::MultipleEvents(events) => {
+            for (key, pressed) in events {
+                lines.push(format!("{key:?} {}", value_arrow(i32::from(*pressed))));
+            }
+        }
+        ProcessResult::TypeString(text, add_enter) => {
+            let enter = if *add_enter { " + Enter" } else { "" };
+            lines.push(format!("type {text:?}{enter}"));
+        }
+        ProcessResult::RunCommand(command) => lines.push(format!("run {command:?}")),
+        ProcessResult::Mouse(events) => {
+            for event in events {
+                lines.push(format!("mouse {event:?}"));
+            }
+        }
+        ProcessResult::Batch(results) => {
+            for result in results {
+                describe_result(result, lines);
+            }
+        }
+        ProcessResult::None => {}
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::event_processor::tuning::{tune, TuneReport, TuningSession};
+use crate::event_processor::Recording;
+use crate::record::load_config;
+
+/// Recommend MT settings from recorded typing sessions, optionally writing them
+pub fn run_tune(files: &[PathBuf], config_path: Option<&Path>, write: bool) -> Result<()> {
+    println!();
+    println!(
+        "{}",
+        "═══════════════════════════════════════".bright_cyan()
+    );
+    println!("  {}", "MT Tuning".bright_cyan().bold());
+    println!(
+        "{}",
+        "═══════════════════════════════════════".bright_cyan()
+    );
+    println!();
+
+    let config_path = match config_path {
+        Some(path) => path.to_path_buf(),
+        None => Config::default_path()?,
+    };
+    print!("  {} Loading config... ", "→".bright_blue());
+    let config = load_config(&config_path)?;
+    println!("{}", "✓".bright_green());
+
+    print!("  {} Finding intended MT decisions... ", "→".bright_blue());
+    let mut sessions = Vec::new();
+    for file in files {
+        let recording = Recording::load(file)?;
+        sessions.push(TuningSession::new(recording, &config));
+    }
+    println!("{}", "✓".bright_green());
+
+    // Sessions are tuned against one effective config
+    let Some(first) = sessions.first() else {
+        return Ok(());
+    };
+    if sessions
+        .iter()
+        .any(|session| session.config() != first.config())
+    {
+        println!();
+        println!(
+            "  {} Recordings are from keyboards with different settings - tune them separately",
+            "✗".bright_red().bold()
+        );
+        println!();
+        return Ok(());
+    }
+    let effective = first.config().clone();
+    let keyboard_id = first.keyboard_id().to_string();
+
+    let presses: usize = sessions.iter().map(TuningSession::presses).sum();
+    let labeled: usize = sessions.iter().map(|session| session.labeled).sum();
+    let corrected: usize = sessions.iter().map(|session| session.corrected).sum();
+    if presses == 0 {
+        println!();
+        println!(
+            "  {} No MT key presses in the recordings",
+            "!".bright_yellow()
+        );
+        println!();
+        return Ok(());
+    }
+    println!(
+        "    {} MT presses ({} labeled, {} corrected)",
+        presses.to_string().bright_white(),
+        labeled,
+        corrected
+    );
+    if labeled < presses {
+        println!(
+            "    {}",
+            "Unlabeled presses count as meant the way the current config decided them".dimmed()
+        );
+        println!(
+            "    {}",
+            "(unless corrected), so results lean toward the current settings".dimmed()
+        );
+    }
+
+    let report = tune(&effective, &sessions, |param| {
+        println!("  {} Sweeping {}...", "→".bright_blue(), param.field());
+    });
+    print_report(&report, presses);
+
+    if report.tuning.global.is_empty() && report.tuning.per_key.is_empty() {
+        println!(
+            "  {} Current settings are already the best",
+            "✓".bright_green()
+        );
+        println!();
+        return Ok(());
+    }
+
+    if write {
+        write_tuning(&config_path, &config, &keyboard_id, &report)?;
+    } else {
+        println!(
+            "  {} Run again with {} to save these to the config",
+            "Tip:".bright_yellow().bold(),
+            "--write".bright_white()
+        );
+        println!();
+    }
+
+    Ok(())
+}
+
+fn print_report(report: &TuneReport, presses: usize) {
+    println!();
+    println!("  {}", "Settings:".bright_yellow().bold());
+    for result in &report.params {
+        let field = format!("{:<28}", result.param.field());
+        let current = result.param.format(result.current);
+        let best = result.param.format(result.best);
+        let mismatches = format!(
+            "({} → {} mismatches)",
+            result.mismatches(result.current).unwrap_or(0),
+            result.mismatches(result.best).unwrap_or(0)
+        );
+        if result.best == result.current {
+            println!(
+                "    {} {} {}",
+                field,
+                current.bright_white(),
+                "(keep)".dimmed()
+            );
+        } else if result.param.is_shared() {
+            // DT, TD and OSM read the global value too, MT keys get theirs per key
+            println!(
+                "    {} {} {}",
+                field,
+                current.bright_white(),
+                format!("(kept for DT/TD/OSM, MT keys → {best} per key {mismatches})").dimmed()
+            );
+        } else {
+            println!(
+                "    {} {} → {} {}",
+                field,
+                current.dimmed(),
+                best.bright_green(),
+                mismatches.dimmed()
+            );
+        }
+    }
+
+    let per_key: Vec<_> = report
+        .params
+        .iter()
+        .flat_map(|result| {
+            result
+                .per_key
+                .iter()
+                .map(move |entry| (result.param, entry))
+        })
+        .collect();
+    if !per_key.is_empty() {
+        println!();
+        println!("  {}", "Per-key:".bright_yellow().bold());
+        for (param, &(keycode, value, before, after)) in per_key {
+            println!(
+                "    {:<8} {}: {} {}",
+                format!("{keycode:?}"),
+                param.field(),
+                param.format(value).bright_green(),
+                format!("({before} → {after} mismatches)").dimmed()
+            );
+        }
+    }
+
+    println!();
+    println!(
+        "  {} {} → {} of {} MT decisions differ from what was meant",
+        "Overall:".bright_white().bold(),
+        report.before.total,
+        report.after.total.to_string().bright_green(),
+        presses
+    );
+    println!();
+}
+
+/// Write the recommended settings into the config file, keeping its formatting
+/// Settings the keyboard's per-keyboard override sets are written there
+fn write_tuning(
+    config_path: &Path,
+    config: &Config,
+    keyboard_id: &str,
+    report: &TuneReport,
+) -> Result<()> {
+    let content = std::fs::read_to_string(config_path)?;
+    let edited = Config::edit_text(&content, &report.tuning.edits(config, keyboard_id));
+
+    // Only write if the edited file gives the keyboard exactly the tuned config
+    let effective = |config: &Config| {
+        let mut effective = config.for_keyboard(keyboard_id);
+        effective.per_keyboard_overrides.clear();
+        effective
+    };
+    let mut expected = effective(config);
+    report.tuning.apply(&mut expected);
+    let verified = edited
+        .filter(|edited| Config::parse(edited).is_ok_and(|parsed| effective(&parsed) == expected));
+    let Some(edited) = verified else {
+        println!(
+            "  {} Couldn't update {} in place - add the settings above by hand",
+            "✗".bright_red().bold(),
+            config_path.display()
+        );
+        println!();
+        return Ok(());
+    };
+
+    let backup = config_path.with_extension("ron.bak");
+    std::fs::copy(config_path, &backup)
+        .with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+    std::fs::write(config_path, edited)?;
+
+    println!(
+        "  {} Saved to {} {}",
+        "✓".bright_green(),
+        config_path.display().to_string().bright_white(),
+        format!("(backup: {})", backup.display()).dimmed()
+    );
+    println!();
+    Ok(())
+}